@@ -0,0 +1,51 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use m3u_parser::M3uParser;
+
+const COUNTRIES: [&str; 5] = ["GB", "US", "NP", "FR", "DE"];
+const LANGUAGES: [&str; 5] = ["English", "English", "Nepali", "French", "German"];
+const CATEGORIES: [&str; 4] = ["News", "Sports", "Movies", "Music"];
+
+/// Builds a playlist with `entries` entries, repeating a small set of countries, languages and
+/// categories as real-world playlists do.
+fn synthetic_playlist(entries: usize) -> String {
+    let mut content = String::from("#EXTM3U\n");
+    for i in 0..entries {
+        let j = i % COUNTRIES.len();
+        content.push_str(&format!(
+            "#EXTINF:-1 tvg-id=\"channel{i}.{country}\" tvg-name=\"Channel {i}\" \
+             tvg-logo=\"https://example.com/logos/{i}.png\" tvg-country=\"{country}\" \
+             tvg-language=\"{language}\" group-title=\"{category}\",Channel {i}\n\
+             https://example.com/streams/{i}/index.m3u8\n",
+            country = COUNTRIES[j],
+            language = LANGUAGES[j],
+            category = CATEGORIES[i % CATEGORIES.len()],
+        ));
+    }
+    content
+}
+
+fn parse_throughput(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+
+    for entries in [10_000, 100_000, 1_000_000] {
+        let content = synthetic_playlist(entries);
+        group.throughput(Throughput::Elements(entries as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(entries),
+            &content,
+            |b, content| {
+                b.iter(|| {
+                    let mut parser = M3uParser::new(None);
+                    runtime.block_on(parser.parse_m3u_content(content, false, true));
+                    parser.streams_info.len()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_throughput);
+criterion_main!(benches);
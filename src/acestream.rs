@@ -0,0 +1,76 @@
+use reqwest::Client;
+use serde_json::Value;
+use std::time::Instant;
+
+use crate::cache::unix_now;
+use crate::{CheckErrorKind, CheckResult};
+
+/// Client of the HTTP API of a local Ace Stream engine, used to check acestream links.
+pub(crate) struct AceStreamEngine {
+    client: Client,
+    url: String,
+}
+
+impl AceStreamEngine {
+    /// Connects to the engine listening at `url`, e.g. "http://127.0.0.1:6878", and checks that
+    /// it answers.
+    pub(crate) async fn connect(client: Client, url: &str) -> Result<Self, String> {
+        let engine = AceStreamEngine {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+        };
+        engine.version().await?;
+        Ok(engine)
+    }
+
+    /// Returns the version of the engine, or an error if the engine does not answer.
+    async fn version(&self) -> Result<String, String> {
+        let url = format!("{}/webui/api/service?method=get_version", self.url);
+        let body = self.get_json(&url).await?;
+        body["result"]["version"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| String::from("Unexpected answer of the Ace Stream engine"))
+    }
+
+    /// Checks whether the engine can start the content of the acestream link.
+    ///
+    /// The playback session started by the check is stopped right away.
+    pub(crate) async fn check(&self, link: &str) -> CheckResult {
+        let started = Instant::now();
+        let id = link.trim_start_matches("acestream://");
+        let url = format!("{}/ace/getstream?id={}&format=json", self.url, id);
+        let answer = self.get_json(&url).await;
+        let mut result = CheckResult {
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            checked_at: unix_now(),
+            ..Default::default()
+        };
+        match answer {
+            Ok(body) => match body["error"].as_str() {
+                Some(error) => {
+                    result.error = Some(error.to_string());
+                    result.error_kind = Some(CheckErrorKind::Other);
+                }
+                None => {
+                    result.available = true;
+                    if let Some(command_url) = body["response"]["command_url"].as_str() {
+                        let stop = format!("{}?method=stop", command_url);
+                        let _ = self.client.get(stop).send().await;
+                    }
+                }
+            },
+            Err(e) => {
+                result.error = Some(e);
+                result.error_kind = Some(CheckErrorKind::Other);
+            }
+        }
+        result
+    }
+
+    async fn get_json(&self, url: &str) -> Result<Value, String> {
+        let response = self.client.get(url).send().await;
+        let response = response.map_err(|e| e.to_string())?;
+        response.json().await.map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,174 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::html::escape;
+use crate::{title_from_location, Info};
+
+static ASX_ENTRY_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<entry(?:ref)?\b(.*?)(?:/>|</entry>)").unwrap());
+static ASX_TITLE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<title>(.*?)</title>").unwrap());
+static HREF_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?is)\bhref\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+static WPL_MEDIA_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?is)<media\b[^>]*?\bsrc\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// Decodes the predefined XML entities of the text.
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Returns the value of the first capture group that matched, of the double or single quoted
+/// attribute.
+fn attribute_value(captures: &regex::Captures) -> String {
+    let value = captures.get(1).or_else(|| captures.get(2));
+    unescape(value.map(|value| value.as_str()).unwrap_or_default().trim())
+}
+
+/// Converts an ASX or WPL playlist of Windows Media Player into an extended M3U playlist, or
+/// returns `None` if the content is neither.
+///
+/// The title of an ASX entry is kept, while the entries of a WPL playlist, which have no title,
+/// are named after their file.
+pub(crate) fn to_m3u(content: &str) -> Option<String> {
+    let start = content.trim_start().get(..5)?.to_lowercase();
+    let entries: Vec<(String, String)> = if start == "<asx " || start == "<asx>" {
+        ASX_ENTRY_REGEX
+            .captures_iter(content)
+            .filter_map(|entry| {
+                let entry = &entry[1];
+                let location = attribute_value(&HREF_REGEX.captures(entry)?);
+                let title = ASX_TITLE_REGEX
+                    .captures(entry)
+                    .map(|title| unescape(title[1].trim()))
+                    .unwrap_or_else(|| title_from_location(&location));
+                Some((title, location))
+            })
+            .collect()
+    } else if start == "<?wpl" || start == "<smil" {
+        WPL_MEDIA_REGEX
+            .captures_iter(content)
+            .map(|media| {
+                let location = attribute_value(&media);
+                (title_from_location(&location), location)
+            })
+            .collect()
+    } else {
+        return None;
+    };
+
+    let mut lines = vec![String::from("#EXTM3U")];
+    for (title, location) in entries {
+        // Commas and line breaks would end the title of the `#EXTINF` line early.
+        let title = title.replace([',', '\n'], " ");
+        lines.push(format!("#EXTINF:-1,{}", title));
+        lines.push(location);
+    }
+    Some(lines.join("\n"))
+}
+
+/// Renders the stream information as an ASX playlist, with the title and URL of each stream.
+pub(crate) fn render_asx(streams_info: &[Info]) -> String {
+    let mut lines = vec![String::from("<asx version=\"3.0\">")];
+    for info in streams_info {
+        lines.push(String::from("  <entry>"));
+        if !info.title.is_empty() {
+            lines.push(format!("    <title>{}</title>", escape(&info.title)));
+        }
+        lines.push(format!("    <ref href=\"{}\"/>", escape(&info.url)));
+        lines.push(String::from("  </entry>"));
+    }
+    lines.push(String::from("</asx>"));
+    lines.join("\n") + "\n"
+}
+
+/// Renders the stream information as a WPL playlist, with the URL of each stream.
+pub(crate) fn render_wpl(streams_info: &[Info]) -> String {
+    let mut lines = vec![
+        String::from("<?wpl version=\"1.0\"?>"),
+        String::from("<smil>"),
+        String::from("  <head>"),
+        String::from("    <meta name=\"Generator\" content=\"m3u_parser\"/>"),
+        format!(
+            "    <meta name=\"ItemCount\" content=\"{}\"/>",
+            streams_info.len()
+        ),
+        String::from("  </head>"),
+        String::from("  <body>"),
+        String::from("    <seq>"),
+    ];
+    for info in streams_info {
+        lines.push(format!("      <media src=\"{}\"/>", escape(&info.url)));
+    }
+    lines.extend(["    </seq>", "  </body>", "</smil>"].map(String::from));
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_asx, render_wpl, to_m3u};
+    use crate::Info;
+
+    #[test]
+    fn test_to_m3u() {
+        let asx = r#"<ASX version="3.0">
+  <Title>Radios</Title>
+  <Entry>
+    <Title>Jazz &amp; Blues, Live</Title>
+    <Ref HREF="http://example.com/jazz?a=1&amp;b=2"/>
+  </Entry>
+  <Entry><Ref href='mms://example.com/news.wmv'/></Entry>
+  <EntryRef href="http://example.com/more.asx"/>
+</ASX>"#;
+        assert_eq!(
+            to_m3u(asx).unwrap(),
+            "#EXTM3U\n#EXTINF:-1,Jazz & Blues  Live\nhttp://example.com/jazz?a=1&b=2\n\
+             #EXTINF:-1,news\nmms://example.com/news.wmv\n\
+             #EXTINF:-1,more\nhttp://example.com/more.asx"
+        );
+
+        let wpl = r#"<?wpl version="1.0"?>
+<smil><body><seq>
+  <media src="C:\Music\Song One.mp3"/>
+  <media src="http://example.com/two.mp3" tid="{1}"/>
+</seq></body></smil>"#;
+        assert_eq!(
+            to_m3u(wpl).unwrap(),
+            "#EXTM3U\n#EXTINF:-1,Song One\nC:\\Music\\Song One.mp3\n\
+             #EXTINF:-1,two\nhttp://example.com/two.mp3"
+        );
+
+        assert!(to_m3u("#EXTM3U\n#EXTINF:-1,A\nhttp://example.com/a.m3u8").is_none());
+        assert!(to_m3u("<a").is_none());
+    }
+
+    #[test]
+    fn test_render() {
+        let streams_info = vec![Info {
+            title: String::from("Jazz & Blues"),
+            url: String::from("http://example.com/jazz?a=1&b=2"),
+            ..Default::default()
+        }];
+        let asx = render_asx(&streams_info);
+        assert_eq!(
+            asx,
+            "<asx version=\"3.0\">\n  <entry>\n    <title>Jazz &amp; Blues</title>\n    \
+             <ref href=\"http://example.com/jazz?a=1&amp;b=2\"/>\n  </entry>\n</asx>\n"
+        );
+        assert_eq!(
+            to_m3u(&asx).unwrap(),
+            "#EXTM3U\n#EXTINF:-1,Jazz & Blues\nhttp://example.com/jazz?a=1&b=2"
+        );
+
+        let wpl = render_wpl(&streams_info);
+        assert!(wpl.contains("<media src=\"http://example.com/jazz?a=1&amp;b=2\"/>"));
+        assert!(to_m3u(&wpl)
+            .unwrap()
+            .ends_with("\nhttp://example.com/jazz?a=1&b=2"));
+    }
+}
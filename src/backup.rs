@@ -0,0 +1,119 @@
+use std::fs::{read_dir, remove_file, rename};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cache::{unix_now, utc_date_time};
+
+/// Formats seconds since the Unix epoch as `YYYYMMDD-HHMMSS`, in UTC.
+pub(crate) fn timestamp(secs: u64) -> String {
+    let [year, month, day, hour, minute, second] = utc_date_time(secs);
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// Moves the existing file at `path` to `name.YYYYMMDD-HHMMSS.bak` next to it, then removes the
+/// oldest backups of the file beyond the `keep` most recent ones.
+///
+/// # Returns
+///
+/// A `std::io::Result` with the path of the backup, or `None` if there was no file to back up.
+///
+pub(crate) fn rotate(path: &Path, keep: usize) -> io::Result<Option<PathBuf>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let file_name = match path.file_name() {
+        Some(file_name) => file_name.to_string_lossy().to_string(),
+        None => return Ok(None),
+    };
+    let backup = path.with_file_name(format!("{}.{}.bak", file_name, timestamp(unix_now())));
+    rename(path, &backup)?;
+
+    let directory = match path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => Path::new("."),
+    };
+    let mut backups: Vec<PathBuf> = read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|candidate| {
+            candidate
+                .file_name()
+                .map(|name| is_backup_of(&name.to_string_lossy(), &file_name))
+                .unwrap_or(false)
+        })
+        .collect();
+    // The timestamps sort chronologically, so the most recent backups come last.
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep.max(1));
+    for old in &backups[..excess] {
+        remove_file(old)?;
+    }
+    Ok(Some(backup))
+}
+
+/// Returns whether `name` is a backup of `file_name` made by `rotate`.
+fn is_backup_of(name: &str, file_name: &str) -> bool {
+    let stamp = name
+        .strip_prefix(file_name)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .and_then(|rest| rest.strip_suffix(".bak"));
+    match stamp {
+        Some(stamp) => {
+            stamp.len() == 15
+                && stamp
+                    .char_indices()
+                    .all(|(i, c)| if i == 8 { c == '-' } else { c.is_ascii_digit() })
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_backup_of, rotate, timestamp};
+    use std::fs;
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(0), "19700101-000000");
+        assert_eq!(timestamp(951_782_400), "20000229-000000");
+        assert_eq!(timestamp(1_700_000_000), "20231114-221320");
+    }
+
+    #[test]
+    fn test_rotate() {
+        let directory = std::env::temp_dir().join("m3u_parser_backups");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("playlist.m3u");
+        assert_eq!(rotate(&path, 2).unwrap(), None);
+
+        for stamp in ["20240101-000000", "20240102-000000"] {
+            fs::write(directory.join(format!("playlist.m3u.{}.bak", stamp)), "old").unwrap();
+        }
+        fs::write(directory.join("other.m3u.20240101-000000.bak"), "other").unwrap();
+        fs::write(&path, "current").unwrap();
+        let backup = rotate(&path, 2).unwrap().unwrap();
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "current");
+
+        let mut names: Vec<String> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names[0], "other.m3u.20240101-000000.bak");
+        assert_eq!(names[1], "playlist.m3u.20240102-000000.bak");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_is_backup_of() {
+        assert!(is_backup_of("a.m3u.20240101-000000.bak", "a.m3u"));
+        assert!(!is_backup_of("a.m3u.bak", "a.m3u"));
+        assert!(!is_backup_of("b.m3u.20240101-000000.bak", "a.m3u"));
+    }
+}
@@ -0,0 +1,90 @@
+use reqwest::Client;
+use std::time::{Duration, Instant};
+use url::Url;
+
+use crate::{HlsMasterPlaylist, HlsMediaPlaylist};
+
+/// Downloader of the first seconds of a stream, measuring the observed throughput.
+pub(crate) struct BitrateSampler<'a> {
+    pub(crate) client: &'a Client,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) duration: Duration,
+}
+
+impl BitrateSampler<'_> {
+    /// Downloads the stream for the sampling duration and returns its throughput in kbit/s, or
+    /// `None` if nothing could be downloaded.
+    ///
+    /// The segments of HLS streams are downloaded in order, from the first variant of a master
+    /// playlist, until the duration is elapsed.
+    pub(crate) async fn sample(&self, url: &str) -> Option<u64> {
+        let started = Instant::now();
+        let mut response = self.get(url).await?;
+        let mut bytes = 0;
+        // The first bytes, telling HLS playlists from media.
+        let mut head = vec![];
+        while let Ok(Some(chunk)) = response.chunk().await {
+            bytes += chunk.len();
+            if head.len() < 7 {
+                head.extend_from_slice(&chunk);
+            }
+            if head.starts_with(b"#EXTM3U") {
+                let mut content = head;
+                while let Ok(Some(chunk)) = response.chunk().await {
+                    content.extend_from_slice(&chunk);
+                }
+                let content = String::from_utf8_lossy(&content).to_string();
+                return self.sample_hls(url, &content).await;
+            }
+            if started.elapsed() >= self.duration {
+                break;
+            }
+        }
+        throughput(bytes, started.elapsed())
+    }
+
+    async fn sample_hls(&self, url: &str, content: &str) -> Option<u64> {
+        let mut url = Url::parse(url).ok()?;
+        let mut media = HlsMediaPlaylist::parse(content);
+        if media.segments.is_empty() {
+            let master = HlsMasterPlaylist::parse(content);
+            url = url.join(&master.variants.first()?.uri).ok()?;
+            let content = self.get(url.as_str()).await?.text().await.ok()?;
+            media = HlsMediaPlaylist::parse(&content);
+        }
+        // The download of the playlists is not part of the throughput.
+        let mut elapsed = Duration::ZERO;
+        let mut bytes = 0;
+        for segment in &media.segments {
+            if elapsed >= self.duration {
+                break;
+            }
+            let started = Instant::now();
+            let Some(segment_url) = url.join(&segment.uri).ok() else {
+                continue;
+            };
+            if let Some(response) = self.get(segment_url.as_str()).await {
+                if let Ok(content) = response.bytes().await {
+                    bytes += content.len();
+                }
+            }
+            elapsed += started.elapsed();
+        }
+        throughput(bytes, elapsed)
+    }
+
+    async fn get(&self, url: &str) -> Option<reqwest::Response> {
+        let mut request = self.client.get(url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await.ok()?;
+        response.status().is_success().then_some(response)
+    }
+}
+
+/// Returns the throughput in kbit/s of downloading the bytes in the elapsed time.
+fn throughput(bytes: usize, elapsed: Duration) -> Option<u64> {
+    let millis = elapsed.as_millis().max(1) as u64;
+    (bytes > 0).then(|| bytes as u64 * 8 / millis)
+}
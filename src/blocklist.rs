@@ -0,0 +1,174 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::read_to_string;
+
+use crate::search::normalize;
+use crate::Info;
+
+/// Struct representing a list of blocked streams, e.g. for parental control, loadable from TOML or
+/// JSON.
+///
+/// ```toml
+/// keywords = ["xxx", "adult"]
+/// categories = ["Adult", "XXX"]
+/// patterns = ["(?i)porn"]
+/// tag = "blocked"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Blocklist {
+    /// Words blocking the streams whose title, `tvg-name` or category contains them as whole
+    /// words, ignoring case and accents.
+    pub keywords: Vec<String>,
+    /// Categories blocking the streams in any of them, ignoring case and accents.
+    pub categories: Vec<String>,
+    /// Regular expressions blocking the streams whose title, `tvg-name`, category or URL matches
+    /// them.
+    pub patterns: Vec<String>,
+    /// The tag added to the blocked streams, or `None` to remove them.
+    pub tag: Option<String>,
+}
+
+/// A blocklist with its keywords normalized and its regular expressions compiled.
+pub(crate) struct CompiledBlocklist {
+    keywords: Vec<String>,
+    categories: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl CompiledBlocklist {
+    /// Returns whether the stream is blocked.
+    pub(crate) fn blocks(&self, info: &Info) -> bool {
+        let fields = [&info.title, &info.tvg.name, &info.category];
+        let normalized: Vec<String> = fields.iter().map(|field| normalize(field)).collect();
+        let blocked_keyword = self
+            .keywords
+            .iter()
+            .any(|keyword| normalized.iter().any(|field| contains_word(field, keyword)));
+        let blocked_category = info
+            .categories
+            .iter()
+            .chain([&info.category])
+            .any(|category| self.categories.contains(&normalize(category)));
+        let blocked_pattern = self.patterns.iter().any(|regex| {
+            fields
+                .iter()
+                .chain([&&info.url])
+                .any(|field| regex.is_match(field))
+        });
+        blocked_keyword || blocked_category || blocked_pattern
+    }
+}
+
+/// Returns whether the word appears in the text, not preceded or followed by a letter or digit.
+pub(crate) fn contains_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+impl Blocklist {
+    /// Parses a blocklist from JSON, e.g. `{"keywords": ["xxx"], "categories": ["Adult"]}`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the JSON is invalid or a pattern is not a valid regular
+    /// expression.
+    ///
+    pub fn from_json(content: &str) -> Result<Self, Box<dyn Error>> {
+        let blocklist: Blocklist = serde_json::from_str(content)?;
+        blocklist.compile()?;
+        Ok(blocklist)
+    }
+
+    /// Parses a blocklist from TOML.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the TOML is invalid or a pattern is not a valid regular
+    /// expression.
+    ///
+    pub fn from_toml(content: &str) -> Result<Self, Box<dyn Error>> {
+        let blocklist: Blocklist = toml::from_str(content)?;
+        blocklist.compile()?;
+        Ok(blocklist)
+    }
+
+    /// Loads a blocklist from a file, parsed as JSON if its extension is ".json" and as TOML
+    /// otherwise.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = read_to_string(path)?;
+        if path.to_lowercase().ends_with(".json") {
+            Blocklist::from_json(&content)
+        } else {
+            Blocklist::from_toml(&content)
+        }
+    }
+
+    /// Normalizes the keywords and categories and compiles the regular expressions.
+    pub(crate) fn compile(&self) -> Result<CompiledBlocklist, String> {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| format!("Blocklist: invalid pattern: {}", e))
+            })
+            .collect::<Result<_, String>>()?;
+        let normalize_all = |values: &[String]| -> Vec<String> {
+            values
+                .iter()
+                .map(|value| normalize(value.trim()))
+                .filter(|value| !value.is_empty())
+                .collect()
+        };
+        Ok(CompiledBlocklist {
+            keywords: normalize_all(&self.keywords),
+            categories: normalize_all(&self.categories),
+            patterns,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contains_word, Blocklist};
+    use crate::Info;
+
+    #[test]
+    fn test_blocklist() {
+        let blocklist = Blocklist::from_toml(
+            r#"
+            keywords = ["XXX"]
+            categories = ["Adulte"]
+            patterns = ["/adult/"]
+            "#,
+        )
+        .unwrap();
+        assert!(blocklist.tag.is_none());
+        let compiled = blocklist.compile().unwrap();
+
+        let info = |title: &str, category: &str, url: &str| Info {
+            title: title.to_string(),
+            category: category.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        };
+        assert!(compiled.blocks(&info("Hot XXX", "", "")));
+        assert!(!compiled.blocks(&info("Maxxxine", "", "")));
+        assert!(compiled.blocks(&info("Night", "ADULTÉ", "")));
+        assert!(compiled.blocks(&info("Night", "", "http://example.com/adult/1.m3u8")));
+        assert!(!compiled.blocks(&info("Cartoons", "Kids", "http://example.com/kids.m3u8")));
+
+        assert!(Blocklist::from_json(r#"{"patterns": ["("]}"#).is_err());
+    }
+
+    #[test]
+    fn test_contains_word() {
+        assert!(contains_word("hot xxx tv", "xxx"));
+        assert!(contains_word("xxx", "xxx"));
+        assert!(!contains_word("maxxxine", "xxx"));
+    }
+}
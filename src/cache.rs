@@ -0,0 +1,155 @@
+#[cfg(feature = "net")]
+use std::collections::HashMap;
+#[cfg(feature = "net")]
+use std::fs::{read_to_string, File};
+#[cfg(feature = "net")]
+use std::io::Write;
+#[cfg(feature = "net")]
+use std::path::PathBuf;
+#[cfg(feature = "net")]
+use std::sync::Mutex;
+#[cfg(feature = "net")]
+use std::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm-bindgen")))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "net")]
+use crate::CheckResult;
+
+/// On-disk cache of liveness check results, keyed by URL and stored as JSON.
+#[cfg(feature = "net")]
+#[derive(Debug)]
+pub struct LivenessCache {
+    path: PathBuf,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CheckResult>>,
+}
+
+#[cfg(feature = "net")]
+impl LivenessCache {
+    /// Loads the cache from the given file. A missing or unreadable file gives an empty cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the cache file.
+    /// * `ttl` - How long a check result stays valid.
+    ///
+    pub fn load(path: &str, ttl: Duration) -> Self {
+        let entries = match read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid liveness cache {}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        LivenessCache {
+            path: PathBuf::from(path),
+            ttl,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Returns the cached result for the URL if it was checked within the TTL.
+    pub fn get(&self, url: &str) -> Option<CheckResult> {
+        let now = unix_now();
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(url)
+            .filter(|entry| now.saturating_sub(entry.checked_at) < self.ttl.as_secs())
+            .cloned()
+    }
+
+    /// Records the result of checking the URL.
+    pub fn insert(&self, url: &str, result: &CheckResult) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), result.clone());
+    }
+
+    /// Writes the cache back to its file, dropping the expired results.
+    pub fn save(&self) -> std::io::Result<()> {
+        let now = unix_now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| now.saturating_sub(entry.checked_at) < self.ttl.as_secs());
+        let content = serde_json::to_string(&*entries).map_err(std::io::Error::from)?;
+        File::create(&self.path)?.write_all(content.as_bytes())
+    }
+}
+
+/// Returns the number of seconds since the Unix epoch.
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm-bindgen")))]
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Returns the number of seconds since the Unix epoch, from the clock of JavaScript since
+/// `SystemTime::now` panics on wasm32-unknown-unknown.
+#[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+pub(crate) fn unix_now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+/// Splits seconds since the Unix epoch into the UTC year, month, day, hour, minute and second.
+pub(crate) fn utc_date_time(secs: u64) -> [u64; 6] {
+    let (days, time) = (secs / 86400, secs % 86400);
+    // Converts days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    [year, month, day, time / 3600, time % 3600 / 60, time % 60]
+}
+
+/// Formats seconds since the Unix epoch as an RFC 3339 UTC timestamp, e.g.
+/// "2023-11-14T22:13:20Z".
+pub(crate) fn format_utc(secs: u64) -> String {
+    let [year, month, day, hour, minute, second] = utc_date_time(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+#[cfg(all(test, feature = "net"))]
+mod tests {
+    use super::{unix_now, LivenessCache};
+    use crate::CheckResult;
+    use std::time::Duration;
+
+    #[test]
+    fn test_liveness_cache() {
+        let path = std::env::temp_dir().join("m3u_parser_liveness_cache.json");
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let cache = LivenessCache::load(path, Duration::from_secs(3600));
+        assert_eq!(cache.get("http://example.com/a.m3u8"), None);
+        let result = |available| CheckResult {
+            available,
+            checked_at: unix_now(),
+            ..Default::default()
+        };
+        cache.insert("http://example.com/a.m3u8", &result(true));
+        cache.insert("http://example.com/b.m3u8", &result(false));
+        cache.save().unwrap();
+
+        let cache = LivenessCache::load(path, Duration::from_secs(3600));
+        let available = |url| cache.get(url).map(|result| result.available);
+        assert_eq!(available("http://example.com/a.m3u8"), Some(true));
+        assert_eq!(available("http://example.com/b.m3u8"), Some(false));
+
+        let cache = LivenessCache::load(path, Duration::ZERO);
+        assert_eq!(cache.get("http://example.com/a.m3u8"), None);
+        std::fs::remove_file(path).unwrap();
+    }
+}
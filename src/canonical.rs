@@ -0,0 +1,76 @@
+use url::Url;
+
+/// Returns the canonical form of the URL, or `None` if it is not a valid URL.
+///
+/// The scheme and host are lowercased, the default port is removed and the `.` and `..` path
+/// segments are resolved. Percent-encoded unreserved characters (letters, digits, `-`, `.`, `_`
+/// and `~`) are decoded and the hexadecimal digits of the other escapes are uppercased. With
+/// `sort_query`, the query parameters are sorted by name, keeping the order of the values of a
+/// same name.
+pub(crate) fn canonicalize(url: &str, sort_query: bool) -> Option<String> {
+    let mut url = Url::parse(url.trim()).ok()?;
+    if sort_query {
+        if let Some(query) = url.query().filter(|query| !query.is_empty()) {
+            let mut params: Vec<&str> = query.split('&').collect();
+            params.sort_by_key(|param| param.split('=').next().unwrap_or_default());
+            let query = params.join("&");
+            url.set_query(Some(&query));
+        }
+    }
+    Some(decode_unreserved(url.as_str()))
+}
+
+/// Decodes the percent-encoded unreserved characters and uppercases the other escapes.
+fn decode_unreserved(url: &str) -> String {
+    let bytes = url.as_bytes();
+    let mut canonical = String::with_capacity(url.len());
+    let mut position = 0;
+    while position < bytes.len() {
+        let escape = bytes
+            .get(position + 1..position + 3)
+            .filter(|_| bytes[position] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escape {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => {
+                canonical.push(byte as char);
+                position += 3;
+            }
+            Some(byte) => {
+                canonical.push_str(&format!("%{:02X}", byte));
+                position += 3;
+            }
+            None => {
+                let c = url[position..].chars().next().unwrap_or_default();
+                canonical.push(c);
+                position += c.len_utf8();
+            }
+        }
+    }
+    canonical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::canonicalize;
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(
+            canonicalize("HTTP://Example.COM:80/a/./b/../c%7E%2fd?x=1", false).unwrap(),
+            "http://example.com/a/c~%2Fd?x=1"
+        );
+        assert_eq!(
+            canonicalize("https://example.com:443", false).unwrap(),
+            "https://example.com/"
+        );
+        assert_eq!(
+            canonicalize("http://example.com:8080/live?b=2&a=1&b=1", true).unwrap(),
+            "http://example.com:8080/live?a=1&b=2&b=1"
+        );
+        assert_eq!(
+            canonicalize("http://example.com/live?b=2&a=1", false).unwrap(),
+            "http://example.com/live?b=2&a=1"
+        );
+        assert!(canonicalize("/media/videos/local.mp4", true).is_none());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::blocklist::contains_word;
+use crate::search::normalize;
+use crate::Info;
+
+/// Keywords of the built-in categories, lowercase and without accents, in English, French,
+/// Spanish, Portuguese, German, Italian and Turkish, along with well-known channel names.
+const KEYWORDS: &[(&str, &[&str])] = &[
+    (
+        "News",
+        &[
+            "news",
+            "info",
+            "infos",
+            "actualites",
+            "noticias",
+            "noticiero",
+            "telejornal",
+            "nachrichten",
+            "notizie",
+            "tg24",
+            "haber",
+            "haberler",
+            "cnn",
+            "euronews",
+            "al jazeera",
+        ],
+    ),
+    (
+        "Sports",
+        &[
+            "sport",
+            "sports",
+            "deportes",
+            "esporte",
+            "esportes",
+            "football",
+            "futbol",
+            "soccer",
+            "calcio",
+            "fussball",
+            "tennis",
+            "golf",
+            "nba",
+            "nfl",
+            "espn",
+            "eurosport",
+            "bein",
+        ],
+    ),
+    (
+        "Kids",
+        &[
+            "kids",
+            "children",
+            "cartoon",
+            "cartoons",
+            "junior",
+            "enfants",
+            "infantil",
+            "kinder",
+            "bambini",
+            "cocuk",
+            "nickelodeon",
+            "nick jr",
+            "baby",
+        ],
+    ),
+    (
+        "Music",
+        &[
+            "music", "musique", "musica", "musik", "muzik", "hits", "mtv", "vh1", "concert",
+        ],
+    ),
+    (
+        "Movies",
+        &[
+            "movie",
+            "movies",
+            "cinema",
+            "cine",
+            "film",
+            "films",
+            "filme",
+            "filmes",
+            "peliculas",
+            "kino",
+            "hbo",
+            "cinemax",
+        ],
+    ),
+];
+
+/// Returns the category of the stream guessed from the keywords in its title or `tvg-name`, or
+/// `None` if no keyword matches.
+///
+/// The keywords added by the user, already normalized, are tried before the built-in ones, so
+/// they can also override them.
+pub(crate) fn categorize(info: &Info, custom: &[(String, Vec<String>)]) -> Option<String> {
+    let fields = [normalize(&info.title), normalize(&info.tvg.name)];
+    let matches = |keywords: &[&str]| {
+        keywords
+            .iter()
+            .any(|keyword| fields.iter().any(|field| contains_word(field, keyword)))
+    };
+
+    let custom = custom.iter().find(|(_, keywords)| {
+        let keywords: Vec<&str> = keywords.iter().map(String::as_str).collect();
+        matches(&keywords)
+    });
+    if let Some((category, _)) = custom {
+        return Some(category.clone());
+    }
+    KEYWORDS
+        .iter()
+        .find(|(_, keywords)| matches(keywords))
+        .map(|(category, _)| category.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::categorize;
+    use crate::Info;
+
+    #[test]
+    fn test_categorize() {
+        let info = |title: &str| Info {
+            title: title.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(categorize(&info("CNN International"), &[]).unwrap(), "News");
+        assert_eq!(categorize(&info("Télé Enfants"), &[]).unwrap(), "Kids");
+        assert_eq!(categorize(&info("Fox Deportes HD"), &[]).unwrap(), "Sports");
+        assert_eq!(categorize(&info("MTV Hits"), &[]).unwrap(), "Music");
+        assert!(categorize(&info("Infomercials"), &[]).is_none());
+        assert!(categorize(&info("BBC One"), &[]).is_none());
+
+        let custom = vec![(String::from("Documentary"), vec![String::from("discovery")])];
+        assert_eq!(
+            categorize(&info("Discovery Channel"), &custom).unwrap(),
+            "Documentary"
+        );
+    }
+}
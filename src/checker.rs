@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "net")]
+use std::error::Error;
+#[cfg(feature = "net")]
+use std::io::ErrorKind;
+#[cfg(feature = "net")]
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::cache::unix_now;
+
+/// Struct representing the outcome of checking the availability of a stream.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct CheckResult {
+    pub available: bool,
+    /// The HTTP status code of the response, if a response was received.
+    pub http_status: Option<u16>,
+    /// The time taken by the check in milliseconds.
+    pub latency_ms: Option<u64>,
+    /// The reason why the stream is not available, if known.
+    pub error: Option<String>,
+    /// The kind of failure, telling network problems from failures of the provider.
+    pub error_kind: Option<CheckErrorKind>,
+    /// The URL of the final response, after following redirects.
+    pub final_url: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub checked_at: u64,
+    /// The throughput observed while downloading the stream in kbit/s, measured by
+    /// `M3uParser::measure_bitrate`.
+    pub bitrate_kbps: Option<u64>,
+}
+
+impl CheckResult {
+    /// Returns when the check was made as an RFC 3339 UTC timestamp, e.g.
+    /// "2023-11-14T22:13:20Z".
+    pub fn checked_at_utc(&self) -> String {
+        crate::cache::format_utc(self.checked_at)
+    }
+}
+
+/// Enum representing why a check failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum CheckErrorKind {
+    /// The check did not complete within the timeout.
+    Timeout,
+    /// The host name could not be resolved.
+    Dns,
+    /// The server refused the connection.
+    ConnectionRefused,
+    /// The TLS handshake failed, e.g. because of an invalid certificate.
+    Tls,
+    /// The server answered with an unsuccessful HTTP status.
+    HttpStatus,
+    /// The server refused the stream in the country of the client: HTTP status 451, or 403 along
+    /// with a geo-blocking header of the CDN. The stream may play through a VPN.
+    GeoBlocked,
+    /// Any other failure, e.g. a missing local file or a reset connection.
+    Other,
+}
+
+#[cfg(feature = "net")]
+impl CheckErrorKind {
+    /// Classifies a request error by walking its chain of sources.
+    pub(crate) fn from_reqwest(error: &reqwest::Error) -> Self {
+        if error.is_timeout() {
+            return CheckErrorKind::Timeout;
+        }
+        let mut messages = String::new();
+        let mut source: Option<&dyn Error> = Some(error);
+        while let Some(error) = source {
+            if let Some(io_error) = error.downcast_ref::<std::io::Error>() {
+                match io_error.kind() {
+                    ErrorKind::ConnectionRefused => return CheckErrorKind::ConnectionRefused,
+                    ErrorKind::TimedOut => return CheckErrorKind::Timeout,
+                    _ => {}
+                }
+            }
+            messages.push_str(&error.to_string().to_lowercase());
+            messages.push('\n');
+            source = error.source();
+        }
+
+        if messages.contains("dns error") || messages.contains("failed to lookup address") {
+            CheckErrorKind::Dns
+        } else if ["certificate", "tls", "ssl", "handshake"]
+            .iter()
+            .any(|word| messages.contains(word))
+        {
+            CheckErrorKind::Tls
+        } else {
+            CheckErrorKind::Other
+        }
+    }
+}
+
+/// Trait used to check whether streams are available when parsing with `check_live`.
+///
+/// Implement it to replace the default HTTP check, e.g. with a mock in tests, a checker probing
+/// the media with ffprobe or one consulting a cache, and install it with
+/// `M3uParser::set_stream_checker`. Local files are verified by the parser itself.
+#[async_trait]
+pub trait StreamChecker: Send + Sync {
+    /// Checks whether the stream at the given location is available.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the stream, with relative paths already resolved against the
+    ///   location of the playlist.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the stream is available, in which case its status is set to "GOOD".
+    ///
+    async fn is_available(&self, url: &str) -> bool;
+
+    /// Checks the stream and reports the details of the check, stored in `Info.check`.
+    ///
+    /// The default implementation calls `is_available` and only measures the latency; override it
+    /// to report the HTTP status or the error as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the stream.
+    ///
+    async fn check(&self, url: &str) -> CheckResult {
+        let started = Instant::now();
+        let available = self.is_available(url).await;
+        CheckResult {
+            available,
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            checked_at: unix_now(),
+            ..Default::default()
+        }
+    }
+
+    /// Checks the stream, sending the HTTP headers set for the entry in the playlist.
+    ///
+    /// The parser calls this method rather than `check`. The default implementation ignores the
+    /// headers and calls `check`.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the stream.
+    /// * `headers` - The headers of the entry as `(name, value)` pairs, e.g. the `User-Agent` and
+    ///   `Referer` set with `#EXTVLCOPT`; they take precedence over the defaults of the checker.
+    ///
+    async fn check_with_headers(&self, url: &str, _headers: &[(&str, &str)]) -> CheckResult {
+        self.check(url).await
+    }
+}
+
+/// Names of the response headers with which CDNs and proxies tell that the client is refused
+/// because of its location. They are matched exactly: headers merely mentioning a country or a
+/// region, such as the `x-amz-bucket-region` of S3, say nothing about the client.
+#[cfg(feature = "net")]
+const GEO_BLOCK_HEADERS: [&str; 3] = ["x-geo-blocked", "x-geo-block", "x-geoblocked"];
+
+/// Returns whether the response refuses the stream because of the location of the client.
+#[cfg(feature = "net")]
+fn is_geo_blocked(status: u16, headers: &reqwest::header::HeaderMap) -> bool {
+    match status {
+        451 => true,
+        403 => GEO_BLOCK_HEADERS
+            .iter()
+            .any(|name| headers.contains_key(*name)),
+        _ => false,
+    }
+}
+
+/// The default `StreamChecker`, which sends a GET request and expects a successful status code.
+#[cfg(feature = "net")]
+#[derive(Debug, Clone)]
+pub struct HttpChecker {
+    client: reqwest::Client,
+    useragent: String,
+}
+
+#[cfg(feature = "net")]
+impl HttpChecker {
+    /// Creates a new HTTP checker.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The timeout of each request.
+    /// * `useragent` - The value of the `User-Agent` header sent with each request.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the HTTP client cannot be initialized.
+    ///
+    pub fn new(timeout: Duration, useragent: &str) -> Self {
+        let client = reqwest::Client::builder().timeout(timeout).build().unwrap();
+        HttpChecker::with_client(client, useragent)
+    }
+
+    /// Creates a new HTTP checker sending its requests with the given client.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client to use, configured with the timeout, redirect policy, etc.
+    /// * `useragent` - The value of the `User-Agent` header sent with each request.
+    ///
+    pub fn with_client(client: reqwest::Client, useragent: &str) -> Self {
+        HttpChecker {
+            client,
+            useragent: useragent.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+#[async_trait]
+impl StreamChecker for HttpChecker {
+    async fn is_available(&self, url: &str) -> bool {
+        self.check(url).await.available
+    }
+
+    async fn check(&self, url: &str) -> CheckResult {
+        self.check_with_headers(url, &[]).await
+    }
+
+    async fn check_with_headers(&self, url: &str, headers: &[(&str, &str)]) -> CheckResult {
+        let started = Instant::now();
+        let mut request = self.client.get(url);
+        if !headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("User-Agent"))
+        {
+            request = request.header("User-Agent", &self.useragent);
+        }
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let response = request.send().await;
+        let mut result = CheckResult {
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            checked_at: unix_now(),
+            ..Default::default()
+        };
+        match response {
+            Ok(response) => {
+                let status = response.status();
+                result.available = status.is_success();
+                result.http_status = Some(status.as_u16());
+                result.final_url = Some(response.url().to_string());
+                if is_geo_blocked(status.as_u16(), response.headers()) {
+                    result.error = Some(format!("HTTP status {}, blocked in this country", status));
+                    result.error_kind = Some(CheckErrorKind::GeoBlocked);
+                } else if !result.available {
+                    result.error = Some(format!("HTTP status {}", status));
+                    result.error_kind = Some(CheckErrorKind::HttpStatus);
+                }
+            }
+            Err(e) => {
+                result.error_kind = Some(CheckErrorKind::from_reqwest(&e));
+                // Without the URL, which may hold the credentials of the stream.
+                result.error = Some(e.without_url().to_string());
+            }
+        }
+        result
+    }
+}
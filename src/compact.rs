@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::{Country, Info, Language, Tvg};
+
+/// Struct used to share a single allocation between repeated strings.
+///
+/// Values such as categories, countries and languages are repeated across most entries of large
+/// playlists, so interning them keeps a single copy of each distinct value in memory.
+#[derive(Debug, Default)]
+pub struct Interner {
+    values: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of the value, storing it first if it was not seen before.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to intern.
+    ///
+    /// # Returns
+    ///
+    /// An `Arc<str>` pointing to the single copy of the value.
+    ///
+    pub fn intern(&mut self, value: &str) -> Arc<str> {
+        match self.values.get(value) {
+            Some(interned) => Arc::clone(interned),
+            None => {
+                let interned: Arc<str> = Arc::from(value);
+                self.values.insert(Arc::clone(&interned));
+                interned
+            }
+        }
+    }
+
+    /// Returns the number of distinct values interned.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if no value has been interned.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Struct representing the stream information in a compact form.
+///
+/// Values that are usually unique to an entry (title, URL, `tvg-*` attributes) are stored as boxed
+/// strings without spare capacity, while values that are usually shared between entries (logo,
+/// categories, country, language, status, album, artist, image and HTTP headers) are interned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfoCompact {
+    pub title: Box<str>,
+    pub logo: Arc<str>,
+    pub url: Box<str>,
+    pub category: Arc<str>,
+    pub categories: Box<[Arc<str>]>,
+    pub tvg_id: Box<str>,
+    pub tvg_name: Box<str>,
+    pub tvg_url: Box<str>,
+    pub country_code: Arc<str>,
+    pub country_name: Arc<str>,
+    pub language_code: Arc<str>,
+    pub language_name: Arc<str>,
+    pub status: Arc<str>,
+    pub duration: Option<f64>,
+    pub album: Option<Arc<str>>,
+    pub artist: Option<Arc<str>>,
+    pub image: Option<Arc<str>>,
+    pub user_agent: Option<Arc<str>>,
+    pub referrer: Option<Arc<str>>,
+    pub source: Option<Arc<str>>,
+    pub tags: Box<[Arc<str>]>,
+    pub first_seen: Option<u64>,
+    pub last_seen: Option<u64>,
+    pub source_index: usize,
+    pub line_number: usize,
+}
+
+impl InfoCompact {
+    /// Creates the compact form of the stream information.
+    ///
+    /// # Arguments
+    ///
+    /// * `info` - The stream information to convert.
+    /// * `interner` - The interner holding the shared values; use the same one for every entry.
+    ///
+    /// # Returns
+    ///
+    /// The `InfoCompact` holding the same information as `info`, except for the original lines.
+    ///
+    pub fn from_info(info: &Info, interner: &mut Interner) -> Self {
+        let mut intern_option =
+            |value: &Option<String>| value.as_deref().map(|value| interner.intern(value));
+        let album = intern_option(&info.album);
+        let artist = intern_option(&info.artist);
+        let image = intern_option(&info.image);
+        let user_agent = intern_option(&info.user_agent);
+        let referrer = intern_option(&info.referrer);
+        let source = intern_option(&info.source);
+
+        InfoCompact {
+            title: info.title.as_str().into(),
+            logo: interner.intern(&info.logo),
+            url: info.url.as_str().into(),
+            category: interner.intern(&info.category),
+            categories: info
+                .categories
+                .iter()
+                .map(|category| interner.intern(category))
+                .collect(),
+            tvg_id: info.tvg.id.as_str().into(),
+            tvg_name: info.tvg.name.as_str().into(),
+            tvg_url: info.tvg.url.as_str().into(),
+            country_code: interner.intern(&info.country.code),
+            country_name: interner.intern(&info.country.name),
+            language_code: interner.intern(&info.language.code),
+            language_name: interner.intern(&info.language.name),
+            status: interner.intern(&info.status),
+            duration: info.duration,
+            album,
+            artist,
+            image,
+            user_agent,
+            referrer,
+            source,
+            tags: info.tags.iter().map(|tag| interner.intern(tag)).collect(),
+            first_seen: info.first_seen,
+            last_seen: info.last_seen,
+            source_index: info.source_index,
+            line_number: info.line_number,
+        }
+    }
+
+    /// Converts the compact form back to the stream information.
+    pub fn to_info(&self) -> Info {
+        Info {
+            title: self.title.to_string(),
+            logo: self.logo.to_string(),
+            url: self.url.to_string(),
+            category: self.category.to_string(),
+            categories: self.categories.iter().map(|c| c.to_string()).collect(),
+            tvg: Tvg {
+                id: self.tvg_id.to_string(),
+                name: self.tvg_name.to_string(),
+                url: self.tvg_url.to_string(),
+            },
+            country: Country {
+                code: self.country_code.to_string(),
+                name: self.country_name.to_string(),
+            },
+            language: Language {
+                code: self.language_code.to_string(),
+                name: self.language_name.to_string(),
+            },
+            status: self.status.to_string(),
+            duration: self.duration,
+            album: self.album.as_deref().map(String::from),
+            artist: self.artist.as_deref().map(String::from),
+            image: self.image.as_deref().map(String::from),
+            user_agent: self.user_agent.as_deref().map(String::from),
+            referrer: self.referrer.as_deref().map(String::from),
+            source: self.source.as_deref().map(String::from),
+            tags: self.tags.iter().map(|tag| tag.to_string()).collect(),
+            first_seen: self.first_seen,
+            last_seen: self.last_seen,
+            source_index: self.source_index,
+            line_number: self.line_number,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InfoCompact, Interner};
+    use crate::{Country, Info};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_info_compact() {
+        let info = |title: &str| Info {
+            title: title.to_string(),
+            url: format!("http://example.com/{}.m3u8", title),
+            category: String::from("News"),
+            categories: vec![String::from("News")],
+            country: Country {
+                code: String::from("GB"),
+                name: String::from("United Kingdom"),
+            },
+            status: String::from("GOOD"),
+            ..Default::default()
+        };
+        let mut interner = Interner::new();
+        let first = InfoCompact::from_info(&info("one"), &mut interner);
+        let second = InfoCompact::from_info(&info("two"), &mut interner);
+
+        assert!(Arc::ptr_eq(&first.category, &second.category));
+        assert!(Arc::ptr_eq(&first.country_name, &second.country_name));
+        // "", "News", "GB", "United Kingdom" and "GOOD".
+        assert_eq!(interner.len(), 5);
+
+        let restored = second.to_info();
+        assert_eq!(restored.title, "two");
+        assert_eq!(restored.categories, vec!["News"]);
+        assert_eq!(restored.country.name, "United Kingdom");
+        assert_eq!(restored.status, "GOOD");
+    }
+}
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::read_to_string;
+
+/// Struct representing a curation pipeline: the playlists to parse, the filters to apply and the
+/// files to write, loadable from TOML or JSON.
+///
+/// ```toml
+/// sources = ["https://example.com/news.m3u", "local.m3u"]
+///
+/// [check]
+/// live = true
+/// timeout_secs = 3
+///
+/// [[filters]]
+/// key = "country-code"
+/// values = ["us", "gb"]
+///
+/// [[outputs]]
+/// path = "curated.m3u"
+///
+/// [[outputs]]
+/// path = "curated.json"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParserConfig {
+    /// The paths or URLs of the M3U playlists, merged in order.
+    pub sources: Vec<String>,
+    /// Whether to enforce the M3U schema, `true` by default.
+    pub enforce_schema: bool,
+    /// The maximum number of playlists downloaded from the same host at the same time, 2 by
+    /// default.
+    pub per_host: usize,
+    /// The minimum number of entries required to overwrite an existing output, see
+    /// `M3uParser::set_min_entries`.
+    pub min_entries: Option<usize>,
+    /// The number of backups kept of each overwritten output, see
+    /// `M3uParser::set_backup_retention`.
+    pub backup_retention: Option<usize>,
+    /// The settings of the stream checks.
+    pub check: CheckConfig,
+    /// The filters applied in order after parsing.
+    pub filters: Vec<FilterConfig>,
+    /// The files written after filtering.
+    pub outputs: Vec<OutputConfig>,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        ParserConfig {
+            sources: vec![],
+            enforce_schema: true,
+            per_host: 2,
+            min_entries: None,
+            backup_retention: None,
+            check: CheckConfig::default(),
+            filters: vec![],
+            outputs: vec![],
+        }
+    }
+}
+
+/// Struct representing the settings of the stream checks of a `ParserConfig`. Unset values keep
+/// the settings of the parser. With `M3uParser::run_config`, the `M3U_PARSER_*` environment
+/// variables override these settings, see `M3uParser::apply_env`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckConfig {
+    /// Whether to check the availability of the streams while parsing.
+    pub live: bool,
+    /// The timeout of each stream check, in seconds.
+    pub timeout_secs: Option<u64>,
+    /// The timeout of downloading each playlist, in seconds.
+    pub fetch_timeout_secs: Option<u64>,
+    /// The `User-Agent` header sent when checking streams without their own.
+    pub user_agent: Option<String>,
+}
+
+/// Struct representing a filter of a `ParserConfig`, applied like `filter_by`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterConfig {
+    /// The key to filter on, e.g. "title" or "country-code". Keys containing `-` are nested keys.
+    pub key: String,
+    /// The regular expressions matched against the values of the key.
+    pub values: Vec<String>,
+    /// Whether to keep the matching streams (`true`, the default) or remove them.
+    #[serde(default = "default_retrieve")]
+    pub retrieve: bool,
+}
+
+fn default_retrieve() -> bool {
+    true
+}
+
+/// Struct representing a file written by a `ParserConfig`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// The path of the file, overwritten if it exists.
+    pub path: String,
+    /// The format, one of "m3u", "json", "html", "md", "asx" and "wpl", guessed from the
+    /// extension of the path if not set.
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+impl OutputConfig {
+    /// Returns the format of the output, guessed from the extension of the path and defaulting to
+    /// M3U if it is not set.
+    pub fn format(&self) -> String {
+        if let Some(format) = &self.format {
+            return format.clone();
+        }
+        match self.path.rsplit_once('.') {
+            Some((_, extension)) if ["json", "html", "md", "asx", "wpl"].contains(&extension) => {
+                extension.to_string()
+            }
+            _ => String::from("m3u"),
+        }
+    }
+}
+
+impl ParserConfig {
+    /// Parses a configuration from JSON.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the JSON is invalid.
+    ///
+    pub fn from_json(content: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    /// Parses a configuration from TOML.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the TOML is invalid.
+    ///
+    pub fn from_toml(content: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Loads a configuration from a file, parsed as JSON if its extension is ".json" and as TOML
+    /// otherwise.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = read_to_string(path)?;
+        if path.to_lowercase().ends_with(".json") {
+            ParserConfig::from_json(&content)
+        } else {
+            ParserConfig::from_toml(&content)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OutputConfig, ParserConfig};
+
+    #[test]
+    fn test_parser_config() {
+        let config = ParserConfig::from_toml(
+            r#"
+            sources = ["news.m3u"]
+            min_entries = 100
+
+            [check]
+            timeout_secs = 3
+
+            [[filters]]
+            key = "country-code"
+            values = ["us", "gb"]
+
+            [[filters]]
+            key = "title"
+            values = ["Shop"]
+            retrieve = false
+
+            [[outputs]]
+            path = "curated.json"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.sources, vec!["news.m3u"]);
+        assert!(config.enforce_schema);
+        assert_eq!(config.min_entries, Some(100));
+        assert_eq!(config.backup_retention, None);
+        assert!(!config.check.live);
+        assert_eq!(config.check.timeout_secs, Some(3));
+        assert!(config.filters[0].retrieve);
+        assert!(!config.filters[1].retrieve);
+        assert_eq!(config.outputs[0].format(), "json");
+
+        let output = OutputConfig {
+            path: String::from("curated.txt"),
+            format: None,
+        };
+        assert_eq!(output.format(), "m3u");
+
+        assert!(ParserConfig::from_toml("[[filters]]\nkey = \"title\"").is_err());
+        assert_eq!(
+            ParserConfig::from_json("{}").unwrap(),
+            ParserConfig::default()
+        );
+    }
+}
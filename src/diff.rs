@@ -0,0 +1,244 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::sync::DESCRIPTIVE_KEYS;
+use crate::{render_m3u, Info, InfoKey};
+
+/// Struct representing a field whose value differs between two versions of a stream.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: InfoKey,
+    pub old: String,
+    pub new: String,
+}
+
+/// Struct representing a stream found in both playlists with different fields.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedStream {
+    pub old: Info,
+    pub new: Info,
+    pub fields: Vec<FieldChange>,
+}
+
+/// Struct representing the differences between two playlists, returned by `M3uParser::diff`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PlaylistDiff {
+    /// The streams of the new playlist that were not in the old one.
+    pub added: Vec<Info>,
+    /// The streams of the old playlist that are no longer in the new one.
+    pub removed: Vec<Info>,
+    /// The streams found in both playlists whose URL or descriptive fields changed.
+    pub changed: Vec<ChangedStream>,
+}
+
+/// Compares the streams of two playlists, matched by `tvg-id` or else by URL.
+pub(crate) fn diff(old: &[Info], new: &[Info]) -> PlaylistDiff {
+    let mut by_tvg_id: HashMap<&str, usize> = HashMap::new();
+    let mut by_url: HashMap<&str, usize> = HashMap::new();
+    for (position, info) in new.iter().enumerate() {
+        if !info.tvg.id.is_empty() {
+            by_tvg_id.entry(&info.tvg.id).or_insert(position);
+        }
+        by_url.entry(&info.url).or_insert(position);
+    }
+
+    let mut diff = PlaylistDiff::default();
+    let mut matched = vec![false; new.len()];
+    for info in old {
+        let position = match info.tvg.id.as_str() {
+            "" => None,
+            id => by_tvg_id.get(id),
+        }
+        .or_else(|| by_url.get(info.url.as_str()))
+        .copied()
+        .filter(|position| !matched[*position]);
+        let Some(position) = position else {
+            diff.removed.push(info.clone());
+            continue;
+        };
+        matched[position] = true;
+
+        let fresh = &new[position];
+        let fields: Vec<FieldChange> = [InfoKey::Url]
+            .iter()
+            .chain(DESCRIPTIVE_KEYS.iter())
+            .filter(|key| key.value(info) != key.value(fresh))
+            .map(|key| FieldChange {
+                field: *key,
+                old: key.value(info).to_string(),
+                new: key.value(fresh).to_string(),
+            })
+            .collect();
+        if !fields.is_empty() {
+            diff.changed.push(ChangedStream {
+                old: info.clone(),
+                new: fresh.clone(),
+                fields,
+            });
+        }
+    }
+    diff.added = new
+        .iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(info, _)| info.clone())
+        .collect();
+    diff
+}
+
+impl PlaylistDiff {
+    /// Returns `true` if the playlists have the same streams.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Retrieves the differences in JSON format, with the `added`, `removed` and `changed` streams.
+    ///
+    /// # Arguments
+    ///
+    /// * `pretty` - A boolean indicating whether to format the JSON output in a pretty, human-readable way.
+    ///
+    /// # Returns
+    ///
+    /// A `serde_json::Result<String>` representing the JSON output.
+    ///
+    pub fn to_json(&self, pretty: bool) -> serde_json::Result<String> {
+        match pretty {
+            true => serde_json::to_string_pretty(self),
+            false => serde_json::to_string(self),
+        }
+    }
+
+    /// Retrieves the differences as a Markdown report, e.g. to post the nightly changes of a
+    /// playlist to a chat channel. Empty sections are left out.
+    pub fn to_markdown(&self) -> String {
+        if self.is_empty() {
+            return String::from("No changes.\n");
+        }
+
+        let mut sections = vec![];
+        let list = |title: &str, streams: &[Info]| {
+            let items: Vec<String> = streams
+                .iter()
+                .map(|info| format!("- {} ({})", info.title, info.url))
+                .collect();
+            format!("## {} ({})\n\n{}\n", title, streams.len(), items.join("\n"))
+        };
+        if !self.added.is_empty() {
+            sections.push(list("Added", &self.added));
+        }
+        if !self.removed.is_empty() {
+            sections.push(list("Removed", &self.removed));
+        }
+        if !self.changed.is_empty() {
+            let items: Vec<String> = self
+                .changed
+                .iter()
+                .map(|changed| {
+                    let fields: Vec<String> = changed
+                        .fields
+                        .iter()
+                        .map(|change| {
+                            format!(
+                                "  - {}: `{}` → `{}`",
+                                change.field.name(),
+                                change.old,
+                                change.new
+                            )
+                        })
+                        .collect();
+                    format!("- {}\n{}", changed.new.title, fields.join("\n"))
+                })
+                .collect();
+            sections.push(format!(
+                "## Changed ({})\n\n{}\n",
+                self.changed.len(),
+                items.join("\n")
+            ));
+        }
+        sections.join("\n")
+    }
+
+    /// Retrieves the streams of the differences as an M3U playlist, e.g. to publish the new
+    /// channels.
+    ///
+    /// # Arguments
+    ///
+    /// * `added_only` - A boolean indicating whether to only write the added streams, rather than
+    ///   the added streams followed by the new version of the changed ones.
+    ///
+    /// # Returns
+    ///
+    /// A `String` with the M3U playlist, which is empty if there is no stream to write.
+    ///
+    pub fn to_m3u(&self, added_only: bool) -> String {
+        let mut streams_info = self.added.clone();
+        if !added_only {
+            streams_info.extend(self.changed.iter().map(|changed| changed.new.clone()));
+        }
+        render_m3u(&streams_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+    use crate::{Info, InfoKey, Tvg};
+
+    fn info(title: &str, id: &str, url: &str) -> Info {
+        Info {
+            title: title.to_string(),
+            url: url.to_string(),
+            tvg: Tvg {
+                id: id.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = vec![
+            info("BBC One", "bbcone.uk", "http://example.com/bbc.m3u8"),
+            info("TF1", "", "http://example.com/tf1.m3u8"),
+            info("ITV", "", "http://example.com/itv.m3u8"),
+        ];
+        let new = vec![
+            info("BBC One HD", "bbcone.uk", "http://example.com/bbc-hd.m3u8"),
+            info("TF1", "", "http://example.com/tf1.m3u8"),
+            info("Arte", "", "http://example.com/arte.m3u8"),
+        ];
+        let diff = diff(&old, &new);
+        assert_eq!(diff.added[0].title, "Arte");
+        assert_eq!(diff.removed[0].title, "ITV");
+        assert_eq!(diff.changed.len(), 1);
+        let fields: Vec<InfoKey> = diff.changed[0]
+            .fields
+            .iter()
+            .map(|change| change.field)
+            .collect();
+        assert_eq!(fields, vec![InfoKey::Url, InfoKey::Title]);
+
+        assert_eq!(
+            diff.to_markdown(),
+            "## Added (1)\n\n- Arte (http://example.com/arte.m3u8)\n\n\
+             ## Removed (1)\n\n- ITV (http://example.com/itv.m3u8)\n\n\
+             ## Changed (1)\n\n- BBC One HD\n\
+             \x20 - url: `http://example.com/bbc.m3u8` → `http://example.com/bbc-hd.m3u8`\n\
+             \x20 - title: `BBC One` → `BBC One HD`\n"
+        );
+        assert_eq!(
+            diff.to_m3u(true),
+            "#EXTM3U\n#EXTINF:-1,Arte\nhttp://example.com/arte.m3u8"
+        );
+        assert_eq!(diff.to_m3u(false).matches("#EXTINF").count(), 2);
+
+        let json: serde_json::Value = serde_json::from_str(&diff.to_json(false).unwrap()).unwrap();
+        assert_eq!(json["changed"][0]["fields"][1]["new"], "BBC One HD");
+
+        let unchanged = super::diff(&old, &old);
+        assert!(unchanged.is_empty());
+        assert_eq!(unchanged.to_markdown(), "No changes.\n");
+    }
+}
@@ -0,0 +1,27 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+/// Trait used to download playlists from URLs.
+///
+/// By default, playlists are downloaded with reqwest, which requires the `net` feature and a tokio
+/// runtime. Implement this trait with the HTTP client of another runtime, e.g. surf or isahc for
+/// async-std and smol applications, and install it with `M3uParser::set_http_fetch` to parse URLs
+/// without embedding tokio; it also works without the `net` feature.
+#[async_trait]
+pub trait HttpFetch: Send + Sync {
+    /// Downloads the content at the URL as text.
+    ///
+    /// The HTTP settings of the parser, such as the fetch timeout and the redirect policy, do not
+    /// apply; the implementation is responsible for them.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the playlist.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` with the body of the response, or an error if it cannot be downloaded.
+    ///
+    async fn fetch(&self, url: &str) -> Result<String, Box<dyn Error + Send + Sync>>;
+}
@@ -0,0 +1,527 @@
+use crate::Info;
+
+/// The UN M49 regions and subregions, with the ISO 3166-1 alpha-2 codes of their countries.
+const REGIONS: &[(&str, &str, &[&str])] = &[
+    (
+        "Africa",
+        "Northern Africa",
+        &["DZ", "EG", "LY", "MA", "SD", "TN", "EH"],
+    ),
+    (
+        "Africa",
+        "Eastern Africa",
+        &[
+            "BI", "KM", "DJ", "ER", "ET", "KE", "MG", "MW", "MU", "YT", "MZ", "RE", "RW", "SC",
+            "SO", "SS", "UG", "TZ", "ZM", "ZW", "IO", "TF",
+        ],
+    ),
+    (
+        "Africa",
+        "Middle Africa",
+        &["AO", "CM", "CF", "TD", "CG", "CD", "GQ", "GA", "ST"],
+    ),
+    ("Africa", "Southern Africa", &["BW", "SZ", "LS", "NA", "ZA"]),
+    (
+        "Africa",
+        "Western Africa",
+        &[
+            "BJ", "BF", "CV", "CI", "GM", "GH", "GN", "GW", "LR", "ML", "MR", "NE", "NG", "SH",
+            "SN", "SL", "TG",
+        ],
+    ),
+    (
+        "Americas",
+        "Caribbean",
+        &[
+            "AI", "AG", "AW", "BS", "BB", "BQ", "VG", "KY", "CU", "CW", "DM", "DO", "GD", "GP",
+            "HT", "JM", "MQ", "MS", "PR", "BL", "KN", "LC", "MF", "VC", "SX", "TT", "TC", "VI",
+        ],
+    ),
+    (
+        "Americas",
+        "Central America",
+        &["BZ", "CR", "SV", "GT", "HN", "MX", "NI", "PA"],
+    ),
+    (
+        "Americas",
+        "South America",
+        &[
+            "AR", "BO", "BV", "BR", "CL", "CO", "EC", "FK", "GF", "GY", "PY", "PE", "GS", "SR",
+            "UY", "VE",
+        ],
+    ),
+    (
+        "Americas",
+        "Northern America",
+        &["BM", "CA", "GL", "PM", "US"],
+    ),
+    ("Antarctica", "Antarctica", &["AQ"]),
+    ("Asia", "Central Asia", &["KZ", "KG", "TJ", "TM", "UZ"]),
+    (
+        "Asia",
+        "Eastern Asia",
+        &["CN", "HK", "MO", "KP", "JP", "MN", "KR", "TW"],
+    ),
+    (
+        "Asia",
+        "South-eastern Asia",
+        &[
+            "BN", "KH", "ID", "LA", "MY", "MM", "PH", "SG", "TH", "TL", "VN",
+        ],
+    ),
+    (
+        "Asia",
+        "Southern Asia",
+        &["AF", "BD", "BT", "IN", "IR", "MV", "NP", "PK", "LK"],
+    ),
+    (
+        "Asia",
+        "Western Asia",
+        &[
+            "AM", "AZ", "BH", "CY", "GE", "IQ", "IL", "JO", "KW", "LB", "OM", "QA", "SA", "PS",
+            "SY", "TR", "AE", "YE",
+        ],
+    ),
+    (
+        "Europe",
+        "Eastern Europe",
+        &["BY", "BG", "CZ", "HU", "PL", "MD", "RO", "RU", "SK", "UA"],
+    ),
+    (
+        "Europe",
+        "Northern Europe",
+        &[
+            "AX", "DK", "EE", "FO", "FI", "GG", "IS", "IE", "IM", "JE", "LV", "LT", "NO", "SJ",
+            "SE", "GB",
+        ],
+    ),
+    (
+        "Europe",
+        "Southern Europe",
+        &[
+            "AL", "AD", "BA", "HR", "GI", "GR", "VA", "IT", "MT", "ME", "MK", "PT", "SM", "RS",
+            "SI", "ES", "XK",
+        ],
+    ),
+    (
+        "Europe",
+        "Western Europe",
+        &["AT", "BE", "FR", "DE", "LI", "LU", "MC", "NL", "CH"],
+    ),
+    (
+        "Oceania",
+        "Australia and New Zealand",
+        &["AU", "CX", "CC", "HM", "NZ", "NF"],
+    ),
+    ("Oceania", "Melanesia", &["FJ", "NC", "PG", "SB", "VU"]),
+    (
+        "Oceania",
+        "Micronesia",
+        &["GU", "KI", "MH", "FM", "NR", "MP", "PW", "UM"],
+    ),
+    (
+        "Oceania",
+        "Polynesia",
+        &["AS", "CK", "PF", "NU", "PN", "WS", "TK", "TO", "TV", "WF"],
+    ),
+];
+
+/// Common names of groups of subregions.
+const ALIASES: &[(&str, &[&str])] = &[
+    (
+        "North America",
+        &["Northern America", "Central America", "Caribbean"],
+    ),
+    (
+        "Latin America",
+        &["Central America", "Caribbean", "South America"],
+    ),
+    (
+        "Sub-Saharan Africa",
+        &[
+            "Eastern Africa",
+            "Middle Africa",
+            "Southern Africa",
+            "Western Africa",
+        ],
+    ),
+];
+
+/// The main IANA time zone of each country, by ISO 3166-1 alpha-2 code, sorted by code. Countries
+/// spanning several time zones get the zone of their most populated area.
+const TIMEZONES: &[(&str, &str)] = &[
+    ("AD", "Europe/Andorra"),
+    ("AE", "Asia/Dubai"),
+    ("AF", "Asia/Kabul"),
+    ("AG", "America/Antigua"),
+    ("AI", "America/Anguilla"),
+    ("AL", "Europe/Tirane"),
+    ("AM", "Asia/Yerevan"),
+    ("AO", "Africa/Luanda"),
+    ("AQ", "Antarctica/McMurdo"),
+    ("AR", "America/Argentina/Buenos_Aires"),
+    ("AS", "Pacific/Pago_Pago"),
+    ("AT", "Europe/Vienna"),
+    ("AU", "Australia/Sydney"),
+    ("AW", "America/Aruba"),
+    ("AX", "Europe/Mariehamn"),
+    ("AZ", "Asia/Baku"),
+    ("BA", "Europe/Sarajevo"),
+    ("BB", "America/Barbados"),
+    ("BD", "Asia/Dhaka"),
+    ("BE", "Europe/Brussels"),
+    ("BF", "Africa/Ouagadougou"),
+    ("BG", "Europe/Sofia"),
+    ("BH", "Asia/Bahrain"),
+    ("BI", "Africa/Bujumbura"),
+    ("BJ", "Africa/Porto-Novo"),
+    ("BL", "America/St_Barthelemy"),
+    ("BM", "Atlantic/Bermuda"),
+    ("BN", "Asia/Brunei"),
+    ("BO", "America/La_Paz"),
+    ("BQ", "America/Kralendijk"),
+    ("BR", "America/Sao_Paulo"),
+    ("BS", "America/Nassau"),
+    ("BT", "Asia/Thimphu"),
+    ("BW", "Africa/Gaborone"),
+    ("BY", "Europe/Minsk"),
+    ("BZ", "America/Belize"),
+    ("CA", "America/Toronto"),
+    ("CC", "Indian/Cocos"),
+    ("CD", "Africa/Kinshasa"),
+    ("CF", "Africa/Bangui"),
+    ("CG", "Africa/Brazzaville"),
+    ("CH", "Europe/Zurich"),
+    ("CI", "Africa/Abidjan"),
+    ("CK", "Pacific/Rarotonga"),
+    ("CL", "America/Santiago"),
+    ("CM", "Africa/Douala"),
+    ("CN", "Asia/Shanghai"),
+    ("CO", "America/Bogota"),
+    ("CR", "America/Costa_Rica"),
+    ("CU", "America/Havana"),
+    ("CV", "Atlantic/Cape_Verde"),
+    ("CW", "America/Curacao"),
+    ("CX", "Indian/Christmas"),
+    ("CY", "Asia/Nicosia"),
+    ("CZ", "Europe/Prague"),
+    ("DE", "Europe/Berlin"),
+    ("DJ", "Africa/Djibouti"),
+    ("DK", "Europe/Copenhagen"),
+    ("DM", "America/Dominica"),
+    ("DO", "America/Santo_Domingo"),
+    ("DZ", "Africa/Algiers"),
+    ("EC", "America/Guayaquil"),
+    ("EE", "Europe/Tallinn"),
+    ("EG", "Africa/Cairo"),
+    ("EH", "Africa/El_Aaiun"),
+    ("ER", "Africa/Asmara"),
+    ("ES", "Europe/Madrid"),
+    ("ET", "Africa/Addis_Ababa"),
+    ("FI", "Europe/Helsinki"),
+    ("FJ", "Pacific/Fiji"),
+    ("FK", "Atlantic/Stanley"),
+    ("FM", "Pacific/Pohnpei"),
+    ("FO", "Atlantic/Faroe"),
+    ("FR", "Europe/Paris"),
+    ("GA", "Africa/Libreville"),
+    ("GB", "Europe/London"),
+    ("GD", "America/Grenada"),
+    ("GE", "Asia/Tbilisi"),
+    ("GF", "America/Cayenne"),
+    ("GG", "Europe/Guernsey"),
+    ("GH", "Africa/Accra"),
+    ("GI", "Europe/Gibraltar"),
+    ("GL", "America/Nuuk"),
+    ("GM", "Africa/Banjul"),
+    ("GN", "Africa/Conakry"),
+    ("GP", "America/Guadeloupe"),
+    ("GQ", "Africa/Malabo"),
+    ("GR", "Europe/Athens"),
+    ("GS", "Atlantic/South_Georgia"),
+    ("GT", "America/Guatemala"),
+    ("GU", "Pacific/Guam"),
+    ("GW", "Africa/Bissau"),
+    ("GY", "America/Guyana"),
+    ("HK", "Asia/Hong_Kong"),
+    ("HN", "America/Tegucigalpa"),
+    ("HR", "Europe/Zagreb"),
+    ("HT", "America/Port-au-Prince"),
+    ("HU", "Europe/Budapest"),
+    ("ID", "Asia/Jakarta"),
+    ("IE", "Europe/Dublin"),
+    ("IL", "Asia/Jerusalem"),
+    ("IM", "Europe/Isle_of_Man"),
+    ("IN", "Asia/Kolkata"),
+    ("IO", "Indian/Chagos"),
+    ("IQ", "Asia/Baghdad"),
+    ("IR", "Asia/Tehran"),
+    ("IS", "Atlantic/Reykjavik"),
+    ("IT", "Europe/Rome"),
+    ("JE", "Europe/Jersey"),
+    ("JM", "America/Jamaica"),
+    ("JO", "Asia/Amman"),
+    ("JP", "Asia/Tokyo"),
+    ("KE", "Africa/Nairobi"),
+    ("KG", "Asia/Bishkek"),
+    ("KH", "Asia/Phnom_Penh"),
+    ("KI", "Pacific/Tarawa"),
+    ("KM", "Indian/Comoro"),
+    ("KN", "America/St_Kitts"),
+    ("KP", "Asia/Pyongyang"),
+    ("KR", "Asia/Seoul"),
+    ("KW", "Asia/Kuwait"),
+    ("KY", "America/Cayman"),
+    ("KZ", "Asia/Almaty"),
+    ("LA", "Asia/Vientiane"),
+    ("LB", "Asia/Beirut"),
+    ("LC", "America/St_Lucia"),
+    ("LI", "Europe/Vaduz"),
+    ("LK", "Asia/Colombo"),
+    ("LR", "Africa/Monrovia"),
+    ("LS", "Africa/Maseru"),
+    ("LT", "Europe/Vilnius"),
+    ("LU", "Europe/Luxembourg"),
+    ("LV", "Europe/Riga"),
+    ("LY", "Africa/Tripoli"),
+    ("MA", "Africa/Casablanca"),
+    ("MC", "Europe/Monaco"),
+    ("MD", "Europe/Chisinau"),
+    ("ME", "Europe/Podgorica"),
+    ("MF", "America/Marigot"),
+    ("MG", "Indian/Antananarivo"),
+    ("MH", "Pacific/Majuro"),
+    ("MK", "Europe/Skopje"),
+    ("ML", "Africa/Bamako"),
+    ("MM", "Asia/Yangon"),
+    ("MN", "Asia/Ulaanbaatar"),
+    ("MO", "Asia/Macau"),
+    ("MP", "Pacific/Saipan"),
+    ("MQ", "America/Martinique"),
+    ("MR", "Africa/Nouakchott"),
+    ("MS", "America/Montserrat"),
+    ("MT", "Europe/Malta"),
+    ("MU", "Indian/Mauritius"),
+    ("MV", "Indian/Maldives"),
+    ("MW", "Africa/Blantyre"),
+    ("MX", "America/Mexico_City"),
+    ("MY", "Asia/Kuala_Lumpur"),
+    ("MZ", "Africa/Maputo"),
+    ("NA", "Africa/Windhoek"),
+    ("NC", "Pacific/Noumea"),
+    ("NE", "Africa/Niamey"),
+    ("NF", "Pacific/Norfolk"),
+    ("NG", "Africa/Lagos"),
+    ("NI", "America/Managua"),
+    ("NL", "Europe/Amsterdam"),
+    ("NO", "Europe/Oslo"),
+    ("NP", "Asia/Kathmandu"),
+    ("NR", "Pacific/Nauru"),
+    ("NU", "Pacific/Niue"),
+    ("NZ", "Pacific/Auckland"),
+    ("OM", "Asia/Muscat"),
+    ("PA", "America/Panama"),
+    ("PE", "America/Lima"),
+    ("PF", "Pacific/Tahiti"),
+    ("PG", "Pacific/Port_Moresby"),
+    ("PH", "Asia/Manila"),
+    ("PK", "Asia/Karachi"),
+    ("PL", "Europe/Warsaw"),
+    ("PM", "America/Miquelon"),
+    ("PN", "Pacific/Pitcairn"),
+    ("PR", "America/Puerto_Rico"),
+    ("PS", "Asia/Gaza"),
+    ("PT", "Europe/Lisbon"),
+    ("PW", "Pacific/Palau"),
+    ("PY", "America/Asuncion"),
+    ("QA", "Asia/Qatar"),
+    ("RE", "Indian/Reunion"),
+    ("RO", "Europe/Bucharest"),
+    ("RS", "Europe/Belgrade"),
+    ("RU", "Europe/Moscow"),
+    ("RW", "Africa/Kigali"),
+    ("SA", "Asia/Riyadh"),
+    ("SB", "Pacific/Guadalcanal"),
+    ("SC", "Indian/Mahe"),
+    ("SD", "Africa/Khartoum"),
+    ("SE", "Europe/Stockholm"),
+    ("SG", "Asia/Singapore"),
+    ("SH", "Atlantic/St_Helena"),
+    ("SI", "Europe/Ljubljana"),
+    ("SJ", "Arctic/Longyearbyen"),
+    ("SK", "Europe/Bratislava"),
+    ("SL", "Africa/Freetown"),
+    ("SM", "Europe/San_Marino"),
+    ("SN", "Africa/Dakar"),
+    ("SO", "Africa/Mogadishu"),
+    ("SR", "America/Paramaribo"),
+    ("SS", "Africa/Juba"),
+    ("ST", "Africa/Sao_Tome"),
+    ("SV", "America/El_Salvador"),
+    ("SX", "America/Lower_Princes"),
+    ("SY", "Asia/Damascus"),
+    ("SZ", "Africa/Mbabane"),
+    ("TC", "America/Grand_Turk"),
+    ("TD", "Africa/Ndjamena"),
+    ("TF", "Indian/Kerguelen"),
+    ("TG", "Africa/Lome"),
+    ("TH", "Asia/Bangkok"),
+    ("TJ", "Asia/Dushanbe"),
+    ("TK", "Pacific/Fakaofo"),
+    ("TL", "Asia/Dili"),
+    ("TM", "Asia/Ashgabat"),
+    ("TN", "Africa/Tunis"),
+    ("TO", "Pacific/Tongatapu"),
+    ("TR", "Europe/Istanbul"),
+    ("TT", "America/Port_of_Spain"),
+    ("TV", "Pacific/Funafuti"),
+    ("TW", "Asia/Taipei"),
+    ("TZ", "Africa/Dar_es_Salaam"),
+    ("UA", "Europe/Kyiv"),
+    ("UG", "Africa/Kampala"),
+    ("UM", "Pacific/Wake"),
+    ("US", "America/New_York"),
+    ("UY", "America/Montevideo"),
+    ("UZ", "Asia/Tashkent"),
+    ("VA", "Europe/Vatican"),
+    ("VC", "America/St_Vincent"),
+    ("VE", "America/Caracas"),
+    ("VG", "America/Tortola"),
+    ("VI", "America/St_Thomas"),
+    ("VN", "Asia/Ho_Chi_Minh"),
+    ("VU", "Pacific/Efate"),
+    ("WF", "Pacific/Wallis"),
+    ("WS", "Pacific/Apia"),
+    ("XK", "Europe/Belgrade"),
+    ("YE", "Asia/Aden"),
+    ("YT", "Indian/Mayotte"),
+    ("ZA", "Africa/Johannesburg"),
+    ("ZM", "Africa/Lusaka"),
+    ("ZW", "Africa/Harare"),
+];
+
+/// Returns the main IANA time zone of the country, e.g. "Europe/London" for "GB".
+pub(crate) fn timezone(code: &str) -> Option<&'static str> {
+    let code = code.to_ascii_uppercase();
+    TIMEZONES
+        .binary_search_by(|(c, _)| (*c).cmp(code.as_str()))
+        .ok()
+        .map(|position| TIMEZONES[position].1)
+}
+
+/// Returns the region and subregion of the country, e.g. ("Europe", "Northern Europe") for "GB".
+pub(crate) fn region_of(code: &str) -> Option<(&'static str, &'static str)> {
+    REGIONS
+        .iter()
+        .find(|(_, _, codes)| codes.iter().any(|c| c.eq_ignore_ascii_case(code)))
+        .map(|(region, subregion, _)| (*region, *subregion))
+}
+
+/// Returns whether the name is a known region, subregion or alias, ignoring case.
+pub(crate) fn is_region(name: &str) -> bool {
+    let name = name.trim();
+    REGIONS.iter().any(|(region, subregion, _)| {
+        region.eq_ignore_ascii_case(name) || subregion.eq_ignore_ascii_case(name)
+    }) || ALIASES
+        .iter()
+        .any(|(alias, _)| alias.eq_ignore_ascii_case(name))
+}
+
+/// Returns whether the country is in the region, subregion or alias, ignoring case.
+pub(crate) fn in_region(code: &str, name: &str) -> bool {
+    let name = name.trim();
+    let Some((region, subregion)) = region_of(code) else {
+        return false;
+    };
+    region.eq_ignore_ascii_case(name)
+        || subregion.eq_ignore_ascii_case(name)
+        || ALIASES.iter().any(|(alias, subregions)| {
+            alias.eq_ignore_ascii_case(name) && subregions.contains(&subregion)
+        })
+}
+
+/// Returns whether the stream matches the region or subdivision.
+///
+/// A name such as "Europe", "Western Asia" or "Latin America" matches the streams of any of the
+/// countries of the region. An ISO 3166-2 code such as "US-CA" matches the streams whose
+/// `subdivision` attribute is that code.
+pub(crate) fn matches(info: &Info, name: &str) -> bool {
+    let name = name.trim();
+    if is_subdivision(name) {
+        return info
+            .extra_attributes
+            .get("subdivision")
+            .is_some_and(|subdivision| subdivision.eq_ignore_ascii_case(name));
+    }
+    info.country
+        .codes()
+        .iter()
+        .any(|code| in_region(code, name))
+}
+
+/// Returns whether the name looks like an ISO 3166-2 subdivision code, e.g. "US-CA".
+pub(crate) fn is_subdivision(name: &str) -> bool {
+    match name.split_once('-') {
+        Some((country, subdivision)) => {
+            country.len() == 2
+                && country.chars().all(|c| c.is_ascii_alphabetic())
+                && (1..=3).contains(&subdivision.len())
+                && subdivision.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{in_region, is_region, is_subdivision, matches, region_of, timezone};
+    use crate::{Country, Info};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_regions() {
+        assert_eq!(region_of("gb"), Some(("Europe", "Northern Europe")));
+        assert_eq!(region_of("BR"), Some(("Americas", "South America")));
+        assert_eq!(region_of("ZZ"), None);
+
+        assert!(in_region("FR", "europe"));
+        assert!(in_region("FR", "Western Europe"));
+        assert!(!in_region("FR", "Eastern Europe"));
+        assert!(in_region("MX", "North America"));
+        assert!(in_region("MX", "Latin America"));
+        assert!(!in_region("US", "Latin America"));
+        assert!(in_region("NG", "Sub-Saharan Africa"));
+        assert!(!in_region("EG", "Sub-Saharan Africa"));
+
+        assert!(is_region("asia"));
+        assert!(!is_region("Atlantis"));
+        assert!(is_subdivision("US-CA"));
+        assert!(!is_subdivision("Europe"));
+        assert!(!is_subdivision("Sub-Saharan Africa"));
+    }
+
+    #[test]
+    fn test_timezone() {
+        assert_eq!(timezone("GB"), Some("Europe/London"));
+        assert_eq!(timezone("np"), Some("Asia/Kathmandu"));
+        assert_eq!(timezone("US"), Some("America/New_York"));
+        assert_eq!(timezone("ZZ"), None);
+    }
+
+    #[test]
+    fn test_matches() {
+        let info = Info {
+            country: Country {
+                code: "uk;IN".to_string(),
+                ..Default::default()
+            },
+            extra_attributes: BTreeMap::from([("subdivision".to_string(), "GB-SCT".to_string())]),
+            ..Default::default()
+        };
+        assert!(matches(&info, "Europe"));
+        assert!(matches(&info, "Southern Asia"));
+        assert!(!matches(&info, "Africa"));
+        assert!(matches(&info, "gb-sct"));
+        assert!(!matches(&info, "GB-WLS"));
+    }
+}
@@ -0,0 +1,290 @@
+use serde::Serialize;
+
+/// Struct representing a media segment of an HLS media playlist.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HlsSegment {
+    /// The URI of the segment as written in the playlist, possibly relative to the playlist.
+    pub uri: String,
+    /// The duration of the segment in seconds, from its `#EXTINF` line.
+    pub duration: f64,
+    /// The title of the segment, usually empty.
+    pub title: String,
+}
+
+/// Struct representing an HLS media playlist, listing the segments of a single stream.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HlsMediaPlaylist {
+    /// The maximum duration of a segment in seconds, from `#EXT-X-TARGETDURATION`.
+    pub target_duration: Option<u64>,
+    /// The sequence number of the first segment, from `#EXT-X-MEDIA-SEQUENCE`.
+    pub media_sequence: u64,
+    /// The type of the playlist from `#EXT-X-PLAYLIST-TYPE`, "VOD" or "EVENT", if present.
+    pub playlist_type: Option<String>,
+    /// Whether the playlist is complete, i.e. ends with `#EXT-X-ENDLIST`.
+    pub ended: bool,
+    pub segments: Vec<HlsSegment>,
+}
+
+impl HlsMediaPlaylist {
+    /// Parses the content of an HLS media playlist.
+    ///
+    /// Unknown directives are ignored, so a master playlist gives a playlist without segments.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the media playlist.
+    ///
+    pub fn parse(content: &str) -> Self {
+        let mut playlist = HlsMediaPlaylist::default();
+        let mut segment: Option<HlsSegment> = None;
+        for line in content.lines().map(str::trim) {
+            if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+                playlist.target_duration = value.trim().parse().ok();
+            } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+                playlist.media_sequence = value.trim().parse().unwrap_or_default();
+            } else if let Some(value) = line.strip_prefix("#EXT-X-PLAYLIST-TYPE:") {
+                playlist.playlist_type = Some(value.trim().to_string());
+            } else if line.starts_with("#EXT-X-ENDLIST") {
+                playlist.ended = true;
+            } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+                let (duration, title) = value.split_once(',').unwrap_or((value, ""));
+                segment = Some(HlsSegment {
+                    duration: duration.trim().parse().unwrap_or_default(),
+                    title: title.trim().to_string(),
+                    ..Default::default()
+                });
+            } else if !line.is_empty() && !line.starts_with('#') {
+                if let Some(mut segment) = segment.take() {
+                    segment.uri = line.to_string();
+                    playlist.segments.push(segment);
+                }
+            }
+        }
+        playlist
+    }
+
+    /// Returns the number of segments listed in the playlist.
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns whether the playlist is a live stream, to which the server keeps adding segments.
+    ///
+    /// A playlist is live unless it ends with `#EXT-X-ENDLIST` or is of type "VOD".
+    pub fn is_live(&self) -> bool {
+        !self.ended && self.playlist_type.as_deref() != Some("VOD")
+    }
+
+    /// Returns the sum of the durations of the listed segments in seconds.
+    ///
+    /// For a VOD playlist this is the duration of the whole stream; for a live playlist it is only
+    /// the duration of the window of segments currently available.
+    pub fn total_duration(&self) -> f64 {
+        self.segments.iter().map(|segment| segment.duration).sum()
+    }
+}
+
+/// Struct representing a variant of an HLS master playlist, from its `#EXT-X-STREAM-INF` line.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HlsVariant {
+    /// The URI of the media playlist of the variant, possibly relative to the master playlist.
+    pub uri: String,
+    /// The peak bitrate of the variant in bits per second.
+    pub bandwidth: u64,
+    /// The `(width, height)` of the video of the variant, if known.
+    pub resolution: Option<(u32, u32)>,
+    pub codecs: Option<String>,
+}
+
+/// Struct representing an HLS master playlist, listing the variants of a stream.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HlsMasterPlaylist {
+    pub variants: Vec<HlsVariant>,
+}
+
+/// Enum representing how a variant is chosen from an HLS master playlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariantStrategy {
+    /// The variant with the highest bandwidth.
+    HighestBandwidth,
+    /// The variant with the lowest bandwidth.
+    LowestBandwidth,
+    /// The variant whose resolution is closest to the given `(width, height)`, comparing the
+    /// number of pixels. Variants without a resolution are only chosen if no variant has one.
+    Closest(u32, u32),
+}
+
+impl HlsMasterPlaylist {
+    /// Parses the content of an HLS master playlist.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the master playlist.
+    ///
+    pub fn parse(content: &str) -> Self {
+        let mut variants = vec![];
+        let mut variant: Option<HlsVariant> = None;
+        for line in content.lines().map(str::trim) {
+            if line.starts_with("#EXT-X-STREAM-INF:") {
+                let resolution = hls_attribute(line, "RESOLUTION")
+                    .and_then(|resolution| resolution.split_once('x'))
+                    .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+                variant = Some(HlsVariant {
+                    bandwidth: hls_attribute(line, "BANDWIDTH")
+                        .and_then(|bandwidth| bandwidth.parse().ok())
+                        .unwrap_or_default(),
+                    resolution,
+                    codecs: hls_attribute(line, "CODECS").map(String::from),
+                    ..Default::default()
+                });
+            } else if !line.is_empty() && !line.starts_with('#') {
+                if let Some(mut variant) = variant.take() {
+                    variant.uri = line.to_string();
+                    variants.push(variant);
+                }
+            }
+        }
+        HlsMasterPlaylist { variants }
+    }
+
+    /// Chooses a variant following the given strategy.
+    ///
+    /// # Returns
+    ///
+    /// The chosen `HlsVariant`, or `None` if the playlist has no variants.
+    ///
+    pub fn select(&self, strategy: VariantStrategy) -> Option<&HlsVariant> {
+        match strategy {
+            VariantStrategy::HighestBandwidth => {
+                self.variants.iter().max_by_key(|variant| variant.bandwidth)
+            }
+            VariantStrategy::LowestBandwidth => {
+                self.variants.iter().min_by_key(|variant| variant.bandwidth)
+            }
+            VariantStrategy::Closest(width, height) => {
+                let target = width as i64 * height as i64;
+                self.variants
+                    .iter()
+                    .filter(|variant| variant.resolution.is_some())
+                    .min_by_key(|variant| {
+                        let (width, height) = variant.resolution.unwrap_or_default();
+                        (width as i64 * height as i64 - target).abs()
+                    })
+                    .or_else(|| self.variants.first())
+            }
+        }
+    }
+}
+
+/// Returns the value of the attribute `NAME=value` or `NAME="value"` of an HLS directive line.
+pub(crate) fn hls_attribute<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let (_, attributes) = line.split_once(':')?;
+    let mut rest = attributes;
+    while !rest.is_empty() {
+        let (key, value) = rest.split_once('=')?;
+        let (value, next) = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                let next = quoted[end + 1..].trim_start_matches(',');
+                (&quoted[..end], next)
+            }
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+        if key.trim() == name {
+            return Some(value);
+        }
+        rest = next;
+    }
+    None
+}
+
+/// Returns the title of a variant of an HLS master playlist, from its resolution or bandwidth.
+pub(crate) fn variant_title(line: &str) -> String {
+    match (
+        hls_attribute(line, "RESOLUTION"),
+        hls_attribute(line, "BANDWIDTH"),
+    ) {
+        (Some(resolution), _) => resolution.to_string(),
+        (None, Some(bandwidth)) => format!("{} bps", bandwidth),
+        (None, None) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        hls_attribute, variant_title, HlsMasterPlaylist, HlsMediaPlaylist, VariantStrategy,
+    };
+
+    #[test]
+    fn test_hls_media_playlist() {
+        let vod = HlsMediaPlaylist::parse(
+            "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-TARGETDURATION:10\n#EXT-X-PLAYLIST-TYPE:VOD\n\
+             #EXTINF:9.009,\nsegment0.ts\n#EXTINF:9.009,Intro\nsegment1.ts\n\
+             #EXTINF:3.003,\nsegment2.ts\n#EXT-X-ENDLIST\n",
+        );
+        assert_eq!(vod.target_duration, Some(10));
+        assert_eq!(vod.segment_count(), 3);
+        assert_eq!(vod.segments[1].title, "Intro");
+        assert_eq!(vod.segments[2].uri, "segment2.ts");
+        assert!(!vod.is_live());
+        assert!((vod.total_duration() - 21.021).abs() < 1e-9);
+
+        let live = HlsMediaPlaylist::parse(
+            "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXT-X-MEDIA-SEQUENCE:2680\n\
+             #EXTINF:6.0,\nhttps://example.com/fileSequence2680.ts\n",
+        );
+        assert_eq!(live.media_sequence, 2680);
+        assert_eq!(live.segment_count(), 1);
+        assert!(live.is_live());
+    }
+
+    #[test]
+    fn test_hls_master_playlist() {
+        let master = HlsMasterPlaylist::parse(
+            "#EXTM3U\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=2560000,RESOLUTION=1280x720,CODECS=\"avc1.4d401f,mp4a.40.2\"\n\
+             mid/index.m3u8\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=7680000,RESOLUTION=1920x1080\nhigh/index.m3u8\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=640000,RESOLUTION=640x360\nlow/index.m3u8\n",
+        );
+        assert_eq!(master.variants.len(), 3);
+        assert_eq!(master.variants[0].resolution, Some((1280, 720)));
+        assert_eq!(
+            master.variants[0].codecs.as_deref(),
+            Some("avc1.4d401f,mp4a.40.2")
+        );
+
+        let uri = |strategy| master.select(strategy).map(|variant| variant.uri.as_str());
+        assert_eq!(
+            uri(VariantStrategy::HighestBandwidth),
+            Some("high/index.m3u8")
+        );
+        assert_eq!(
+            uri(VariantStrategy::LowestBandwidth),
+            Some("low/index.m3u8")
+        );
+        assert_eq!(
+            uri(VariantStrategy::Closest(1024, 576)),
+            Some("mid/index.m3u8")
+        );
+        assert_eq!(
+            HlsMasterPlaylist::default().select(VariantStrategy::HighestBandwidth),
+            None
+        );
+    }
+
+    #[test]
+    fn test_hls_attribute() {
+        let line = r#"#EXT-X-STREAM-INF:BANDWIDTH=1280000,CODECS="avc1.4d401f,mp4a.40.2",RESOLUTION=1280x720"#;
+        assert_eq!(hls_attribute(line, "BANDWIDTH"), Some("1280000"));
+        assert_eq!(hls_attribute(line, "CODECS"), Some("avc1.4d401f,mp4a.40.2"));
+        assert_eq!(hls_attribute(line, "RESOLUTION"), Some("1280x720"));
+        assert_eq!(hls_attribute(line, "FRAME-RATE"), None);
+        assert_eq!(variant_title(line), "1280x720");
+        assert_eq!(
+            variant_title("#EXT-X-STREAM-INF:BANDWIDTH=640000"),
+            "640000 bps"
+        );
+    }
+}
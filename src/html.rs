@@ -0,0 +1,170 @@
+use crate::Info;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;background:#fafafa;color:#222}\
+.channels{display:grid;grid-template-columns:repeat(auto-fill,minmax(180px,1fr));gap:1em}\
+.channel{background:#fff;border:1px solid #ddd;border-radius:8px;padding:1em;text-align:center}\
+.channel img,.channel .logo{width:96px;height:96px;object-fit:contain;margin:0 auto .5em;display:block}\
+.channel a{display:block;font-weight:bold;color:#0645ad;text-decoration:none;margin-bottom:.3em}\
+.category{display:block;color:#666;font-size:.85em}\
+.status{display:inline-block;margin-top:.4em;padding:.1em .6em;border-radius:1em;font-size:.75em;color:#fff;background:#888}\
+.status.good{background:#2e7d32}.status.bad{background:#c62828}";
+
+/// Escapes the text for the content and the attribute values of an HTML page.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the link, replacing it with `#` if it uses a scheme running code when clicked, as
+/// the page may be built from an untrusted playlist.
+fn escape_link(link: &str) -> String {
+    let scheme = link
+        .split(':')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    match link.contains(':') && ["javascript", "vbscript", "data"].contains(&scheme.as_str()) {
+        true => String::from("#"),
+        false => escape(link),
+    }
+}
+
+fn render_channel(info: &Info) -> String {
+    let logo = match info.logo.is_empty() {
+        true => String::from("<div class=\"logo\"></div>"),
+        false => format!(
+            "<img src=\"{}\" alt=\"\" loading=\"lazy\">",
+            escape_link(&info.logo)
+        ),
+    };
+    let category = match info.category.is_empty() {
+        true => String::new(),
+        false => format!("<span class=\"category\">{}</span>", escape(&info.category)),
+    };
+    let status = match info.status.is_empty() {
+        true => String::new(),
+        false => format!(
+            "<span class=\"status {}\">{}</span>",
+            escape(&info.status.to_lowercase()),
+            escape(&info.status)
+        ),
+    };
+    format!(
+        "<div class=\"channel\">{}<a href=\"{}\">{}</a>{}{}</div>",
+        logo,
+        escape_link(&info.url),
+        escape(&info.title),
+        category,
+        status
+    )
+}
+
+fn render_channels(streams_info: &[&Info]) -> String {
+    let channels: Vec<String> = streams_info
+        .iter()
+        .map(|info| render_channel(info))
+        .collect();
+    format!("<div class=\"channels\">\n{}\n</div>", channels.join("\n"))
+}
+
+/// Renders the stream information as a self-contained HTML page showing the logo, title,
+/// category and status of each channel, with a link to its stream.
+///
+/// With `grouped`, the channels are listed under a heading per category, in the order of their
+/// first appearance, followed by the channels without category.
+pub(crate) fn render_html(streams_info: &[Info], grouped: bool) -> String {
+    let body = match grouped {
+        false => render_channels(&streams_info.iter().collect::<Vec<&Info>>()),
+        true => {
+            let mut groups: Vec<(&str, Vec<&Info>)> = vec![];
+            for info in streams_info {
+                match groups
+                    .iter_mut()
+                    .find(|(category, _)| *category == info.category)
+                {
+                    Some((_, infos)) => infos.push(info),
+                    None => groups.push((&info.category, vec![info])),
+                }
+            }
+            groups.sort_by_key(|(category, _)| category.is_empty());
+            let sections: Vec<String> = groups
+                .iter()
+                .map(|(category, infos)| {
+                    let heading = match category.is_empty() {
+                        true => "Uncategorized",
+                        false => category,
+                    };
+                    format!(
+                        "<h2>{} ({})</h2>\n{}",
+                        escape(heading),
+                        infos.len(),
+                        render_channels(infos)
+                    )
+                })
+                .collect();
+            sections.join("\n")
+        }
+    };
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>Channels</title>\n<style>{}</style>\n</head>\n<body>\n\
+         <h1>Channels ({})</h1>\n{}\n</body>\n</html>\n",
+        STYLE,
+        streams_info.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_link, render_html};
+    use crate::Info;
+
+    #[test]
+    fn test_render_html() {
+        let info = |title: &str, category: &str, url: &str| Info {
+            title: title.to_string(),
+            category: category.to_string(),
+            url: url.to_string(),
+            status: String::from("GOOD"),
+            ..Default::default()
+        };
+        let streams_info = vec![
+            info("Tom & Jerry", "Kids", "http://example.com/tj.m3u8"),
+            info("Radio", "", "http://example.com/radio.mp3"),
+            info("<b>News</b>", "News", "javascript:alert(1)"),
+            info("Cartoons", "Kids", "http://example.com/cartoons.m3u8"),
+        ];
+
+        let html = render_html(&streams_info, false);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(
+            "<a href=\"http://example.com/tj.m3u8\">Tom &amp; Jerry</a>\
+             <span class=\"category\">Kids</span><span class=\"status good\">GOOD</span>"
+        ));
+        assert!(html.contains("<a href=\"#\">&lt;b&gt;News&lt;/b&gt;</a>"));
+        assert!(!html.contains("<h2>"));
+
+        let html = render_html(&streams_info, true);
+        let kids = html.find("<h2>Kids (2)</h2>").unwrap();
+        let news = html.find("<h2>News (1)</h2>").unwrap();
+        let uncategorized = html.find("<h2>Uncategorized (1)</h2>").unwrap();
+        assert!(kids < news && news < uncategorized);
+        assert!(html.find("Cartoons").unwrap() < news);
+
+        assert_eq!(escape_link(" JavaScript:alert(1)"), "#");
+        assert_eq!(escape_link("/logos/a\"b.png"), "/logos/a&quot;b.png");
+    }
+}
@@ -0,0 +1,24 @@
+/// Enum representing how HTTP redirects are handled when downloading playlists and checking
+/// streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    /// Follow up to the given number of redirects; a longer chain is an error.
+    Limited(usize),
+    /// Do not follow redirects; the redirect response itself is the result.
+    None,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy::Limited(10)
+    }
+}
+
+impl RedirectPolicy {
+    pub(crate) fn to_reqwest(self) -> reqwest::redirect::Policy {
+        match self {
+            RedirectPolicy::Limited(max) => reqwest::redirect::Policy::limited(max),
+            RedirectPolicy::None => reqwest::redirect::Policy::none(),
+        }
+    }
+}
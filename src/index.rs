@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::Info;
+
+/// Index of stream information by `tvg-id` and by URL, borrowed from a parser.
+///
+/// Entries with an empty `tvg-id` are only indexed by URL. When several entries share a key, the
+/// first one is indexed.
+#[derive(Debug, Clone, Default)]
+pub struct StreamIndex<'a> {
+    by_tvg_id: HashMap<&'a str, &'a Info>,
+    by_url: HashMap<&'a str, &'a Info>,
+}
+
+impl<'a> StreamIndex<'a> {
+    pub(crate) fn new(streams_info: &'a [Info]) -> Self {
+        let mut index = StreamIndex::default();
+        for info in streams_info {
+            if !info.tvg.id.is_empty() {
+                index.by_tvg_id.entry(&info.tvg.id).or_insert(info);
+            }
+            index.by_url.entry(&info.url).or_insert(info);
+        }
+        index
+    }
+
+    /// Returns the stream information with the given `tvg-id`.
+    pub fn get_by_tvg_id(&self, id: &str) -> Option<&'a Info> {
+        self.by_tvg_id.get(id).copied()
+    }
+
+    /// Returns the stream information with the given URL.
+    pub fn get_by_url(&self, url: &str) -> Option<&'a Info> {
+        self.by_url.get(url).copied()
+    }
+
+    /// Returns the index by `tvg-id`.
+    pub fn by_tvg_id(&self) -> &HashMap<&'a str, &'a Info> {
+        &self.by_tvg_id
+    }
+
+    /// Returns the index by URL.
+    pub fn by_url(&self) -> &HashMap<&'a str, &'a Info> {
+        &self.by_url
+    }
+}
+
+/// Positions of the stream information by `tvg-id` and by URL, cached by the parser.
+#[derive(Debug, Default)]
+pub(crate) struct PositionIndex {
+    pub(crate) by_tvg_id: HashMap<String, usize>,
+    pub(crate) by_url: HashMap<String, usize>,
+}
+
+impl PositionIndex {
+    pub(crate) fn new(streams_info: &[Info]) -> Self {
+        let mut index = PositionIndex::default();
+        for (position, info) in streams_info.iter().enumerate() {
+            if !info.tvg.id.is_empty() {
+                index
+                    .by_tvg_id
+                    .entry(info.tvg.id.clone())
+                    .or_insert(position);
+            }
+            index.by_url.entry(info.url.clone()).or_insert(position);
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamIndex;
+    use crate::{Info, Tvg};
+
+    #[test]
+    fn test_stream_index() {
+        let info = |title: &str, id: &str| Info {
+            title: title.to_string(),
+            url: format!("http://example.com/{}.m3u8", title),
+            tvg: Tvg {
+                id: id.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let streams_info = vec![
+            info("one", "one.uk"),
+            info("two", ""),
+            info("dup", "one.uk"),
+        ];
+        let index = StreamIndex::new(&streams_info);
+
+        assert_eq!(index.get_by_tvg_id("one.uk").unwrap().title, "one");
+        assert!(index.get_by_tvg_id("").is_none());
+        assert_eq!(
+            index
+                .get_by_url("http://example.com/two.m3u8")
+                .unwrap()
+                .title,
+            "two"
+        );
+        assert_eq!(index.by_tvg_id().len(), 1);
+        assert_eq!(index.by_url().len(), 3);
+    }
+}
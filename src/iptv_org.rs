@@ -0,0 +1,298 @@
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{title_from_location, Country, Info, Language, Tvg};
+
+/// Struct representing the options of `M3uParser::from_iptv_org`, selecting the channels loaded
+/// from the iptv-org API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiOptions {
+    /// The base URL of the API, serving `channels.json`, `streams.json`, `guides.json`,
+    /// `languages.json` and `countries.json`.
+    pub base_url: String,
+    /// The ISO 3166-1 alpha-2 codes of the countries of the channels to load, e.g. "GB", or all
+    /// countries if empty.
+    pub countries: Vec<String>,
+    /// The ids of the categories of the channels to load, e.g. "news", or all categories if
+    /// empty.
+    pub categories: Vec<String>,
+    /// Whether to load the channels marked as not safe for work.
+    pub include_nsfw: bool,
+    /// Whether to load the channels that closed.
+    pub include_closed: bool,
+}
+
+impl Default for ApiOptions {
+    fn default() -> Self {
+        ApiOptions {
+            base_url: String::from("https://iptv-org.github.io/api"),
+            countries: vec![],
+            categories: vec![],
+            include_nsfw: false,
+            include_closed: false,
+        }
+    }
+}
+
+impl ApiOptions {
+    /// Returns the URL of a file of the API, e.g. "channels.json".
+    pub(crate) fn url(&self, file: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), file)
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Channel {
+    id: String,
+    name: String,
+    country: String,
+    subdivision: Option<String>,
+    categories: Vec<String>,
+    languages: Vec<String>,
+    is_nsfw: bool,
+    closed: Option<String>,
+    website: Option<String>,
+    logo: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Stream {
+    channel: Option<String>,
+    feed: Option<String>,
+    title: Option<String>,
+    url: String,
+    referrer: Option<String>,
+    http_referrer: Option<String>,
+    user_agent: Option<String>,
+    quality: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Guide {
+    channel: Option<String>,
+    site: String,
+    site_id: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct NamedCode {
+    code: String,
+    name: String,
+}
+
+/// The JSON documents of the API.
+pub(crate) struct ApiData {
+    pub(crate) channels: String,
+    pub(crate) streams: String,
+    pub(crate) guides: String,
+    pub(crate) languages: String,
+    pub(crate) countries: String,
+}
+
+/// Returns the category id with its first letter uppercased, e.g. "News" for "news".
+fn category_name(id: &str) -> String {
+    let mut chars = id.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn names(json: &str) -> serde_json::Result<HashMap<String, String>> {
+    let entries: Vec<NamedCode> = serde_json::from_str(json)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| (entry.code, entry.name))
+        .collect())
+}
+
+/// Builds the stream information of the streams of the API, enriched with the country,
+/// languages, categories and website of their channel and with their guide.
+///
+/// Streams without channel are only kept when no country or category is selected.
+pub(crate) fn build(data: &ApiData, options: &ApiOptions) -> serde_json::Result<Vec<Info>> {
+    let channels: Vec<Channel> = serde_json::from_str(&data.channels)?;
+    let channels: HashMap<&str, &Channel> = channels
+        .iter()
+        .map(|channel| (channel.id.as_str(), channel))
+        .collect();
+    let streams: Vec<Stream> = serde_json::from_str(&data.streams)?;
+    let guides: Vec<Guide> = serde_json::from_str(&data.guides)?;
+    let mut guides_by_channel: HashMap<&str, &Guide> = HashMap::new();
+    for guide in &guides {
+        if let Some(channel) = &guide.channel {
+            guides_by_channel.entry(channel).or_insert(guide);
+        }
+    }
+    let languages = names(&data.languages)?;
+    let countries = names(&data.countries)?;
+
+    let is_selected = |channel: Option<&Channel>| match channel {
+        Some(channel) => {
+            (options.include_nsfw || !channel.is_nsfw)
+                && (options.include_closed || channel.closed.is_none())
+                && (options.countries.is_empty()
+                    || options
+                        .countries
+                        .iter()
+                        .any(|code| code.eq_ignore_ascii_case(&channel.country)))
+                && (options.categories.is_empty()
+                    || channel
+                        .categories
+                        .iter()
+                        .any(|category| options.categories.contains(category)))
+        }
+        None => options.countries.is_empty() && options.categories.is_empty(),
+    };
+
+    let mut streams_info = vec![];
+    for stream in streams {
+        let channel = stream
+            .channel
+            .as_deref()
+            .and_then(|id| channels.get(id).copied());
+        if stream.url.is_empty() || !is_selected(channel) {
+            continue;
+        }
+        let channel = channel.map(|channel| (channel.id.as_str(), channel));
+
+        let mut extra_attributes = BTreeMap::new();
+        let mut extra = |field: &str, value: Option<&String>| {
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                extra_attributes.insert(field.to_string(), value.clone());
+            }
+        };
+        extra("quality", stream.quality.as_ref());
+        extra("feed", stream.feed.as_ref());
+        if let Some((id, channel)) = channel {
+            extra("website", channel.website.as_ref());
+            extra("subdivision", channel.subdivision.as_ref());
+            if let Some(guide) = guides_by_channel.get(id) {
+                extra("guide_site", Some(&guide.site));
+                extra("guide_site_id", Some(&guide.site_id));
+            }
+        }
+
+        let title = match (channel, &stream.title) {
+            (Some((_, channel)), _) => channel.name.clone(),
+            (None, Some(title)) => title.clone(),
+            (None, None) => title_from_location(&stream.url),
+        };
+        let mut info = Info {
+            title,
+            url: stream.url.clone(),
+            user_agent: stream.user_agent.clone(),
+            referrer: stream.referrer.clone().or(stream.http_referrer.clone()),
+            extra_attributes,
+            ..Default::default()
+        };
+        if let Some((id, channel)) = channel {
+            info.logo = channel.logo.clone().unwrap_or_default();
+            info.categories = channel
+                .categories
+                .iter()
+                .map(|category| category_name(category))
+                .collect();
+            info.category = info.categories.first().cloned().unwrap_or_default();
+            info.tvg = Tvg {
+                id: id.to_string(),
+                name: channel.name.clone(),
+                ..Default::default()
+            };
+            info.country = Country {
+                name: countries.get(&channel.country).cloned().unwrap_or_default(),
+                code: channel.country.clone(),
+            };
+            info.timezone = info.country.timezone();
+            if let Some(code) = channel.languages.first() {
+                info.language = Language {
+                    name: languages.get(code).cloned().unwrap_or_default(),
+                    code: code.clone(),
+                };
+            }
+        }
+        streams_info.push(info);
+    }
+    Ok(streams_info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build, ApiData, ApiOptions};
+
+    fn sample_data() -> ApiData {
+        ApiData {
+            channels: String::from(
+                r#"[
+                {"id": "BBCOne.uk", "name": "BBC One", "country": "UK", "categories": ["general"],
+                 "languages": ["eng"], "is_nsfw": false, "closed": null,
+                 "website": "https://www.bbc.co.uk/bbcone", "logo": "https://example.com/bbc.png"},
+                {"id": "Euronews.fr", "name": "Euronews", "country": "FR", "categories": ["news"],
+                 "languages": ["fra"], "is_nsfw": false, "closed": null},
+                {"id": "Closed.fr", "name": "Closed", "country": "FR", "categories": ["news"],
+                 "is_nsfw": false, "closed": "2020-01-01"}
+            ]"#,
+            ),
+            streams: String::from(
+                r#"[
+                {"channel": "BBCOne.uk", "feed": "HD", "url": "http://example.com/bbc.m3u8",
+                 "referrer": null, "user_agent": "Mozilla/5.0", "quality": "1080p"},
+                {"channel": "Euronews.fr", "url": "http://example.com/euronews.m3u8"},
+                {"channel": "Closed.fr", "url": "http://example.com/closed.m3u8"},
+                {"channel": null, "title": "Unknown", "url": "http://example.com/unknown.m3u8"}
+            ]"#,
+            ),
+            guides: String::from(
+                r#"[{"channel": "BBCOne.uk", "site": "bbc.co.uk", "site_id": "bbc_one", "lang": "en"}]"#,
+            ),
+            languages: String::from(
+                r#"[{"code": "eng", "name": "English"}, {"code": "fra", "name": "French"}]"#,
+            ),
+            countries: String::from(
+                r#"[{"code": "UK", "name": "United Kingdom"}, {"code": "FR", "name": "France"}]"#,
+            ),
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let streams_info = build(&sample_data(), &ApiOptions::default()).unwrap();
+        let titles: Vec<&str> = streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["BBC One", "Euronews", "Unknown"]);
+
+        let bbc = &streams_info[0];
+        assert_eq!(bbc.tvg.id, "BBCOne.uk");
+        assert_eq!(bbc.category, "General");
+        assert_eq!(bbc.country.name, "United Kingdom");
+        assert_eq!(bbc.language.name, "English");
+        assert_eq!(bbc.user_agent.as_deref(), Some("Mozilla/5.0"));
+        assert_eq!(
+            bbc.extra_attributes["website"],
+            "https://www.bbc.co.uk/bbcone"
+        );
+        assert_eq!(bbc.extra_attributes["guide_site_id"], "bbc_one");
+        assert_eq!(bbc.extra_attributes["quality"], "1080p");
+
+        let options = ApiOptions {
+            countries: vec![String::from("fr")],
+            include_closed: true,
+            ..Default::default()
+        };
+        let streams_info = build(&sample_data(), &options).unwrap();
+        assert_eq!(streams_info.len(), 2);
+        assert_eq!(streams_info[1].title, "Closed");
+
+        let options = ApiOptions {
+            categories: vec![String::from("news")],
+            ..Default::default()
+        };
+        assert_eq!(build(&sample_data(), &options).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,322 @@
+use serde_json::{Map, Value};
+use std::io::Write;
+
+/// Enum representing the naming convention of the keys in the JSON output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyCase {
+    /// Keys are written as they are named in `Info`, e.g. `tvg_id`.
+    #[default]
+    SnakeCase,
+    /// Keys are written in camel case, e.g. `tvgId`.
+    CamelCase,
+}
+
+/// Struct representing the options used to serialize the stream information to JSON.
+#[derive(Debug, Clone, Default)]
+pub struct JsonOptions {
+    /// Whether to format the JSON output in a pretty, human-readable way.
+    pub pretty: bool,
+    /// Whether to drop empty strings and empty objects from the output.
+    pub skip_empty: bool,
+    /// The naming convention of the keys.
+    pub key_case: KeyCase,
+    /// Whether to flatten nested objects, e.g. `{"tvg": {"id": ..}}` into `{"tvg_id": ..}`.
+    pub flatten: bool,
+    /// Whether to sort the keys of every object alphabetically, for deterministic output that
+    /// diffs well, e.g. when the export is committed to git.
+    pub sort_keys: bool,
+    /// Whether to escape non-ASCII characters as `\uXXXX`.
+    pub ascii: bool,
+    /// Whether to end the output with a newline.
+    pub trailing_newline: bool,
+}
+
+/// Serialize a value to a JSON string according to the given options.
+///
+/// # Arguments
+///
+/// * `data` - The value to serialize.
+/// * `options` - The options controlling the output.
+///
+/// # Returns
+///
+/// A `serde_json::Result<String>` containing the JSON output.
+///
+pub fn to_string<T: serde::Serialize + ?Sized>(
+    data: &T,
+    options: &JsonOptions,
+) -> serde_json::Result<String> {
+    let mut value = serde_json::to_value(data)?;
+    if options.skip_empty {
+        remove_empty_fields(&mut value);
+    }
+    if options.flatten {
+        flatten(&mut value);
+    }
+    if options.key_case == KeyCase::CamelCase {
+        camel_case_keys(&mut value);
+    }
+    if options.sort_keys {
+        sort_keys(&mut value);
+    }
+    let mut output = if options.pretty {
+        serde_json::to_string_pretty(&value)?
+    } else {
+        serde_json::to_string(&value)?
+    };
+    if options.ascii {
+        output = escape_non_ascii(&output);
+    }
+    if options.trailing_newline {
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// Sort the keys of every object alphabetically.
+fn sort_keys(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(sort_keys),
+        Value::Object(map) => {
+            let mut fields: Vec<(String, Value)> = std::mem::take(map).into_iter().collect();
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, mut field) in fields {
+                sort_keys(&mut field);
+                map.insert(key, field);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Escape the non-ASCII characters of serialized JSON as `\uXXXX`, using surrogate pairs outside
+/// the Basic Multilingual Plane. Non-ASCII characters can only appear inside strings.
+fn escape_non_ascii(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+        } else {
+            let mut units = [0; 2];
+            for unit in c.encode_utf16(&mut units) {
+                escaped.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+    escaped
+}
+
+/// Writes a slice as a JSON array element by element, producing the same output as `to_string`
+/// without holding it in memory.
+///
+/// # Arguments
+///
+/// * `writer` - The writer to write the JSON output to.
+/// * `items` - The elements of the array.
+/// * `options` - The options controlling the output.
+///
+pub(crate) fn write_array<W: Write, T: serde::Serialize>(
+    writer: &mut W,
+    items: &[T],
+    options: &JsonOptions,
+) -> std::io::Result<()> {
+    let end = if options.trailing_newline { "\n" } else { "" };
+    if items.is_empty() {
+        return writer.write_all(format!("[]{}", end).as_bytes());
+    }
+    let element_options = JsonOptions {
+        trailing_newline: false,
+        ..options.clone()
+    };
+
+    writer.write_all(if options.pretty { b"[\n" } else { b"[" })?;
+    for (position, item) in items.iter().enumerate() {
+        if position > 0 {
+            writer.write_all(if options.pretty { b",\n" } else { b"," })?;
+        }
+        let element = to_string(item, &element_options)?;
+        if options.pretty {
+            // Indent the element as if it were serialized inside the array.
+            let lines: Vec<String> = element.lines().map(|line| format!("  {}", line)).collect();
+            writer.write_all(lines.join("\n").as_bytes())?;
+        } else {
+            writer.write_all(element.as_bytes())?;
+        }
+    }
+    writer.write_all(if options.pretty { b"\n]" } else { b"]" })?;
+    writer.write_all(end.as_bytes())
+}
+
+/// Remove the fields that hold no information from a JSON value.
+///
+/// Nulls, empty strings, empty arrays, and objects which become empty once their own empty fields
+/// are removed, are dropped recursively. This is used when the schema is not enforced so that only the
+/// attributes actually found in the playlist are present in the output.
+///
+/// # Arguments
+///
+/// * `value` - The JSON value to clean up in place.
+///
+pub fn remove_empty_fields(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(remove_empty_fields),
+        Value::Object(map) => {
+            map.values_mut().for_each(remove_empty_fields);
+            map.retain(|_, field| !is_empty(field));
+        }
+        _ => {}
+    }
+}
+
+/// Flatten nested objects inside each object, joining the keys with an underscore.
+fn flatten(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(flatten),
+        Value::Object(map) => {
+            let mut flattened = Map::new();
+            for (key, mut field) in std::mem::take(map) {
+                flatten(&mut field);
+                match field {
+                    Value::Object(nested) => {
+                        for (nested_key, nested_field) in nested {
+                            flattened.insert(format!("{}_{}", key, nested_key), nested_field);
+                        }
+                    }
+                    field => {
+                        flattened.insert(key, field);
+                    }
+                }
+            }
+            *map = flattened;
+        }
+        _ => {}
+    }
+}
+
+/// Rename every object key from snake case to camel case.
+fn camel_case_keys(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(camel_case_keys),
+        Value::Object(map) => {
+            *map = std::mem::take(map)
+                .into_iter()
+                .map(|(key, mut field)| {
+                    camel_case_keys(&mut field);
+                    (to_camel_case(&key), field)
+                })
+                .collect();
+        }
+        _ => {}
+    }
+}
+
+fn to_camel_case(key: &str) -> String {
+    let mut camel = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::String(string) => string.is_empty(),
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remove_empty_fields, to_string, write_array, JsonOptions, KeyCase};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_remove_empty_fields() {
+        let mut value = json!([{
+            "title": "BBC One",
+            "logo": "",
+            "album": null,
+            "tvg": {"id": "", "name": "", "url": ""},
+            "country": {"code": "GB", "name": ""},
+        }]);
+        remove_empty_fields(&mut value);
+        assert_eq!(
+            value,
+            json!([{"title": "BBC One", "country": {"code": "GB"}}])
+        );
+    }
+
+    #[test]
+    fn test_write_array() {
+        let value = json!([
+            {"title": "BBC One", "tvg": {"id": "bbcone.uk"}, "categories": ["News", "UK"]},
+            {"title": "TF1", "tvg": {"id": ""}, "categories": []},
+        ]);
+        let items = value.as_array().unwrap();
+        for pretty in [false, true] {
+            let options = JsonOptions {
+                pretty,
+                sort_keys: pretty,
+                trailing_newline: pretty,
+                ..Default::default()
+            };
+            let mut output = vec![];
+            write_array(&mut output, items, &options).unwrap();
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                to_string(items, &options).unwrap()
+            );
+
+            let mut output = vec![];
+            write_array::<_, Value>(&mut output, &[], &options).unwrap();
+            assert_eq!(output, to_string(&json!([]), &options).unwrap().as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_sort_keys_and_ascii() {
+        let value = json!([{"title": "Télé 🎬", "logo": "", "tvg": {"name": "b", "id": "a"}}]);
+        let options = JsonOptions {
+            sort_keys: true,
+            ascii: true,
+            trailing_newline: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string(&value, &options).unwrap(),
+            "[{\"logo\":\"\",\"title\":\"T\\u00e9l\\u00e9 \\ud83c\\udfac\",\
+             \"tvg\":{\"id\":\"a\",\"name\":\"b\"}}]\n"
+        );
+        let parsed: Value = serde_json::from_str(&to_string(&value, &options).unwrap()).unwrap();
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn test_flatten_and_camel_case() {
+        let value = json!([{
+            "title": "BBC One",
+            "tvg": {"id": "bbcone.uk", "name": ""},
+        }]);
+        let options = JsonOptions {
+            skip_empty: true,
+            key_case: KeyCase::CamelCase,
+            flatten: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string(&value, &options).unwrap(),
+            r#"[{"title":"BBC One","tvgId":"bbcone.uk"}]"#
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use serde::Serialize;
+
+/// Enum representing the type of a playlist, detected from its directives and attributes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize)]
+pub enum PlaylistKind {
+    /// An extended M3U playlist of channels with `tvg-*` or `group-title` attributes, as published
+    /// by IPTV providers.
+    #[default]
+    IptvExtended,
+    /// A list of URLs or paths without `#EXTINF` lines.
+    PlainM3u,
+    /// An HLS master playlist listing the variants of a stream with `#EXT-X-STREAM-INF`.
+    HlsMaster,
+    /// An HLS media playlist listing the segments of a single stream.
+    HlsMedia,
+    /// An extended M3U playlist of audio or video files, with durations or `#EXTALB`, `#EXTART`
+    /// and `#EXTIMG` directives but no IPTV attributes.
+    AudioPlaylist,
+}
+
+impl PlaylistKind {
+    /// Detects the type of the playlist from its trimmed lines.
+    pub(crate) fn detect<'a>(lines: impl Iterator<Item = &'a str>) -> Self {
+        let mut has_media_directives = false;
+        let mut has_extinf = false;
+        let mut has_iptv_attributes = false;
+        let mut has_audio_directives = false;
+        let mut has_durations = true;
+        for line in lines.filter(|line| line.starts_with('#')) {
+            if line.starts_with("#EXT-X-STREAM-INF") {
+                return PlaylistKind::HlsMaster;
+            } else if line.starts_with("#EXT-X-TARGETDURATION")
+                || line.starts_with("#EXT-X-MEDIA-SEQUENCE")
+            {
+                has_media_directives = true;
+            } else if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+                has_extinf = true;
+                has_iptv_attributes |= line.contains("tvg-") || line.contains("group-title=");
+                has_durations &= !extinf.trim_start().starts_with('-');
+            } else if ["#EXTALB", "#EXTART", "#EXTIMG"]
+                .iter()
+                .any(|directive| line.starts_with(directive))
+            {
+                has_audio_directives = true;
+            }
+        }
+
+        if has_media_directives {
+            PlaylistKind::HlsMedia
+        } else if !has_extinf {
+            PlaylistKind::PlainM3u
+        } else if !has_iptv_attributes && (has_audio_directives || has_durations) {
+            PlaylistKind::AudioPlaylist
+        } else {
+            PlaylistKind::IptvExtended
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlaylistKind;
+
+    fn detect(content: &str) -> PlaylistKind {
+        PlaylistKind::detect(content.lines().map(str::trim))
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            detect("#EXTM3U\n#EXTINF:-1 tvg-id=\"a\",A\nhttp://example.com/a.m3u8"),
+            PlaylistKind::IptvExtended
+        );
+        assert_eq!(
+            detect("#EXTM3U\n#EXTINF:-1,A\nhttp://example.com/a.m3u8"),
+            PlaylistKind::IptvExtended
+        );
+        assert_eq!(
+            detect("http://example.com/a.mp3\nhttp://example.com/b.mp3"),
+            PlaylistKind::PlainM3u
+        );
+        assert_eq!(
+            detect("#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=1280000\nlow/index.m3u8"),
+            PlaylistKind::HlsMaster
+        );
+        assert_eq!(
+            detect("#EXTM3U\n#EXT-X-TARGETDURATION:10\n#EXTINF:9.009,\nsegment0.ts"),
+            PlaylistKind::HlsMedia
+        );
+        assert_eq!(
+            detect("#EXTM3U\n#EXTINF:215,Artist - Song\nsong.mp3"),
+            PlaylistKind::AudioPlaylist
+        );
+    }
+}
@@ -2,807 +2,7949 @@
 //!
 //! A library for parsing and manipulating M3U files.
 
+#[cfg(feature = "net")]
+mod acestream;
+mod asx;
+mod backup;
+#[cfg(feature = "net")]
+mod bitrate;
+mod blocklist;
+mod cache;
+mod canonical;
+mod categorize;
+mod checker;
+mod compact;
+mod config;
+mod diff;
+mod fetch;
+mod geo;
+mod hls;
+mod html;
+#[cfg(feature = "net")]
+mod http;
+mod index;
+mod iptv_org;
+mod json;
+mod kind;
 mod language;
+mod markdown;
+mod media;
+#[cfg(feature = "net")]
+mod monitor;
+mod multi;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "redact-debug")]
+mod redact;
+mod report;
+mod rules;
+mod search;
+mod snapshot;
+mod sort;
+mod stats;
+mod sync;
+mod template;
+mod validate;
+#[cfg(feature = "wasm-bindgen")]
+mod wasm;
+mod write;
 
+pub use blocklist::Blocklist;
+#[cfg(feature = "net")]
+pub use checker::HttpChecker;
+pub use checker::{CheckErrorKind, CheckResult, StreamChecker};
+pub use compact::{InfoCompact, Interner};
+pub use config::{CheckConfig, FilterConfig, OutputConfig, ParserConfig};
+pub use diff::{ChangedStream, FieldChange, PlaylistDiff};
+pub use fetch::HttpFetch;
+pub use hls::{HlsMasterPlaylist, HlsMediaPlaylist, HlsSegment, HlsVariant, VariantStrategy};
+#[cfg(feature = "net")]
+pub use http::RedirectPolicy;
+pub use index::StreamIndex;
+pub use iptv_org::ApiOptions;
+pub use json::{JsonOptions, KeyCase};
+pub use kind::PlaylistKind;
+pub use media::{AudioTrack, MediaInfo};
+#[cfg(feature = "net")]
+pub use monitor::{Monitor, StatusChange, StreamHealth};
+pub use multi::{MultiParseReport, SourceReport};
+pub use rules::{Rule, Rules};
+pub use snapshot::PlaylistSnapshot;
+pub use stats::PlaylistStats;
+pub use sync::{SyncConflict, SyncPolicy, SyncReport};
+pub use validate::{ValidationIssue, ValidationIssueKind};
+pub use write::{M3uAttribute, M3uWriteOptions};
+
+#[cfg(feature = "net")]
+use acestream::AceStreamEngine;
+#[cfg(feature = "net")]
+use cache::LivenessCache;
+use futures::future::BoxFuture;
+use index::PositionIndex;
 use once_cell::sync::Lazy;
+#[cfg(feature = "random")]
+use rand::rngs::StdRng;
+#[cfg(feature = "random")]
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+#[cfg(feature = "random")]
+use rand::{thread_rng, SeedableRng};
 use regex::Regex;
+#[cfg(feature = "net")]
+use reqwest::cookie::Jar;
+#[cfg(feature = "net")]
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json;
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::error::Error;
 use std::fs::{read_to_string, File};
-use std::io::Write;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::io::{BufWriter, Read, Write};
+use std::ops::{RangeBounds, RangeInclusive};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::OnceLock;
 use std::time::Duration;
 use std::vec;
 use url::Url;
 
+/// Records the value of a field of the current span, with the `tracing` feature.
+macro_rules! record_span {
+    ($field:literal, $value:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record($field, $value);
+    };
+}
+
 /// Struct representing the Tvg information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Tvg {
-    id: String,
-    name: String,
-    url: String,
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Tvg {
+    pub id: String,
+    pub name: String,
+    pub url: String,
 }
 
 /// Struct representing the Country information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Country {
-    code: String,
-    name: String,
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Country {
+    pub code: String,
+    pub name: String,
+}
+
+impl Country {
+    /// Returns the ISO 3166-1 alpha-2 codes of the country, uppercased, from the `tvg-country`
+    /// attribute which may hold several codes separated by `;`. The common "UK" is returned as
+    /// "GB", and values that are not two letters are left out.
+    pub fn codes(&self) -> Vec<String> {
+        self.code
+            .split(';')
+            .filter_map(normalize_country_code)
+            .collect()
+    }
+
+    /// Returns the main IANA time zone of the country, e.g. "Europe/London" for "GB", or `None`
+    /// if the country has no known code. With several codes, the zone of the first one is
+    /// returned.
+    pub fn timezone(&self) -> Option<String> {
+        let code = self.codes().into_iter().next()?;
+        geo::timezone(&code).map(String::from)
+    }
+
+    /// Returns the flag emoji of the country, e.g. "🇬🇧" for "GB", or `None` if the country has
+    /// no valid code. With several codes, the flag of the first one is returned.
+    pub fn flag_emoji(&self) -> Option<String> {
+        let code = self.codes().into_iter().next()?;
+        let flag = code
+            .chars()
+            .filter_map(|letter| char::from_u32(0x1F1E6 + (letter as u32 - 'A' as u32)))
+            .collect();
+        Some(flag)
+    }
 }
 
 /// Struct representing the Language information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Language {
-    code: String,
-    name: String,
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Language {
+    pub code: String,
+    pub name: String,
 }
 
 /// Struct representing the stream information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// With the `redact-debug` feature, its `Debug` output redacts the passwords, tokens and Xtream
+/// Codes credentials of the URLs.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(not(feature = "redact-debug"), derive(Debug))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Info {
-    title: String,
-    logo: String,
-    url: String,
-    category: String,
-    tvg: Tvg,
-    country: Country,
-    language: Language,
-    status: String,
+    pub title: String,
+    pub logo: String,
+    pub url: String,
+    /// Alternate URLs of the same channel, from the URL lines following the first one, tried in
+    /// order by `promote_first_good_url` when the stream is down.
+    #[serde(default)]
+    pub backup_urls: Vec<String>,
+    pub category: String,
+    /// All the groups of the entry when `group-title` holds several groups separated by `;`.
+    pub categories: Vec<String>,
+    pub tvg: Tvg,
+    pub country: Country,
+    pub language: Language,
+    pub status: String,
+    /// The duration of the entry in seconds, or `None` if it is unknown (`-1`), as for live streams.
+    pub duration: Option<f64>,
+    /// The album of the entry, from the `#EXTALB` directive.
+    pub album: Option<String>,
+    /// The artist of the entry, from the `#EXTART` directive.
+    pub artist: Option<String>,
+    /// The cover image of the entry, from the `#EXTIMG` directive.
+    pub image: Option<String>,
+    /// The `User-Agent` header of the stream, from the `#EXTVLCOPT:http-user-agent` option.
+    pub user_agent: Option<String>,
+    /// The `Referer` header of the stream, from the `#EXTVLCOPT:http-referrer` option.
+    pub referrer: Option<String>,
+    /// The codecs, resolution, bitrate and audio tracks of the stream, filled by `probe_streams`.
+    pub media: Option<MediaInfo>,
+    /// The details of the last availability check, or `None` if the stream was not checked.
+    pub check: Option<CheckResult>,
+    /// The URL the stream redirects to, recorded during the check if enabled with
+    /// `set_record_resolved_url`.
+    pub resolved_url: Option<String>,
+    /// The URL or path of the playlist the entry comes from, or `None` if the playlist was parsed
+    /// from a string or a reader.
+    pub source: Option<String>,
+    /// Free-form labels of the entry, such as "favorite" or "kids", from the `x-tags` attribute.
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    /// A note of the curator about the entry, such as "dies every weekend", written as `# note:`
+    /// lines before the `#EXTINF` line and parsed back from them.
+    pub comment: Option<String>,
+    /// Values of vendor-specific attributes captured by the extractors registered with
+    /// `extract_attribute` and `extract_regex`, by field name.
+    #[serde(default)]
+    pub extra_attributes: BTreeMap<String, String>,
+    /// The main IANA time zone of the country of the entry, e.g. "Europe/London", or `None` if
+    /// the country is unknown.
+    pub timezone: Option<String>,
+    /// When the entry was first parsed from its source, in seconds since the Unix epoch. It is
+    /// kept by `sync_from` when the entry is updated from a fresh playlist.
+    pub first_seen: Option<u64>,
+    /// When the entry was last parsed from its source, in seconds since the Unix epoch.
+    pub last_seen: Option<u64>,
+    /// The position of the entry in the source playlist, starting at 0.
+    pub source_index: usize,
+    /// The line of the `#EXTINF` line of the entry in the source playlist, starting at 1.
+    #[serde(skip)]
+    pub line_number: usize,
+    /// The original lines of the entry, from the `#EXTINF` line up to and including the URL line.
+    #[serde(skip)]
+    pub raw: Option<String>,
+    /// Hash of the entry as rendered right after parsing, used to detect modified entries.
+    #[serde(skip)]
+    raw_hash: u64,
 }
 
-/// M3U Parser struct for parsing and manipulating M3U files.
-pub struct M3uParser<'a> {
-    pub streams_info: Vec<Info>,
-    streams_info_backup: Vec<Info>,
-    lines: Vec<String>,
-    timeout: Duration,
-    enforce_schema: bool,
-    check_live: bool,
-    useragent: &'a str,
-    file_regex: Lazy<Regex>,
-    tvg_name_regex: Lazy<Regex>,
-    tvg_id_regex: Lazy<Regex>,
-    logo_regex: Lazy<Regex>,
-    category_regex: Lazy<Regex>,
-    title_regex: Lazy<Regex>,
-    country_regex: Lazy<Regex>,
-    language_regex: Lazy<Regex>,
-    tvg_url_regex: Lazy<Regex>,
-    streams_regex: Lazy<Regex>,
-}
-
-impl<'a> M3uParser<'a> {
-    /// Creates a new instance of M3uParser.
+impl Info {
+    /// Returns the title prefixed with the flag emoji of the country, e.g. "🇬🇧 BBC One HD", or
+    /// the title alone if the entry has no valid country code.
+    pub fn display_title(&self) -> String {
+        match self.country.flag_emoji() {
+            Some(flag) if !self.title.is_empty() => format!("{} {}", flag, self.title),
+            _ => self.title.clone(),
+        }
+    }
+
+    /// Returns the HTTP headers to send when requesting the stream, as set by the `#EXTVLCOPT`
+    /// options of the entry.
+    pub fn http_headers(&self) -> Vec<(&str, &str)> {
+        let mut headers = vec![];
+        if let Some(user_agent) = &self.user_agent {
+            headers.push(("User-Agent", user_agent.as_str()));
+        }
+        if let Some(referrer) = &self.referrer {
+            headers.push(("Referer", referrer.as_str()));
+        }
+        headers
+    }
+
+    /// Parses the stream information from an `#EXTINF` line and the URL line following it.
+    ///
+    /// This applies the same attribute parsing as the parser to a single record, e.g. an entry
+    /// received over a message queue, without parsing a whole playlist. The status is "GOOD" for
+    /// acestream links and local paths and "BAD" for URLs, which are not checked.
     ///
     /// # Arguments
     ///
-    /// * `timeout` - An optional `Duration` specifying the timeout for network requests.
-    ///               If not provided, a default timeout of 5 seconds is used.
-    pub fn new(timeout: Option<Duration>) -> M3uParser<'a> {
-        let useragent =  "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/111.0.0.0 Safari/537.36";
-        let timeout = timeout.unwrap_or_else(|| Duration::from_secs(5));
-        M3uParser {
-            streams_info: vec![],
-            streams_info_backup: vec![],
-            lines: vec![],
-            timeout,
-            enforce_schema: true,
-            check_live: false,
-            useragent,
-            file_regex: Lazy::new(|| {
-                Regex::new(r#"^[a-zA-Z]:\\((?:.*?\\)*).*\.[\d\w]{3,5}$|^(/[^/]*)+/?.[\d\w]{3,5}$"#)
-                    .unwrap()
-            }),
-            tvg_name_regex: Lazy::new(|| Regex::new(r#"tvg-name="(.*?)""#).unwrap()),
-            tvg_id_regex: Lazy::new(|| Regex::new(r#"tvg-id="(.*?)""#).unwrap()),
-            logo_regex: Lazy::new(|| Regex::new(r#"tvg-logo="(.*?)""#).unwrap()),
-            category_regex: Lazy::new(|| Regex::new(r#"group-title="(.*?)""#).unwrap()),
-            title_regex: Lazy::new(|| Regex::new(r#",([^",]+)$"#).unwrap()),
-            country_regex: Lazy::new(|| Regex::new(r#"tvg-country="(.*?)""#).unwrap()),
-            language_regex: Lazy::new(|| Regex::new(r#"tvg-language="(.*?)""#).unwrap()),
-            tvg_url_regex: Lazy::new(|| Regex::new(r#"tvg-url="(.*?)""#).unwrap()),
-            streams_regex: Lazy::new(|| Regex::new(r"acestream://[a-zA-Z0-9]+").unwrap()),
+    /// * `extinf_line` - The `#EXTINF` line, e.g. `#EXTINF:-1 tvg-id="bbcone.uk",BBC One`.
+    /// * `url_line` - The URL or path of the stream.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` with the parsed `Info`, which is an error if the first line is not an `#EXTINF`
+    /// line or the second one is not a URL or path.
+    ///
+    pub fn from_extinf(extinf_line: &str, url_line: &str) -> Result<Info, Box<dyn Error>> {
+        let (extinf_line, url_line) = (extinf_line.trim(), url_line.trim());
+        if !extinf_line.starts_with("#EXTINF") {
+            return Err(format!("Not an #EXTINF line: {}", extinf_line).into());
         }
+        let is_url = Url::parse(url_line).is_ok();
+        let is_local = FILE_REGEX.is_match(url_line) || RELATIVE_PATH_REGEX.is_match(url_line);
+        if !is_url && !is_local && !STREAMS_REGEX.is_match(url_line) {
+            return Err(format!("Not a URL or path: {}", url_line).into());
+        }
+
+        let is_checked = is_url && !STREAMS_REGEX.is_match(url_line);
+        let mut info = Info {
+            url: url_line.to_string(),
+            status: String::from(if is_checked { "BAD" } else { "GOOD" }),
+            raw: Some(format!("{}\n{}", extinf_line, url_line)),
+            ..Default::default()
+        };
+        info.parse_extinf(extinf_line);
+        info.raw_hash = hash_entry(&info.to_m3u_entry());
+        Ok(info)
     }
 
-    fn is_valid_url(&self, url: &str) -> bool {
-        match Url::parse(url) {
-            Ok(_) => true,
-            Err(_) => false,
+    /// Sets the title, attributes and duration from an `#EXTINF` line.
+    fn parse_extinf(&mut self, extinf_line: &str) {
+        // Title
+        self.title = TITLE_REGEX
+            .captures(extinf_line)
+            .map(|captures| captures[1].trim().to_string())
+            .unwrap_or_default();
+
+        // Logo
+        self.logo = get_attribute(extinf_line, "tvg-logo").unwrap_or_default();
+
+        // Category
+        if let Some(group_title) = get_attribute(extinf_line, "group-title") {
+            self.categories = group_title
+                .split(';')
+                .map(|category| category.trim().to_string())
+                .filter(|category| !category.is_empty())
+                .collect();
+            self.category = self.categories.first().cloned().unwrap_or_default();
         }
-    }
 
-    async fn read_url(&self, url: &str) -> Result<String, Box<dyn Error>> {
-        let client = Client::new();
-        let response = client.get(url).send().await?;
-        let content = response.text().await?;
-        Ok(content)
-    }
+        // Tags
+        if let Some(tags) = get_attribute(extinf_line, "x-tags") {
+            self.tags = tags
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+        }
 
-    fn save_file(&self, filename: &str, data: &[u8]) {
-        let mut file = File::create(filename).unwrap();
-        file.write(data).unwrap();
-        println!("Saved to file: {}", filename);
-    }
+        // TVG Information
+        let tvg_id = get_attribute(extinf_line, "tvg-id");
+        let tvg_name = get_attribute(extinf_line, "tvg-name");
+        let tvg_url = get_attribute(extinf_line, "tvg-url");
 
-    fn get_by_regex(&self, regex: &Regex, content: &str) -> Option<String> {
-        match regex.captures(content) {
-            Some(captures) => Some(captures[1].trim().to_string()),
-            None => None,
+        self.tvg = Tvg {
+            id: tvg_id.unwrap_or_default(),
+            name: tvg_name.unwrap_or_default(),
+            url: tvg_url.unwrap_or_default(),
+        };
+
+        // Country
+        if let Some(country) = get_attribute(extinf_line, "tvg-country") {
+            let name = country
+                .split(';')
+                .find_map(normalize_country_code)
+                .map(|code| country_name(&code))
+                .unwrap_or_default();
+            self.country = Country {
+                name,
+                code: country,
+            };
+        }
+        self.timezone = self.country.timezone();
+
+        // Language
+        if let Some(language) = get_attribute(extinf_line, "tvg-language") {
+            let language_lower = language.to_lowercase();
+            let country_code = language::get_language_code(&language_lower);
+            self.language = Language {
+                code: country_code.to_owned().to_string(),
+                name: language,
+            };
         }
+
+        // Duration
+        self.duration = DURATION_REGEX
+            .captures(extinf_line)
+            .and_then(|captures| captures[1].trim().parse::<f64>().ok())
+            .filter(|duration| *duration >= 0.0);
     }
 
-    /// Parses the specified M3U playlist file or URL.
-    ///
-    /// # Arguments
-    ///
-    /// * `path` - The path or URL of the M3U playlist.
-    /// * `check_live` - A boolean indicating whether to check the availability of streams.
-    ///                  If set to `true`, the parser will make a request to each stream URL to check its status.
-    /// * `enforce_schema` - A boolean indicating whether to enforce the M3U schema.
-    ///                      If set to `true`, only valid M3U entries will be parsed.
-    pub async fn parse_m3u(&mut self, path: &str, check_live: bool, enforce_schema: bool) {
-        let content: String;
-        self.check_live = check_live;
-        self.enforce_schema = enforce_schema;
+    /// Sets the fields given by the directive lines of the entry, such as `#EXTALB`.
+    fn parse_directives(&mut self, directives: &[&str]) {
+        let get_directive = |name: &str| {
+            directives
+                .iter()
+                .find_map(|directive| directive.strip_prefix(name))
+                .map(|value| value.trim().to_string())
+        };
+        // Audio metadata
+        self.album = get_directive("#EXTALB:");
+        self.artist = get_directive("#EXTART:");
+        self.image = get_directive("#EXTIMG:");
 
-        if self.is_valid_url(path) {
-            match self.read_url(path).await {
-                Ok(url_content) => content = url_content,
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                    return;
-                }
-            }
-        } else {
-            match read_to_string(path) {
-                Ok(file_content) => content = file_content,
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                    return;
-                }
+        // Group from the `#EXTGRP` directive, used by players not supporting `group-title`
+        if self.categories.is_empty() {
+            if let Some(group) = get_directive("#EXTGRP:").filter(|group| !group.is_empty()) {
+                self.categories = vec![group.clone()];
+                self.category = group;
             }
         }
-        let lines: Vec<String> = content
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| line.trim().to_string())
+
+        // Player options
+        self.user_agent = get_directive("#EXTVLCOPT:http-user-agent=");
+        self.referrer = get_directive("#EXTVLCOPT:http-referrer=");
+
+        // Notes of the curator
+        let notes: Vec<&str> = directives
+            .iter()
+            .filter_map(|directive| directive.strip_prefix("# note:"))
+            .map(str::trim)
             .collect();
+        self.comment = (!notes.is_empty()).then(|| notes.join("\n"));
+    }
 
-        self.lines = lines;
+    /// Returns the SHA-256 hash of the entry, as a lowercase hexadecimal string.
+    ///
+    /// The hash covers the entry as rendered in an M3U playlist, so it changes when the title,
+    /// attributes or URL change, but not when the status or the check details do.
+    pub fn content_hash(&self) -> String {
+        sha256_hex(&self.to_m3u_entry())
+    }
 
-        if !self.lines.is_empty() {
-            self.parse_lines().await;
-        } else {
-            eprintln!("No content to parse!!!");
+    /// Renders the entry as `#EXTINF` line followed by the URL line.
+    fn to_m3u_entry(&self) -> String {
+        write::render_entry(self, &write::DEFAULT_OPTIONS, 0)
+    }
+
+    /// Returns the `# note:` lines of the comment, each followed by `\n`.
+    fn note_lines(&self) -> String {
+        self.comment
+            .iter()
+            .flat_map(|comment| comment.lines())
+            .map(|note| format!("# note: {}\n", note))
+            .collect()
+    }
+
+    /// Returns the value of the `x-tags` attribute, joining the tags with `,`.
+    fn tags_attribute(&self) -> String {
+        let tags: Vec<&str> = self.tags.iter().map(String::as_str).collect();
+        tags.join(",")
+    }
+
+    /// Returns the value of the `group-title` attribute, joining multiple groups with `;`.
+    fn group_title(&self) -> String {
+        match self.categories.first() {
+            Some(first) if *first == self.category => self.categories.join(";"),
+            _ => self.category.clone(),
         }
     }
 
-    async fn parse_lines(&mut self) {
-        let num_lines = self.lines.len();
-        self.streams_info.clear();
-        let client = reqwest::Client::builder()
-            .timeout(self.timeout)
-            .build()
-            .unwrap();
-        let mut requests = Vec::new();
-        for line_num in 0..num_lines {
-            if self.lines[line_num].contains("#EXTINF") {
-                let request = self.parse_line(line_num, &client);
-                requests.push(request);
+    /// Renders the entry, re-using the original lines if the entry was not modified since parsing.
+    fn to_raw_m3u_entry(&self) -> String {
+        let entry = self.to_m3u_entry();
+        match &self.raw {
+            Some(raw) if hash_entry(&entry) == self.raw_hash => {
+                format!("{}{}", self.note_lines(), raw)
             }
+            _ => entry,
         }
-        let results = futures::future::join_all(requests).await;
-        for result in results {
-            if let Some(info) = result {
-                self.streams_info.push(info.clone());
-                self.streams_info_backup.push(info);
-            }
+    }
+}
+
+/// Returns the trimmed value of the attribute `name="value"` in the line, if present.
+///
+/// This is equivalent to matching `name="(.*?)"` but avoids running a regular expression for each
+/// attribute of each entry, which dominates the parse time of large playlists.
+fn get_attribute(line: &str, name: &str) -> Option<String> {
+    let value = line
+        .match_indices(name)
+        .find_map(|(start, _)| line[start + name.len()..].strip_prefix("=\""))?;
+    let end = value.find('"')?;
+    Some(value[..end].trim().to_string())
+}
+
+/// Returns the uppercased ISO 3166-1 alpha-2 code, with "UK" replaced by "GB", or `None` if the
+/// value is not two letters.
+fn normalize_country_code(code: &str) -> Option<String> {
+    let code = code.trim();
+    if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    match code.to_ascii_uppercase() {
+        code if code == "UK" => Some(String::from("GB")),
+        code => Some(code),
+    }
+}
+
+/// Returns the name of the country with the given ISO 3166-1 alpha-2 code, or an empty string if
+/// the code is unknown.
+#[cfg(feature = "geo")]
+fn country_name(code: &str) -> String {
+    celes::Country::from_alpha2(code)
+        .map(|country| country.long_name.to_string())
+        .unwrap_or_default()
+}
+
+/// Without the `geo` feature the names of the countries are not available.
+#[cfg(not(feature = "geo"))]
+fn country_name(_code: &str) -> String {
+    String::new()
+}
+
+/// Removes the UTF-8 byte order mark at the start of the content and converts its `\r\n` and lone
+/// `\r` line endings to `\n`, so that Windows and classic Mac OS files parse like Unix ones.
+fn normalize_line_endings(content: &str) -> String {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    if content.contains('\r') {
+        content.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        content.to_string()
+    }
+}
+
+/// Returns the file name of the location without its extension, query or fragment.
+fn title_from_location(location: &str) -> String {
+    let path = match Url::parse(location) {
+        Ok(url) if url.has_host() => url.path().to_string(),
+        _ => location
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    };
+    let name = path
+        .trim_end_matches(['/', '\\'])
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default();
+    match name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// Renders the stream information as an M3U playlist with the default options.
+fn render_m3u(streams_info: &[Info]) -> String {
+    write::render_m3u(streams_info, &write::DEFAULT_OPTIONS)
+}
+
+/// Returns the SHA-256 hash of the text as a lowercase hexadecimal string.
+fn sha256_hex(text: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn hash_entry(entry: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    entry.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Enum representing the attributes of the stream information that can be filtered or sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum InfoKey {
+    Title,
+    Logo,
+    Url,
+    Category,
+    TvgId,
+    TvgName,
+    TvgUrl,
+    CountryCode,
+    CountryName,
+    LanguageCode,
+    LanguageName,
+    Status,
+    Source,
+}
+
+impl InfoKey {
+    /// Resolves a key and an optional nested key (e.g. "tvg" and "id") into an `InfoKey`.
+    fn from_keys(key_0: &str, key_1: &str) -> Option<InfoKey> {
+        let key = match (key_0, key_1) {
+            ("title", "") => InfoKey::Title,
+            ("logo", "") => InfoKey::Logo,
+            ("url", "") => InfoKey::Url,
+            ("category", "") => InfoKey::Category,
+            ("status", "") => InfoKey::Status,
+            ("source", "") => InfoKey::Source,
+            ("tvg", "id") => InfoKey::TvgId,
+            ("tvg", "name") => InfoKey::TvgName,
+            ("tvg", "url") => InfoKey::TvgUrl,
+            ("country", "code") => InfoKey::CountryCode,
+            ("country", "name") => InfoKey::CountryName,
+            ("language", "code") => InfoKey::LanguageCode,
+            ("language", "name") => InfoKey::LanguageName,
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    /// Resolves a field name such as "title" or "tvg-id" into an `InfoKey`.
+    fn from_name(name: &str) -> Option<InfoKey> {
+        match name.split_once('-') {
+            Some((key_0, key_1)) => InfoKey::from_keys(key_0, key_1),
+            None => InfoKey::from_keys(name, ""),
         }
-        println!("Parsing completed !!!");
     }
 
-    async fn parse_line(&self, line_num: usize, client: &reqwest::Client) -> Option<Info> {
-        let line_info = &self.lines[line_num];
-        let mut stream_link = String::new();
-        let mut streams_link: Vec<String> = vec![];
-        let mut status = String::from("BAD");
+    /// Sets the value of this attribute. Setting the category replaces all the groups.
+    fn set_value(&self, stream_info: &mut Info, value: &str) {
+        let value = value.to_string();
+        match self {
+            InfoKey::Title => stream_info.title = value,
+            InfoKey::Logo => stream_info.logo = value,
+            InfoKey::Url => stream_info.url = value,
+            InfoKey::Category => {
+                stream_info.categories = vec![value.clone()];
+                stream_info.category = value;
+            }
+            InfoKey::TvgId => stream_info.tvg.id = value,
+            InfoKey::TvgName => stream_info.tvg.name = value,
+            InfoKey::TvgUrl => stream_info.tvg.url = value,
+            InfoKey::CountryCode => stream_info.country.code = value,
+            InfoKey::CountryName => stream_info.country.name = value,
+            InfoKey::LanguageCode => stream_info.language.code = value,
+            InfoKey::LanguageName => stream_info.language.name = value,
+            InfoKey::Status => stream_info.status = value,
+            InfoKey::Source => stream_info.source = Some(value),
+        }
+    }
 
-        for i in [1, 2].iter() {
-            let line = &self.lines[line_num + i];
-            let is_acestream = self.streams_regex.is_match(&line);
-            if !line.is_empty() && (is_acestream || self.is_valid_url(&line)) {
-                streams_link.push(line.to_string());
-                if is_acestream {
-                    status = String::from("GOOD");
-                }
-                break;
-            } else if !line.is_empty() && self.file_regex.is_match(&line) {
-                status = String::from("GOOD");
-                streams_link.push(line.to_string());
-                break;
+    /// Returns whether the value of this attribute matches the given regex. For `Category`, any of
+    /// the groups of the stream information may match.
+    fn is_match(&self, stream_info: &Info, regex: &Regex) -> bool {
+        match self {
+            InfoKey::Category => {
+                regex.is_match(&stream_info.category)
+                    || stream_info
+                        .categories
+                        .iter()
+                        .any(|category| regex.is_match(category))
             }
+            _ => regex.is_match(self.value(stream_info)),
         }
+    }
 
-        if !streams_link.is_empty() {
-            stream_link = streams_link[0].to_string();
+    /// Returns the name of this attribute as used by `filter_by`, e.g. "tvg-id".
+    fn name(&self) -> &'static str {
+        match self {
+            InfoKey::Title => "title",
+            InfoKey::Logo => "logo",
+            InfoKey::Url => "url",
+            InfoKey::Category => "category",
+            InfoKey::TvgId => "tvg-id",
+            InfoKey::TvgName => "tvg-name",
+            InfoKey::TvgUrl => "tvg-url",
+            InfoKey::CountryCode => "country-code",
+            InfoKey::CountryName => "country-name",
+            InfoKey::LanguageCode => "language-code",
+            InfoKey::LanguageName => "language-name",
+            InfoKey::Status => "status",
+            InfoKey::Source => "source",
         }
+    }
 
-        if !line_info.is_empty() && !stream_link.is_empty() {
-            let mut info = Info {
-                title: String::new(),
-                logo: String::new(),
-                url: String::new(),
-                category: String::new(),
-                tvg: Tvg {
-                    id: String::new(),
-                    name: String::new(),
-                    url: String::new(),
-                },
-                country: Country {
-                    code: String::new(),
-                    name: String::new(),
-                },
-                language: Language {
-                    code: String::new(),
-                    name: String::new(),
-                },
-                status,
-            };
+    /// Returns the value of this attribute for the given stream information.
+    fn value<'a>(&self, stream_info: &'a Info) -> &'a str {
+        match self {
+            InfoKey::Title => &stream_info.title,
+            InfoKey::Logo => &stream_info.logo,
+            InfoKey::Url => &stream_info.url,
+            InfoKey::Category => &stream_info.category,
+            InfoKey::TvgId => &stream_info.tvg.id,
+            InfoKey::TvgName => &stream_info.tvg.name,
+            InfoKey::TvgUrl => &stream_info.tvg.url,
+            InfoKey::CountryCode => &stream_info.country.code,
+            InfoKey::CountryName => &stream_info.country.name,
+            InfoKey::LanguageCode => &stream_info.language.code,
+            InfoKey::LanguageName => &stream_info.language.name,
+            InfoKey::Status => &stream_info.status,
+            InfoKey::Source => stream_info.source.as_deref().unwrap_or_default(),
+        }
+    }
+}
 
-            // Title
-            info.title = self
-                .get_by_regex(&self.title_regex, &line_info)
-                .unwrap_or_default();
+/// Enum representing the order in which the stream information is sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
 
-            // Logo
-            info.logo = self
-                .get_by_regex(&self.logo_regex, &line_info)
-                .unwrap_or_default();
+/// Enum representing what makes two streams duplicates of each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupeKey {
+    /// Streams with the same URL.
+    Url,
+    /// Streams with the same `tvg-id`. Streams without `tvg-id` are never duplicates.
+    TvgId,
+    /// Streams with the same title, ignoring case, accents and surrounding whitespace.
+    Title,
+}
+
+impl DedupeKey {
+    fn value(&self, info: &Info) -> String {
+        match self {
+            DedupeKey::Url => info.url.clone(),
+            DedupeKey::TvgId => info.tvg.id.clone(),
+            DedupeKey::Title => search::normalize(info.title.trim()),
+        }
+    }
+}
 
-            // Url
-            info.url = stream_link;
+/// Enum representing what makes two entries the same channel when merging them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeKey {
+    /// Entries with the same `tvg-id`. Entries without `tvg-id` are never merged.
+    TvgId,
+    /// Entries with the same title, ignoring case, accents and surrounding whitespace.
+    Title,
+}
 
-            // Category
-            info.category = self
-                .get_by_regex(&self.category_regex, &line_info)
-                .unwrap_or_default();
+impl MergeKey {
+    fn value(&self, info: &Info) -> String {
+        match self {
+            MergeKey::TvgId => DedupeKey::TvgId.value(info),
+            MergeKey::Title => DedupeKey::Title.value(info),
+        }
+    }
+}
 
-            // TVG Information
-            let tvg_id = self.get_by_regex(&self.tvg_id_regex, &line_info);
-            let tvg_name = self.get_by_regex(&self.tvg_name_regex, &line_info);
-            let tvg_url = self.get_by_regex(&self.tvg_url_regex, &line_info);
+/// Enum representing the reasons why a line of the playlist did not produce stream information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ParseWarningReason {
+    /// An `#EXTINF` line is not followed by a valid URL or path.
+    MissingUrl,
+    /// A URL or path is not preceded by an `#EXTINF` line.
+    UrlWithoutExtinf,
+    /// A line is neither a directive, a comment, a URL nor a path.
+    UnrecognizedLine,
+    /// The playlist does not start with an `#EXTM3U` header. Only reported in strict mode.
+    MissingHeader,
+}
 
-            info.tvg = Tvg {
-                id: tvg_id.unwrap_or_default(),
-                name: tvg_name.unwrap_or_default(),
-                url: tvg_url.unwrap_or_default(),
-            };
+impl ParseWarningReason {
+    fn description(&self) -> &'static str {
+        match self {
+            ParseWarningReason::MissingUrl => "#EXTINF line without URL",
+            ParseWarningReason::UrlWithoutExtinf => "URL without #EXTINF line",
+            ParseWarningReason::UnrecognizedLine => "unrecognized line",
+            ParseWarningReason::MissingHeader => "missing #EXTM3U header",
+        }
+    }
+}
 
-            // Country
-            if let Some(country) = self.get_by_regex(&self.country_regex, &line_info) {
-                let mut country_name = "";
-                if let Ok(country_obj) = celes::Country::from_alpha2(&country) {
-                    country_name = country_obj.long_name;
-                }
-                info.country = Country {
-                    code: country,
-                    name: country_name.to_string(),
-                };
-            }
+/// Struct representing a line of the playlist that was skipped while parsing.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParseWarning {
+    /// The line number in the source playlist, starting at 1.
+    pub line_number: usize,
+    pub reason: ParseWarningReason,
+    pub raw_line: String,
+}
 
-            // Language
-            if let Some(language) = self.get_by_regex(&self.language_regex, &line_info) {
-                let language_lower = language.to_lowercase();
-                let country_code = language::get_language_code(&language_lower);
-                info.language = Language {
-                    code: country_code.to_owned().to_string(),
-                    name: language,
-                };
-            }
+/// Enum representing a registered extractor of vendor-specific attributes.
+enum AttributeExtractor {
+    /// Captures the value of an attribute of the `#EXTINF` line.
+    Attribute { name: String, field: String },
+    /// Captures the first group, or the whole match, of a regular expression in the lines of the
+    /// entry.
+    Regex { regex: Regex, field: String },
+}
 
-            if self.check_live && info.status.eq("BAD") {
-                match client
-                    .get(&info.url)
-                    .header("User-Agent", self.useragent)
-                    .send()
-                    .await
-                {
-                    Ok(response) => {
-                        if response.status().is_success() {
-                            info.status = "GOOD".to_string();
-                        }
-                    }
-                    Err(_) => {}
-                }
+impl AttributeExtractor {
+    fn extract(&self, extinf_line: &str, raw: &str) -> Option<(String, String)> {
+        match self {
+            AttributeExtractor::Attribute { name, field } => {
+                get_attribute(extinf_line, name).map(|value| (field.clone(), value))
+            }
+            AttributeExtractor::Regex { regex, field } => {
+                let captures = regex.captures(raw)?;
+                let value = captures.get(1).or_else(|| captures.get(0))?;
+                Some((field.clone(), value.as_str().trim().to_string()))
             }
-            return Some(info);
         }
-        return None;
     }
+}
 
-    fn get_m3u_content(&self) -> String {
-        if self.streams_info.is_empty() {
-            return String::new();
+/// Struct representing the error of a playlist rejected in strict mode.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrictParseError {
+    /// The problems that made the playlist invalid, ordered by line number.
+    pub problems: Vec<ParseWarning>,
+}
+
+impl std::fmt::Display for StrictParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid playlist")?;
+        for (position, problem) in self.problems.iter().enumerate() {
+            let separator = if position == 0 { ": " } else { "; " };
+            write!(
+                f,
+                "{}line {}: {}",
+                separator,
+                problem.line_number,
+                problem.reason.description()
+            )?;
         }
+        Ok(())
+    }
+}
 
-        let content: Vec<String> = self
-            .streams_info
-            .iter()
-            .map(|stream_info| {
-                let mut line = String::from("#EXTINF:-1");
+impl Error for StrictParseError {}
 
-                macro_rules! append_attribute {
-                    ($attr:expr, $value:expr) => {
-                        if !$value.is_empty() {
-                            line.push_str(&format!(" {}=\"{}\"", $attr, $value));
-                        }
-                    };
-                }
+static FILE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^[a-zA-Z]:\\((?:.*?\\)*).*\.[\d\w]{3,5}$|^(/[^/]*)+/?.[\d\w]{3,5}$"#).unwrap()
+});
+static TITLE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#",([^",]+)$"#).unwrap());
+static STREAMS_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"acestream://[a-zA-Z0-9]+").unwrap());
+static DURATION_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^#EXTINF:\s*(-?\d+(?:\.\d+)?)").unwrap());
+static RELATIVE_PATH_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[^#/\\][^:]*\.[\d\w]{2,5}$").unwrap());
 
-                append_attribute!("tvg-id", stream_info.tvg.id);
-                append_attribute!("tvg-name", stream_info.tvg.name);
-                append_attribute!("tvg-url", stream_info.tvg.url);
-                append_attribute!("tvg-logo", stream_info.logo);
-                append_attribute!("tvg-country", stream_info.country.code);
-                append_attribute!("tvg-language", stream_info.language.name);
-                append_attribute!("group-title", stream_info.category);
+/// The tag of the streams marked with `M3uParser::mark_favorite`.
+const FAVORITE_TAG: &str = "favorite";
 
-                if !stream_info.title.is_empty() {
-                    line.push_str(&format!(",{}", stream_info.title));
-                }
+type UrlRefresher = Arc<dyn Fn(Info) -> BoxFuture<'static, Option<String>> + Send + Sync>;
+type EntryHook = Box<dyn Fn(&Info) + Send + Sync>;
+type CompleteHook = Box<dyn Fn(&PlaylistStats) + Send + Sync>;
 
-                format!("{}\n{}", line, stream_info.url)
-            })
-            .collect();
-        ["#EXTM3U".to_string(), content.join("\n")].join("\n")
-    }
+/// M3U Parser struct for parsing and manipulating M3U files.
+///
+/// The parser is `Send` and `Sync`, so it can be moved to or shared between threads. Use
+/// `snapshot` to share the parsed stream information with request handlers without the parser.
+pub struct M3uParser {
+    /// The parsed stream information. Prefer `streams_mut` and `set_streams` to change it, which
+    /// keep `reset_operations` and the lookups working.
+    pub streams_info: Vec<Info>,
+    /// Copy of the parsed stream information, taken before the first operation that changes it.
+    streams_info_backup: Option<Vec<Info>>,
+    /// Copies of the stream information saved with `save_snapshot`, by name in the order saved.
+    snapshots: Vec<(String, Vec<Info>)>,
+    /// Positions of the stream information by `tvg-id` and URL, built on the first lookup.
+    index: OnceLock<PositionIndex>,
+    content: String,
+    /// Byte ranges of the trimmed lines of `content`.
+    lines: Vec<(usize, usize)>,
+    warnings: Vec<ParseWarning>,
+    strict: bool,
+    parse_error: Option<StrictParseError>,
+    playlist_kind: PlaylistKind,
+    #[cfg(feature = "net")]
+    fetch_timeout: Duration,
+    #[cfg(feature = "net")]
+    check_timeout: Duration,
+    enforce_schema: bool,
+    check_live: bool,
+    #[cfg(feature = "net")]
+    check_local_files: bool,
+    canonicalize_paths: bool,
+    base_location: Option<String>,
+    #[cfg(feature = "net")]
+    require_non_empty_files: bool,
+    natural_sort: bool,
+    #[cfg(feature = "collation")]
+    sort_locale: Option<String>,
+    #[cfg(feature = "collation")]
+    collator: Option<icu_collator::Collator>,
+    #[cfg(feature = "net")]
+    useragent: String,
+    #[cfg(feature = "net")]
+    stream_checker: Option<Arc<dyn StreamChecker>>,
+    #[cfg(feature = "net")]
+    acestream_engine: Option<String>,
+    #[cfg(feature = "net")]
+    liveness_cache: Option<(String, Duration)>,
+    #[cfg(feature = "net")]
+    redirect_policy: RedirectPolicy,
+    #[cfg(feature = "net")]
+    record_resolved_url: bool,
+    /// Values of fields set by the user, by URL or `tvg-id` and then by field name.
+    overrides: BTreeMap<String, BTreeMap<String, String>>,
+    #[cfg(feature = "net")]
+    accept_invalid_certs: bool,
+    #[cfg(feature = "net")]
+    root_certificates: Vec<reqwest::Certificate>,
+    #[cfg(feature = "net")]
+    cookie_jar: Option<Arc<Jar>>,
+    #[cfg(feature = "net")]
+    proxy: Option<reqwest::Proxy>,
+    http_fetch: Option<Arc<dyn HttpFetch>>,
+    url_refresher: Option<UrlRefresher>,
+    entry_hooks: Vec<EntryHook>,
+    complete_hooks: Vec<CompleteHook>,
+    /// Whether `parse_sources` is parsing one of several sources, whose completion is reported
+    /// once for all of them.
+    merging_sources: bool,
+    extractors: Vec<AttributeExtractor>,
+    /// Keywords added with `add_category_keywords`, normalized, by category in the order added.
+    category_keywords: Vec<(String, Vec<String>)>,
+    backup_retention: Option<usize>,
+    min_entries: usize,
+    force_overwrite: bool,
+    parsed_at: Option<u64>,
+    m3u_write_options: M3uWriteOptions,
+    html_grouped: bool,
+}
 
-    /// Resets the operations of the M3uParser by restoring the backup of stream information.
+impl M3uParser {
+    /// Creates a new instance of M3uParser.
     ///
-    /// This function restores the original state of the M3uParser by replacing the current
-    /// stream information with the backup. This can be useful when you want to undo any
-    /// modifications or filtering operations applied to the stream information.
+    /// # Arguments
     ///
-    pub fn reset_operations(&mut self) {
-        self.streams_info = self.streams_info_backup.clone();
-    }
-
-    fn get_key_value(&'a self, stream_info: &'a Info, key_0: &str, key_1: &str) -> &str {
-        let value = match key_0 {
-            "title" => &stream_info.title,
-            "logo" => &stream_info.logo,
-            "url" => &stream_info.url,
-            "category" => &stream_info.category,
-            "status" => &stream_info.status,
-            "tvg" => match key_1 {
-                "id" => &stream_info.tvg.id,
-                "name" => &stream_info.tvg.name,
-                "url" => &stream_info.tvg.url,
-                _ => "",
-            },
-            "country" => match key_1 {
-                "code" => &stream_info.country.code,
-                "name" => &stream_info.country.name,
-                _ => "",
-            },
-            "language" => match key_1 {
-                "code" => &stream_info.country.code,
-                "name" => &stream_info.country.name,
-                _ => "",
-            },
-            _ => "",
-        };
-        value
+    /// * `timeout` - An optional `Duration` specifying the timeout of each stream check.
+    ///   If not provided, a default timeout of 5 seconds is used. Downloading the playlist has a
+    ///   separate timeout of 60 seconds, see `set_fetch_timeout`. Streams are only checked with
+    ///   the `net` feature.
+    #[cfg_attr(not(feature = "net"), allow(unused_variables))]
+    pub fn new(timeout: Option<Duration>) -> M3uParser {
+        #[cfg(feature = "net")]
+        let useragent =  "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/111.0.0.0 Safari/537.36";
+        M3uParser {
+            streams_info: vec![],
+            streams_info_backup: None,
+            snapshots: vec![],
+            index: OnceLock::new(),
+            content: String::new(),
+            lines: vec![],
+            warnings: vec![],
+            playlist_kind: PlaylistKind::default(),
+            #[cfg(feature = "net")]
+            fetch_timeout: Duration::from_secs(60),
+            #[cfg(feature = "net")]
+            check_timeout: timeout.unwrap_or_else(|| Duration::from_secs(5)),
+            enforce_schema: true,
+            check_live: false,
+            #[cfg(feature = "net")]
+            check_local_files: false,
+            canonicalize_paths: false,
+            base_location: None,
+            strict: false,
+            parse_error: None,
+            #[cfg(feature = "net")]
+            require_non_empty_files: false,
+            natural_sort: false,
+            #[cfg(feature = "collation")]
+            sort_locale: None,
+            #[cfg(feature = "collation")]
+            collator: None,
+            #[cfg(feature = "net")]
+            useragent: useragent.to_string(),
+            #[cfg(feature = "net")]
+            stream_checker: None,
+            #[cfg(feature = "net")]
+            acestream_engine: None,
+            #[cfg(feature = "net")]
+            liveness_cache: None,
+            #[cfg(feature = "net")]
+            redirect_policy: RedirectPolicy::default(),
+            #[cfg(feature = "net")]
+            record_resolved_url: false,
+            overrides: BTreeMap::new(),
+            #[cfg(feature = "net")]
+            accept_invalid_certs: false,
+            #[cfg(feature = "net")]
+            root_certificates: vec![],
+            #[cfg(feature = "net")]
+            cookie_jar: None,
+            #[cfg(feature = "net")]
+            proxy: None,
+            http_fetch: None,
+            url_refresher: None,
+            entry_hooks: vec![],
+            complete_hooks: vec![],
+            merging_sources: false,
+            extractors: vec![],
+            category_keywords: vec![],
+            backup_retention: None,
+            min_entries: 0,
+            force_overwrite: false,
+            parsed_at: None,
+            m3u_write_options: M3uWriteOptions::default(),
+            html_grouped: false,
+        }
     }
 
-    /// Filters the stream information based on the specified key and filters.
+    /// Returns the JSON Schema of the stream information exported by `get_json` and `to_file`.
     ///
-    /// This function applies filtering operations to the stream information based on the provided key
-    /// and filters. The key represents the attribute of the stream information that will be filtered,
-    /// and the filters specify the conditions that the attribute should match. The function allows
-    /// filtering based on nested keys and provides options to retrieve or exclude the matching
-    /// stream information.
+    /// The schema describes an array of stream information objects as serialized with the default
+    /// JSON options.
+    ///
+    /// # Returns
+    ///
+    /// A `schemars::Schema` which can be serialized with `serde_json`.
+    ///
+    #[cfg(feature = "schemars")]
+    pub fn json_schema() -> schemars::Schema {
+        schemars::schema_for!(Vec<Info>)
+    }
+
+    /// Sets the timeout of downloading the playlist when parsing a URL, 60 seconds by default.
     ///
     /// # Arguments
     ///
-    /// * `key` - The attribute key to filter by. Valid values are: "title", "logo", "url", "category",
-    ///   "tvg", "country", "language", and "status".
-    /// * `filters` - A vector of filter strings. The stream information will be filtered based on
-    ///   these conditions.
-    /// * `key_splitter` - The delimiter used to split the key for nested filtering. Set it to an empty
-    ///   string (`""`) if nested filtering is not required.
-    /// * `retrieve` - A boolean value indicating whether to retrieve the matching stream information
-    ///   (`true`) or exclude it from the result (`false`).
-    /// * `nested_key` - A boolean value indicating whether the key represents a nested key. If `true`,
-    ///   the key will be split using the `key_splitter`, and filtering will be applied to the nested
-    ///   key. If `false`, the key will be treated as a single key for filtering.
+    /// * `timeout` - The maximum time to download the whole playlist.
     ///
-    /// # Panics
+    #[cfg(feature = "net")]
+    pub fn set_fetch_timeout(&mut self, timeout: Duration) {
+        self.fetch_timeout = timeout;
+    }
+
+    /// Sets the timeout of each stream check, as given to `new`.
     ///
-    /// The function will panic in the following scenarios:
+    /// # Arguments
     ///
-    /// * If the nested key is provided but not in the format `<key><key_splitter><nested_key>`.
-    /// * If the provided key is not one of the valid keys ("title", "logo", "url", "category",
-    ///   "tvg", "country", "language", "status").
+    /// * `timeout` - The maximum time to check a single stream.
     ///
-    pub fn filter_by(
-        &mut self,
-        key: &str,
-        filters: Vec<&str>,
-        key_splitter: &str,
-        retrieve: bool,
-        nested_key: bool,
-    ) {
-        let (key_0, key_1) = if nested_key {
-            match key.split(key_splitter).collect::<Vec<&str>>()[..] {
-                [key0, key1] => (key0, key1),
-                _ => {
-                    eprintln!("Nested key must be in the format <key><key_splitter><nested_key>");
-                    return;
-                }
-            }
-        } else {
-            (key, "")
-        };
-
-        let valid_keys_0: HashSet<&str> = [
-            "title", "logo", "url", "category", "tvg", "country", "language", "status",
-        ]
-        .iter()
-        .copied()
-        .collect();
-
-        let valid_keys_1: HashSet<&str> =
-            ["", "id", "name", "url", "code"].iter().copied().collect();
-
-        if !valid_keys_0.contains(&key_0) {
-            eprintln!("{} key is not present.", key);
-            return;
-        }
-
-        if !valid_keys_1.contains(&key_1) {
-            eprintln!("{} key is not present.", key);
-            return;
-        }
-
-        if filters.is_empty() {
-            eprintln!("Filter word/s missing!!!");
-            return;
-        }
-
-        let re_filters: Vec<Regex> = filters
-            .iter()
-            .map(|filter| Regex::new(filter).unwrap())
-            .collect();
+    #[cfg(feature = "net")]
+    pub fn set_check_timeout(&mut self, timeout: Duration) {
+        self.check_timeout = timeout;
+    }
 
-        self.streams_info = if retrieve {
-            let streams_info: Vec<Info> = self
-                .streams_info
-                .iter()
-                .filter(|stream_info| {
-                    re_filters.iter().any(|filter| {
-                        filter.is_match(self.get_key_value(stream_info, key_0, key_1))
-                    })
-                })
-                .cloned()
-                .collect();
-            streams_info
-        } else {
-            let streams_info: Vec<Info> = self
-                .streams_info
-                .iter()
-                .filter(|stream_info| {
-                    re_filters.iter().all(|filter| {
-                        !filter.is_match(self.get_key_value(stream_info, key_0, key_1))
-                    })
-                })
-                .cloned()
-                .collect();
-            streams_info
-        }
+    /// Sets the `User-Agent` header sent when checking streams that have no `User-Agent` of
+    /// their own, a desktop browser by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_agent` - The value of the `User-Agent` header.
+    ///
+    #[cfg(feature = "net")]
+    pub fn set_user_agent(&mut self, user_agent: &str) {
+        self.useragent = user_agent.to_string();
     }
 
-    /// Sorts the stream information based on the specified key and sorting options.
+    /// Sets how HTTP redirects are handled when downloading playlists and checking streams.
     ///
-    /// This function sorts the stream information based on the provided key and sorting options. The key
-    /// represents the attribute of the stream information that will be used for sorting. The function
-    /// allows sorting based on nested keys and provides options to specify the sorting order.
+    /// By default, up to 10 redirects are followed.
     ///
     /// # Arguments
     ///
-    /// * `key` - The attribute key to sort by. Valid values are: "title", "logo", "url", "category",
-    ///   "tvg", "country", "language", and "status".
-    /// * `key_splitter` - The delimiter used to split the key for nested sorting. Set it to an empty
-    ///   string (`""`) if nested sorting is not required.
-    /// * `asc` - A boolean value indicating the sorting order. If `true`, the stream information will be
-    ///   sorted in ascending order based on the specified key. If `false`, the stream information will
-    ///   be sorted in descending order.
-    /// * `nested_key` - A boolean value indicating whether the key represents a nested key. If `true`,
-    ///   the key will be split using the `key_splitter`, and sorting will be applied to the nested key.
-    ///   If `false`, the key will be treated as a single key for sorting.
+    /// * `policy` - The redirect policy to use.
     ///
-    /// # Panics
+    #[cfg(feature = "net")]
+    pub fn set_redirect_policy(&mut self, policy: RedirectPolicy) {
+        self.redirect_policy = policy;
+    }
+
+    /// Enables or disables accepting invalid TLS certificates, e.g. self-signed or expired ones.
     ///
-    /// The function will panic in the following scenarios:
+    /// This applies to downloading playlists and checking streams. It makes HTTPS connections
+    /// vulnerable to interception, so prefer `add_root_certificate` when the certificate authority
+    /// of the provider is known.
     ///
-    /// * If the nested key is provided but not in the format `<key><key_splitter><nested_key>`.
-    /// * If the provided key is not one of the valid keys ("title", "logo", "url", "category",
-    ///   "tvg", "country", "language", "status").
+    /// # Arguments
     ///
-    pub fn sort_by(&mut self, key: &str, key_splitter: &str, asc: bool, nested_key: bool) {
-        let (key_0, key_1) = if nested_key {
-            match key.split(key_splitter).collect::<Vec<&str>>()[..] {
-                [key0, key1] => (key0, key1),
-                _ => {
-                    eprintln!("Nested key must be in the format <key><key_splitter><nested_key>");
-                    return;
-                }
-            }
-        } else {
-            (key, "")
-        };
+    /// * `accept` - A boolean indicating whether to accept invalid certificates.
+    ///
+    #[cfg(feature = "net")]
+    pub fn set_danger_accept_invalid_certs(&mut self, accept: bool) {
+        self.accept_invalid_certs = accept;
+    }
 
-        let valid_keys_0: HashSet<&str> = [
-            "title", "logo", "url", "category", "tvg", "country", "language", "status",
-        ]
-        .iter()
-        .copied()
-        .collect();
+    /// Trusts an additional root certificate when downloading playlists and checking streams.
+    ///
+    /// # Arguments
+    ///
+    /// * `pem` - The certificate in PEM format, e.g. the content of the CA file of a home-lab.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the certificate cannot be parsed.
+    ///
+    #[cfg(feature = "net")]
+    pub fn add_root_certificate(&mut self, pem: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.root_certificates
+            .push(reqwest::Certificate::from_pem(pem)?);
+        Ok(())
+    }
 
-        let valid_keys_1: HashSet<&str> =
-            ["", "id", "name", "url", "code"].iter().copied().collect();
+    /// Sends the requests of downloading playlists and checking streams through a proxy.
+    ///
+    /// Without a proxy, the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables of
+    /// the system are used.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the HTTP or HTTPS proxy, e.g. "http://proxy.lan:3128". SOCKS proxies
+    ///   are not supported.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the URL is not a valid proxy URL.
+    ///
+    #[cfg(feature = "net")]
+    pub fn set_proxy(&mut self, url: &str) -> Result<(), Box<dyn Error>> {
+        self.proxy = Some(reqwest::Proxy::all(url)?);
+        Ok(())
+    }
 
-        if !valid_keys_0.contains(&key_0) {
-            eprintln!("{} key is not present.", key);
-            return;
+    /// Enables or disables the cookie store, disabled by default.
+    ///
+    /// When enabled, the cookies set by the server while downloading the playlist are sent with
+    /// the following requests, including the stream checks, e.g. for providers requiring the
+    /// session cookie of the playlist download on their stream URLs. Disabling it clears the cookies.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - A boolean indicating whether to store and send cookies.
+    ///
+    #[cfg(feature = "net")]
+    pub fn set_cookie_store(&mut self, enabled: bool) {
+        if !enabled {
+            self.cookie_jar = None;
+        } else if self.cookie_jar.is_none() {
+            self.cookie_jar = Some(Arc::new(Jar::default()));
         }
+    }
 
-        if !valid_keys_1.contains(&key_1) {
-            eprintln!("{} key is not present.", key);
-            return;
+    /// Adds a cookie to the cookie store, enabling it if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `cookie` - The cookie in the format of a `Set-Cookie` header, e.g. "session=abc; Path=/".
+    /// * `url` - The URL the cookie is set for; it is sent to the matching domain and path.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the URL is invalid.
+    ///
+    #[cfg(feature = "net")]
+    pub fn add_cookie(&mut self, cookie: &str, url: &str) -> Result<(), url::ParseError> {
+        let url = reqwest::Url::parse(url)?;
+        self.set_cookie_store(true);
+        if let Some(jar) = &self.cookie_jar {
+            jar.add_cookie_str(cookie, &url);
         }
+        Ok(())
+    }
 
-        let mut cloned_streams_info = self.streams_info.clone();
-
-        cloned_streams_info.sort_by(|a, b| {
-            let a_value = self.get_key_value(a, key_0, key_1);
-            let b_value = self.get_key_value(b, key_0, key_1);
-
-            if asc {
-                a_value.cmp(b_value)
-            } else {
-                b_value.cmp(a_value)
-            }
-        });
+    /// Enables or disables recording the final URL of each stream after following redirects.
+    ///
+    /// Many playlist URLs are redirectors. With this option enabled, parsing with `check_live`
+    /// stores the URL of the final response in `Info.resolved_url`, which players behind strict
+    /// firewalls can use to reach the real endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - A boolean indicating whether to record the final URLs.
+    ///
+    #[cfg(feature = "net")]
+    pub fn set_record_resolved_url(&mut self, record: bool) {
+        self.record_resolved_url = record;
+    }
 
-        self.streams_info = cloned_streams_info;
+    /// Sets the checker used to verify the availability of streams when parsing with `check_live`.
+    ///
+    /// By default, an `HttpChecker` sending a GET request to each stream is used. Installing a
+    /// custom checker allows e.g. testing code that depends on `check_live` without network access.
+    ///
+    /// # Arguments
+    ///
+    /// * `checker` - The checker to use for the following parses.
+    ///
+    #[cfg(feature = "net")]
+    pub fn set_stream_checker<C: StreamChecker + 'static>(&mut self, checker: C) {
+        self.stream_checker = Some(Arc::new(checker));
     }
 
-    /// Removes stream information based on the specified file extensions.
+    /// Sets the local Ace Stream engine used to check acestream links when parsing with
+    /// `check_live`.
     ///
-    /// This function removes stream information based on the file extensions specified in the `extensions`
-    /// parameter. It internally calls the `filter_by` function with the "url" attribute as the key and
-    /// filters the stream information that matches any of the provided extensions.
+    /// Acestream links cannot be checked over HTTP, so without an engine they are always "GOOD".
+    /// With an engine, the parser asks it for its version through
+    /// `/webui/api/service?method=get_version` and then whether it can start each link through
+    /// `/ace/getstream`; links are "BAD" if the engine does not answer or cannot start them.
     ///
     /// # Arguments
     ///
-    /// * `extensions` - A vector of file extensions to be removed. Each extension should be a string.
+    /// * `url` - The URL of the HTTP API of the engine, e.g. "http://127.0.0.1:6878", or `None`
+    ///   to stop checking acestream links.
     ///
-    pub fn remove_by_extension(&mut self, extensions: Vec<&str>) {
-        self.filter_by("url", extensions, "-", false, false)
+    #[cfg(feature = "net")]
+    pub fn set_acestream_engine(&mut self, url: Option<&str>) {
+        self.acestream_engine = url.map(String::from);
     }
 
-    /// Retrieves stream information based on the specified file extensions.
+    /// Enables an on-disk cache of the liveness check results.
     ///
-    /// This function retrieves stream information based on the file extensions specified in the `extensions`
-    /// parameter. It internally calls the `filter_by` function with the "url" attribute as the key and
-    /// filters the stream information that matches any of the provided extensions.
+    /// When parsing with `check_live`, URLs checked within `ttl` are not requested again and reuse
+    /// the cached result, which avoids re-checking every stream of large playlists on each run. The
+    /// cache is a JSON file keyed by URL, created if missing and updated after each check.
     ///
     /// # Arguments
     ///
-    /// * `extensions` - A vector of file extensions to be retrieved. Each extension should be a string.
+    /// * `path` - The path of the cache file, or `None` to disable the cache.
+    /// * `ttl` - How long a check result stays valid.
     ///
-    pub fn retrieve_by_extension(&mut self, extensions: Vec<&str>) {
-        self.filter_by("url", extensions, "-", true, false)
+    #[cfg(feature = "net")]
+    pub fn set_liveness_cache(&mut self, path: Option<&str>, ttl: Duration) {
+        self.liveness_cache = path.map(|path| (path.to_string(), ttl));
     }
 
-    /// Removes stream information based on the specified categories.
+    /// Enables or disables the verification of local files when checking the streams.
     ///
-    /// This function removes stream information based on the categories specified in the `extensions`
-    /// parameter. It internally calls the `filter_by` function with the "category" attribute as the key
-    /// and filters out the stream information that matches any of the provided categories.
+    /// By default, entries pointing to local files are always marked as "GOOD". With this option
+    /// enabled and `check_live` set when parsing, each local file is checked to exist and to be
+    /// readable, and is marked as "BAD" otherwise.
     ///
     /// # Arguments
     ///
-    /// * `categories` - A vector of categories to be removed. Each category should be a string.
+    /// * `check` - A boolean indicating whether to verify local files.
+    /// * `require_non_empty` - A boolean indicating whether empty files should be marked as "BAD".
     ///
-    pub fn remove_by_category(&mut self, extensions: Vec<&str>) {
-        self.filter_by("category", extensions, "-", false, false)
+    #[cfg(feature = "net")]
+    pub fn set_check_local_files(&mut self, check: bool, require_non_empty: bool) {
+        self.check_local_files = check;
+        self.require_non_empty_files = require_non_empty;
     }
 
-    /// Retrieves stream information based on the specified categories.
+    /// Enables or disables writing resolved paths instead of relative ones.
     ///
-    /// This function retrieves stream information based on the categories specified in the `extensions`
-    /// parameter. It internally calls the `filter_by` function with the "category" attribute as the key
-    /// and filters the stream information that matches any of the provided categories.
+    /// Relative paths such as `../music/song.mp3` are always resolved against the location of the
+    /// playlist to check them, but are kept as written in the stream information by default. With
+    /// this option enabled, the resolved absolute path or URL is stored instead. It must be set
+    /// before parsing.
     ///
     /// # Arguments
     ///
-    /// * `categories` - A vector of categories to be retrieved. Each category should be a string.
+    /// * `canonicalize` - A boolean indicating whether to store resolved paths.
     ///
-    pub fn retrieve_by_category(&mut self, extensions: Vec<&str>) {
-        self.filter_by("category", extensions, "-", true, false)
+    pub fn set_canonicalize_paths(&mut self, canonicalize: bool) {
+        self.canonicalize_paths = canonicalize;
     }
 
-    /// Retrieves the stream information in JSON format.
+    /// Enables strict mode, for pipelines that must reject malformed playlists rather than keep
+    /// the entries that could be parsed.
     ///
-    /// This function returns the stream information in JSON format. The JSON can be either
-    /// pretty-formatted or compact depending on the `preety` parameter.
+    /// In strict mode, a playlist without an `#EXTM3U` header, with an `#EXTINF` line without a
+    /// URL or with a URL without an `#EXTINF` line produces no stream information, and the
+    /// problems are reported by `parse_error`. `parse_reader` also returns them as an error. It
+    /// must be set before parsing.
     ///
     /// # Arguments
     ///
-    /// * `pretty` - A boolean indicating whether to format the JSON output in a pretty, human-readable way.
+    /// * `strict` - A boolean indicating whether to reject malformed playlists.
+    ///
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Returns the error of the last parse in strict mode, if the playlist was rejected.
     ///
     /// # Returns
     ///
-    /// A `serde_json::Result<String>` representing the JSON output. If the serialization to JSON is successful,
-    /// the result will contain the JSON string. Otherwise, an error indicating the reason for the failure
-    /// will be returned.
+    /// An `Option<&StrictParseError>` listing the problems of the playlist with their line numbers.
     ///
-    pub fn get_json(&self, preety: bool) -> serde_json::Result<String> {
-        let streams_json: String;
-        if preety {
-            streams_json = serde_json::to_string_pretty(&self.streams_info)?;
-        } else {
-            streams_json = serde_json::to_string(&self.streams_info)?;
-        }
-        Ok(streams_json)
+    pub fn parse_error(&self) -> Option<&StrictParseError> {
+        self.parse_error.as_ref()
     }
 
-    /// Retrieves a vector containing all stream information.
+    fn is_valid_url(&self, url: &str) -> bool {
+        Url::parse(url).is_ok()
+    }
+
+    /// Registers an extractor capturing a vendor-specific attribute of the `#EXTINF` lines, such as
+    /// `catchup-source`, into `Info.extra_attributes`. It must be set before parsing.
     ///
-    /// This function returns a deep clone of the internal `streams_info` vector, which
-    /// contains all the stream information.
+    /// # Arguments
+    ///
+    /// * `attribute` - The name of the attribute, e.g. "catchup-source".
+    /// * `field` - The key of the value in `Info.extra_attributes`, e.g. "catchup_source".
+    ///
+    pub fn extract_attribute(&mut self, attribute: &str, field: &str) {
+        self.extractors.push(AttributeExtractor::Attribute {
+            name: attribute.to_string(),
+            field: field.to_string(),
+        });
+    }
+
+    /// Registers an extractor capturing the first group of a regular expression, or its whole
+    /// match if it has no group, into `Info.extra_attributes`. The regular expression is matched
+    /// against the lines of each entry, from the `#EXTINF` line up to the URL line, so it can also
+    /// capture directives such as `#KODIPROP`. It must be set before parsing.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The regular expression, e.g. `#KODIPROP:inputstream.adaptive.license_key=(.+)`.
+    /// * `field` - The key of the value in `Info.extra_attributes`.
     ///
     /// # Returns
     ///
-    /// A `Vec<Info>` containing all stream information. If there is no stream information
-    /// available, an empty vector will be returned.
+    /// A `Result` which is an error if the pattern is not a valid regular expression.
     ///
-    pub fn get_vector(&self) -> Vec<Info> {
-        self.streams_info.clone()
+    pub fn extract_regex(&mut self, pattern: &str, field: &str) -> Result<(), Box<dyn Error>> {
+        let regex = Regex::new(&format!("(?m){}", pattern))?;
+        self.extractors.push(AttributeExtractor::Regex {
+            regex,
+            field: field.to_string(),
+        });
+        Ok(())
     }
 
-    /// Retrieves a random stream from the available stream information.
+    /// Sets the fetcher used to download playlists from URLs, instead of the default HTTP client.
     ///
-    /// This function randomly selects a stream from the available stream information.
-    /// The `random_shuffle` parameter determines whether to shuffle the stream information
-    /// before selecting a random stream. If the stream information is empty, `None` will be returned.
+    /// This allows applications running on another async runtime than tokio, such as async-std or
+    /// smol, to parse URLs with their own HTTP client, including without the `net` feature.
     ///
     /// # Arguments
     ///
-    /// * `random_shuffle` - A boolean indicating whether to shuffle the stream information before
-    ///                      selecting a random stream.
+    /// * `fetch` - The fetcher to use for the following downloads.
     ///
-    /// # Returns
+    pub fn set_http_fetch<F: HttpFetch + 'static>(&mut self, fetch: F) {
+        self.http_fetch = Some(Arc::new(fetch));
+    }
+
+    /// Sets the function minting fresh URLs for the streams whose URL embeds an expiring token,
+    /// called by `refresh_urls`.
     ///
-    /// An `Option<&Info>` representing the randomly selected stream. If a stream is successfully
-    /// selected, the result will contain a reference to the stream. Otherwise, if the stream
-    /// information is empty, `None` will be returned.
+    /// # Arguments
     ///
-    pub fn get_random_stream(&mut self, random_shuffle: bool) -> Option<&Info> {
-        if self.streams_info.is_empty() {
-            eprintln!("No streams information so could not get any random stream.");
-            return None;
-        }
-        let mut rng = thread_rng();
-        let stream_infos = &mut self.streams_info[..];
-        if random_shuffle {
-            stream_infos.shuffle(&mut rng);
-        }
-        Some(stream_infos.choose(&mut rng).unwrap())
+    /// * `refresher` - An async function called with a copy of the stream information, returning
+    ///   its new URL, e.g. from the API of the provider, or `None` to keep the current one.
+    ///
+    pub fn set_url_refresher<F, Fut>(&mut self, refresher: F)
+    where
+        F: Fn(Info) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<String>> + Send + 'static,
+    {
+        self.url_refresher = Some(Arc::new(move |info| Box::pin(refresher(info))));
     }
 
-    /// Saves the stream information to a file in the specified format.
+    /// Registers a hook called with each entry of the following parses, e.g. to insert the
+    /// entries into a database without copying the parsed stream information.
     ///
-    /// This function saves the stream information to a file with the given `filename` and `format`.
-    /// If the `filename` already contains a file extension, it will be used as the format. Otherwise,
-    /// the `format` parameter will be used as the file extension.
+    /// The hooks are called as soon as each entry is parsed, in playlist order, or with
+    /// `check_live` as soon as its check finishes, in the order the checks finish, so the entries
+    /// can be processed while the rest of the playlist is parsed and checked. In strict mode they
+    /// are only called once the whole playlist is valid, and never when it is rejected. The hooks
+    /// are called by `parse_m3u`, `fetch_all`, `load_iptv_org` and the other parsing methods,
+    /// before the hooks registered with `on_complete`.
     ///
-    /// The supported formats are "json" and "m3u". For "json" format, the stream information will be
-    /// saved as a JSON string in a pretty printed format. For "m3u" format, the stream information will
-    /// be saved as an M3U playlist.
+    /// # Arguments
+    ///
+    /// * `hook` - The function called with each entry.
+    ///
+    pub fn on_entry<F>(&mut self, hook: F)
+    where
+        F: Fn(&Info) + Send + Sync + 'static,
+    {
+        self.entry_hooks.push(Box::new(hook));
+    }
+
+    /// Registers a hook called at the end of each following parse with the statistics of the
+    /// parsed entries.
+    ///
+    /// `fetch_all` calls the hooks once, with the statistics of the entries of all its sources.
     ///
     /// # Arguments
     ///
-    /// * `filename` - A string representing the name of the file to be saved. If the file already exists,
-    ///                it will be overwritten.
-    /// * `format` - A string representing the format in which the stream information should be saved. If
-    ///              the `filename` already contains a file extension, it will be used as the format.
-    ///              Otherwise, the `format` parameter will be used as the file extension.
+    /// * `hook` - The function called with the `PlaylistStats` of the parse.
     ///
-    /// # Panics
+    pub fn on_complete<F>(&mut self, hook: F)
+    where
+        F: Fn(&PlaylistStats) + Send + Sync + 'static,
+    {
+        self.complete_hooks.push(Box::new(hook));
+    }
+
+    /// Refreshes the stream URLs with the function set by `set_url_refresher`, e.g. right before
+    /// exporting the playlist.
     ///
-    /// This function panics if there is an error while converting the stream information to the specified format
-    /// or if there is an error while saving the file.
-    pub fn to_file(&self, filename: &str, format: &str) {
-        let format = if filename.contains(".") {
-            filename.split(".").last().unwrap_or(format)
-        } else {
-            format
-        };
+    /// The refreshed streams lose their resolved URL and the result of their last check, which
+    /// applied to the previous URL, and get the status of a stream that was not checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `expired_only` - Whether to only refresh the streams whose last check was refused with
+    ///   the HTTP status 401 or 403, instead of all of them.
+    /// * `concurrency` - The maximum number of URLs refreshed at the same time, so the API of the
+    ///   provider is not sent a request for every stream at once.
+    ///
+    /// # Returns
+    ///
+    /// The number of URLs changed, 0 if no refresher is set.
+    ///
+    pub async fn refresh_urls(&mut self, expired_only: bool, concurrency: usize) -> usize {
+        use futures::StreamExt;
 
-        let filename = match filename.to_lowercase().ends_with(format) {
-            true => filename.to_owned(),
-            false => format!("{}.{}", filename, format),
+        let Some(refresher) = self.url_refresher.clone() else {
+            return 0;
         };
-
-        if self.streams_info.is_empty() {
-            eprintln!("Either parsing is not done or no stream info was found after parsing !!!");
+        let is_expired = |info: &Info| {
+            let status = info.check.as_ref().and_then(|check| check.http_status);
+            matches!(status, Some(401 | 403))
+        };
+        let urls: Vec<Option<String>> = futures::stream::iter(&self.streams_info)
+            .map(|info| {
+                let request = match !expired_only || is_expired(info) {
+                    true => Some(refresher(info.clone())),
+                    false => None,
+                };
+                async move {
+                    match request {
+                        Some(request) => request.await,
+                        None => None,
+                    }
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+        let urls: Vec<Option<(String, String)>> = urls
+            .into_iter()
+            .zip(&self.streams_info)
+            .map(|(url, info)| url.filter(|url| !url.is_empty() && *url != info.url))
+            .map(|url| url.map(|url| (self.initial_status(&url, false), url)))
+            .collect();
+        let count = urls.iter().flatten().count();
+        if count > 0 {
+            self.save_backup();
+            for (info, url) in self.streams_info.iter_mut().zip(urls) {
+                if let Some((status, url)) = url {
+                    info.status = status;
+                    info.url = url;
+                    info.resolved_url = None;
+                    info.check = None;
+                }
+            }
+        }
+        count
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self), fields(bytes), err)
+    )]
+    async fn read_url(&self, url: &str) -> Result<String, Box<dyn Error>> {
+        let content = match &self.http_fetch {
+            Some(fetch) => fetch
+                .fetch(url)
+                .await
+                .map_err(|e| -> Box<dyn Error> { e })?,
+            None => self.read_url_default(url).await?,
+        };
+        record_span!("bytes", content.len());
+        Ok(content)
+    }
+
+    #[cfg(feature = "net")]
+    async fn read_url_default(&self, url: &str) -> Result<String, Box<dyn Error>> {
+        let client = self.http_client(self.fetch_timeout)?;
+        let response = client.get(url).send().await?;
+        let content = response.text().await?;
+        Ok(content)
+    }
+
+    #[cfg(not(feature = "net"))]
+    async fn read_url_default(&self, url: &str) -> Result<String, Box<dyn Error>> {
+        Err(format!(
+            "Reading {} requires the `net` feature or an `HttpFetch`",
+            url
+        )
+        .into())
+    }
+
+    /// Downloads or reads an HLS media playlist and parses its segments.
+    ///
+    /// Unlike `parse_m3u`, this does not change the stream information of the parser; it is meant
+    /// to inspect a single stream, e.g. to tell live streams from VOD or to catalog the duration of
+    /// a VOD stream. The HTTP settings of the parser apply to the download.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The URL or path of the media playlist, e.g. the URL of a stream.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` with the parsed `HlsMediaPlaylist`, or an error if the playlist cannot be
+    /// downloaded or read.
+    ///
+    pub async fn parse_hls_media(
+        &self,
+        location: &str,
+    ) -> Result<HlsMediaPlaylist, Box<dyn Error>> {
+        let content = self.read_location(location).await?;
+        Ok(HlsMediaPlaylist::parse(&content))
+    }
+
+    /// Pins the entries pointing at HLS master playlists to one of their variants.
+    ///
+    /// The `.m3u8` playlist of each entry is downloaded and, if it is a master playlist, the URL
+    /// of the entry is replaced with the URL of the chosen variant, e.g. for set-top boxes that
+    /// cannot switch variants or have limited bandwidth. Other entries are left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - How the variant is chosen.
+    ///
+    /// # Returns
+    ///
+    /// The number of entries whose URL was replaced.
+    ///
+    pub async fn resolve_hls_variant(&mut self, strategy: VariantStrategy) -> usize {
+        let parser = &*self;
+        let requests = parser.streams_info.iter().map(|info| async move {
+            let location = parser.resolve_location(&info.url);
+            let is_hls = location
+                .split(['?', '#'])
+                .next()
+                .unwrap_or_default()
+                .to_lowercase()
+                .ends_with(".m3u8");
+            if !is_hls {
+                return None;
+            }
+            let content = match parser.read_location(&location).await {
+                Ok(content) => content,
+                Err(e) => {
+                    eprintln!("Error: could not read {}: {}", location, e);
+                    return None;
+                }
+            };
+            let master = HlsMasterPlaylist::parse(&content);
+            let variant = master.select(strategy)?;
+            Some(parser.join_location(&location, &variant.uri))
+        });
+        let urls = futures::future::join_all(requests).await;
+        if urls.iter().all(Option::is_none) {
+            return 0;
+        }
+
+        self.save_backup();
+        let mut resolved = 0;
+        for (info, url) in self.streams_info.iter_mut().zip(urls) {
+            if let Some(url) = url {
+                info.url = url;
+                resolved += 1;
+            }
+        }
+        resolved
+    }
+
+    /// Reads the content at the URL or path.
+    async fn read_location(&self, location: &str) -> Result<String, Box<dyn Error>> {
+        if self.is_valid_url(location) {
+            self.read_url(location).await
+        } else {
+            Ok(read_to_string(location)?)
+        }
+    }
+
+    /// Resolves `reference` relative to the playlist at `location`, a URL or a path.
+    fn join_location(&self, location: &str, reference: &str) -> String {
+        if self.is_valid_url(reference) {
+            return reference.to_string();
+        }
+        if self.is_valid_url(location) {
+            return Url::parse(location)
+                .and_then(|base| base.join(reference))
+                .map(|url| url.to_string())
+                .unwrap_or_else(|_| reference.to_string());
+        }
+        let directory = Path::new(location)
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+        directory.join(reference).to_string_lossy().to_string()
+    }
+
+    /// Builds an HTTP client following the HTTP settings of the parser.
+    #[cfg(feature = "net")]
+    fn http_client(&self, timeout: Duration) -> reqwest::Result<Client> {
+        let mut builder = Client::builder()
+            .timeout(timeout)
+            .redirect(self.redirect_policy.to_reqwest())
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        if let Some(jar) = &self.cookie_jar {
+            builder = builder.cookie_provider(Arc::clone(jar));
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        builder.build()
+    }
+
+    /// Enables or disables backing up playlist files before overwriting them, disabled by default.
+    ///
+    /// When enabled, `to_file`, `to_file_raw`, the streaming writers and `export_favorites` first
+    /// rename an existing file to `name.YYYYMMDD-HHMMSS.bak` next to it, with the current UTC
+    /// time, so that a good playlist overwritten with a bad parse result, e.g. by a scheduled job,
+    /// can be restored. Only the most recent backups of each file are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep` - The number of backups to keep for each file, at least 1, or `None` to disable the
+    ///   backups.
+    ///
+    pub fn set_backup_retention(&mut self, keep: Option<usize>) {
+        self.backup_retention = keep;
+    }
+
+    /// Sets the minimum number of entries required to overwrite an existing playlist file, 0 by
+    /// default.
+    ///
+    /// A provider outage can make a playlist parse to a handful of entries; with this guard,
+    /// `to_file`, `to_file_raw`, the streaming writers and `export_favorites` refuse to replace an
+    /// existing file with fewer entries, unless forced with `set_force_overwrite`. New files are
+    /// always written.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_entries` - The minimum number of entries written over an existing file.
+    ///
+    pub fn set_min_entries(&mut self, min_entries: usize) {
+        self.min_entries = min_entries;
+    }
+
+    /// Enables or disables overwriting existing files regardless of `set_min_entries`.
+    ///
+    /// # Arguments
+    ///
+    /// * `force` - A boolean indicating whether to ignore the minimum number of entries.
+    ///
+    pub fn set_force_overwrite(&mut self, force: bool) {
+        self.force_overwrite = force;
+    }
+
+    /// Returns an error if writing `entries` entries to `path` would overwrite an existing file
+    /// with fewer entries than the minimum.
+    fn check_overwrite(&self, path: &str, entries: usize) -> std::io::Result<()> {
+        if self.force_overwrite || entries >= self.min_entries || !Path::new(path).exists() {
+            return Ok(());
+        }
+        Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!(
+                "Refusing to overwrite {} with {} entries, fewer than the minimum of {}",
+                path, entries, self.min_entries
+            ),
+        ))
+    }
+
+    /// Creates the output file, first moving an existing file to a backup if enabled.
+    fn create_output(&self, path: &str) -> std::io::Result<File> {
+        if let Some(keep) = self.backup_retention {
+            if let Some(backup) = backup::rotate(Path::new(path), keep)? {
+                eprintln!("Backed up {} to {}", path, backup.display());
+            }
+        }
+        File::create(path)
+    }
+
+    fn save_file(&self, filename: &str, data: &[u8]) {
+        if let Err(e) = self.check_overwrite(filename, self.streams_info.len()) {
+            eprintln!("Error: {}", e);
             return;
         }
+        let mut file = self.create_output(filename).unwrap();
+        file.write_all(data).unwrap();
+        eprintln!("Saved to file: {}", filename);
+    }
+
+    fn compare_values(&self, a: &str, b: &str) -> Ordering {
+        #[cfg(feature = "collation")]
+        if let Some(collator) = &self.collator {
+            return collator.compare(a, b);
+        }
+        if self.natural_sort {
+            sort::natural_cmp(a, b)
+        } else {
+            a.cmp(b)
+        }
+    }
+
+    /// Parses the specified M3U playlist file or URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path or URL of the M3U playlist. URLs require the `net` feature.
+    /// * `check_live` - A boolean indicating whether to check the availability of streams.
+    ///   If set to `true`, the parser will make a request to each stream URL to check its status.
+    ///   Streams are only checked with the `net` feature.
+    /// * `enforce_schema` - A boolean indicating whether to enforce the M3U schema.
+    ///   If set to `true`, every attribute is present in the output and missing ones are set to
+    ///   empty strings. If set to `false`, only the attributes actually found are present.
+    pub async fn parse_m3u(&mut self, path: &str, check_live: bool, enforce_schema: bool) {
+        let content: String;
+
+        if self.is_valid_url(path) {
+            match self.read_url(path).await {
+                Ok(url_content) => content = url_content,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return;
+                }
+            }
+        } else {
+            match read_to_string(path) {
+                Ok(file_content) => content = file_content,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return;
+                }
+            }
+        }
+        self.parse_content(&content, Some(path), check_live, enforce_schema)
+            .await;
+    }
+
+    /// Parses the M3U playlist contained in a string.
+    ///
+    /// Relative paths in the playlist are resolved against the current directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the M3U playlist.
+    /// * `check_live` - A boolean indicating whether to check the availability of streams.
+    /// * `enforce_schema` - A boolean indicating whether to enforce the M3U schema.
+    pub async fn parse_m3u_content(
+        &mut self,
+        content: &str,
+        check_live: bool,
+        enforce_schema: bool,
+    ) {
+        self.parse_content(content, None, check_live, enforce_schema)
+            .await;
+    }
+
+    /// Parses the M3U playlist read from a reader such as `std::io::stdin()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader to read the M3U playlist from, until the end.
+    /// * `check_live` - A boolean indicating whether to check the availability of streams.
+    /// * `enforce_schema` - A boolean indicating whether to enforce the M3U schema.
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` which is an error if the playlist could not be read, or was rejected in
+    /// strict mode.
+    ///
+    pub async fn parse_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+        check_live: bool,
+        enforce_schema: bool,
+    ) -> std::io::Result<()> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        self.parse_content(&content, None, check_live, enforce_schema)
+            .await;
+        match &self.parse_error {
+            Some(error) => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                error.clone(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Creates a parser holding the streams of the iptv-org API, with the default timeout.
+    ///
+    /// See `load_iptv_org`.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - An `ApiOptions` struct selecting the channels to load.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` with the parser, which is an error if the API could not be downloaded or parsed.
+    ///
+    pub async fn from_iptv_org(options: &ApiOptions) -> Result<M3uParser, Box<dyn Error>> {
+        let mut parser = M3uParser::new(None);
+        parser.load_iptv_org(options).await?;
+        Ok(parser)
+    }
+
+    /// Replaces the stream information with the streams of the iptv-org API.
+    ///
+    /// Rather than scraping the attributes of the generated iptv-org playlists, this downloads
+    /// the channels, streams, guides, languages and countries of the JSON API, so the stream
+    /// information has the proper country, language and categories of its channel. The website
+    /// of the channel, the quality and feed of the stream and the site of its guide are stored in
+    /// `Info.extra_attributes` as "website", "quality", "feed", "guide_site" and
+    /// "guide_site_id". The streams are not checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - An `ApiOptions` struct selecting the channels to load.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the API could not be downloaded or parsed, in which case
+    /// the stream information is left unchanged.
+    ///
+    pub async fn load_iptv_org(&mut self, options: &ApiOptions) -> Result<(), Box<dyn Error>> {
+        let files = [
+            "channels.json",
+            "streams.json",
+            "guides.json",
+            "languages.json",
+            "countries.json",
+        ];
+        let urls = files.map(|file| options.url(file));
+        let downloads = urls.iter().map(|url| self.read_url(url));
+        let [channels, streams, guides, languages, countries] =
+            futures::future::join_all(downloads)
+                .await
+                .try_into()
+                .map_err(|_| "Unexpected number of downloads")?;
+        let data = iptv_org::ApiData {
+            channels: channels?,
+            streams: streams?,
+            guides: guides?,
+            languages: languages?,
+            countries: countries?,
+        };
+        let mut streams_info = iptv_org::build(&data, options)?;
+
+        let parsed_at = Some(cache::unix_now());
+        for (source_index, info) in streams_info.iter_mut().enumerate() {
+            info.status = self.initial_status(&info.url, false);
+            info.source = Some(options.base_url.clone());
+            info.source_index = source_index;
+            info.first_seen = parsed_at;
+            info.last_seen = parsed_at;
+            self.run_entry_hooks(info);
+        }
+        self.parsed_at = parsed_at;
+        self.parse_error = None;
+        self.warnings = vec![];
+        self.streams_info_backup = None;
+        self.streams_info = streams_info;
+        self.index = OnceLock::new();
+        self.run_complete_hooks();
+        Ok(())
+    }
+
+    /// Downloads and parses several playlists concurrently, e.g. the playlists of several
+    /// providers.
+    ///
+    /// The stream information of the playlists is concatenated in the order of the sources. As
+    /// with `parse_m3u`, each entry has the URL or path of its playlist in `Info.source`, so it can
+    /// be filtered by "source" or attributed to its provider. Sources that cannot be downloaded,
+    /// read or, in strict mode, parsed are skipped without aborting the others. Their errors are
+    /// only reported in the `MultiParseReport`, so `parse_error` is `None` afterwards. The line
+    /// numbers of the parse warnings refer to their own playlist.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - The paths or URLs of the M3U playlists.
+    /// * `per_host` - The maximum number of playlists downloaded from the same host at the same
+    ///   time, so providers limiting concurrent connections are not hit too hard.
+    /// * `check_live` - A boolean indicating whether to check the availability of streams.
+    /// * `enforce_schema` - A boolean indicating whether to enforce the M3U schema.
+    ///
+    /// # Returns
+    ///
+    /// A `MultiParseReport` with the number of entries of each source, or why it failed.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn fetch_all(
+        &mut self,
+        sources: &[&str],
+        per_host: usize,
+        check_live: bool,
+        enforce_schema: bool,
+    ) -> MultiParseReport {
+        let mut limits: HashMap<String, Arc<tokio::sync::Semaphore>> = HashMap::new();
+        let parser = &*self;
+        let downloads = sources.iter().map(|source| {
+            let host = Url::parse(source)
+                .ok()
+                .and_then(|url| url.host_str().map(String::from))
+                .unwrap_or_default();
+            let limit = limits
+                .entry(host)
+                .or_insert_with(|| Arc::new(tokio::sync::Semaphore::new(per_host.max(1))))
+                .clone();
+            async move {
+                let _permit = limit.acquire().await;
+                parser.read_location(source).await
+            }
+        });
+        let contents = futures::future::join_all(downloads).await;
+        self.parse_sources(sources, contents, check_live, enforce_schema)
+            .await
+    }
+
+    /// Parses the contents read from the sources, concatenating their stream information in the
+    /// order of the sources and reporting the sources that could not be read or parsed.
+    async fn parse_sources(
+        &mut self,
+        sources: &[&str],
+        contents: Vec<Result<String, Box<dyn Error>>>,
+        check_live: bool,
+        enforce_schema: bool,
+    ) -> MultiParseReport {
+        let mut report = MultiParseReport::default();
+        let mut streams_info = vec![];
+        let mut warnings = vec![];
+        self.merging_sources = true;
+        for (source, content) in sources.iter().zip(contents) {
+            let content = match content {
+                Ok(content) => content,
+                Err(e) => {
+                    eprintln!("Error: could not read {}: {}", source, e);
+                    report.sources.push(SourceReport {
+                        source: source.to_string(),
+                        entries: 0,
+                        error: Some(e.to_string()),
+                    });
+                    continue;
+                }
+            };
+            self.parse_content(&content, Some(source), check_live, enforce_schema)
+                .await;
+            report.sources.push(SourceReport {
+                source: source.to_string(),
+                entries: self.streams_info.len(),
+                // Moved to the report, so that it does not outlive its source.
+                error: self.parse_error.take().map(|e| e.to_string()),
+            });
+            warnings.append(&mut self.warnings);
+            // Numbered after the entries of the previous sources, for `sort_by_original_order`.
+            for info in &mut self.streams_info {
+                info.source_index += streams_info.len();
+            }
+            streams_info.append(&mut self.streams_info);
+        }
+        self.merging_sources = false;
+        self.warnings = warnings;
+        self.streams_info_backup = None;
+        self.streams_info = streams_info;
+        self.index = OnceLock::new();
+        self.complete_parse();
+        report
+    }
+
+    async fn parse_content(
+        &mut self,
+        content: &str,
+        base_location: Option<&str>,
+        check_live: bool,
+        enforce_schema: bool,
+    ) {
+        #[cfg(not(feature = "net"))]
+        if check_live {
+            eprintln!("Warning: checking streams requires the `net` feature, skipping the check");
+        }
+        self.check_live = check_live && cfg!(feature = "net");
+        self.enforce_schema = enforce_schema;
+        self.base_location = base_location.map(|location| location.to_string());
+
+        self.parsed_at = Some(cache::unix_now());
+        self.parse_error = None;
+        let content = normalize_line_endings(content);
+        self.content = asx::to_m3u(&content).unwrap_or(content);
+        self.lines = self
+            .content
+            .lines()
+            .map(|line| {
+                let line = line.trim();
+                let start = line.as_ptr() as usize - self.content.as_ptr() as usize;
+                (start, start + line.len())
+            })
+            .collect();
+
+        self.playlist_kind = PlaylistKind::detect(self.lines_from(0).map(|(_, line)| line));
+        if self.lines_from(0).any(|(_, line)| !line.is_empty()) {
+            self.parse_lines().await;
+        } else {
+            eprintln!("No content to parse!!!");
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(kind = ?self.playlist_kind, entries, warnings))
+    )]
+    async fn parse_lines(&mut self) {
+        if self.playlist_kind == PlaylistKind::HlsMedia {
+            return self.parse_hls_media_lines().await;
+        }
+
+        let mut streams_info: Vec<Info> = vec![];
+        let mut warnings: Vec<ParseWarning> = vec![];
+        let mut url_lines = HashSet::new();
+        let entry_directive = self.entry_directive();
+        let extinf_line_nums: Vec<usize> = (0..self.lines.len())
+            .filter(|&line_num| {
+                let line = self.line(line_num);
+                if self.playlist_kind == PlaylistKind::PlainM3u {
+                    !line.starts_with('#') && self.is_stream_location(line)
+                } else {
+                    line.starts_with(entry_directive)
+                }
+            })
+            .collect();
+        // Entries to check or to validate in strict mode are passed to the hooks later.
+        let hook_entries = !self.strict && !self.checks_while_parsing();
+        let entries = self.parse_entries(&extinf_line_nums);
+        for (line_num, entry) in extinf_line_nums.into_iter().zip(entries) {
+            match entry {
+                Some((info, url_line_nums)) => {
+                    url_lines.extend(url_line_nums);
+                    let mut info = Info {
+                        source_index: streams_info.len(),
+                        line_number: line_num + 1,
+                        source: self.base_location.clone(),
+                        first_seen: self.parsed_at,
+                        last_seen: self.parsed_at,
+                        ..info
+                    };
+                    self.apply_overrides_to(std::slice::from_mut(&mut info));
+                    if hook_entries {
+                        self.run_entry_hooks(&info);
+                    }
+                    streams_info.push(info);
+                }
+                None => warnings.push(ParseWarning {
+                    line_number: line_num + 1,
+                    reason: ParseWarningReason::MissingUrl,
+                    raw_line: self.line(line_num).to_string(),
+                }),
+            }
+        }
+
+        // Lines that are neither directives nor used as the URL of an entry.
+        for (line_num, line) in self.lines_from(0) {
+            if line.is_empty() || line.starts_with('#') || url_lines.contains(&line_num) {
+                continue;
+            }
+            let reason = if self.is_stream_location(line) {
+                ParseWarningReason::UrlWithoutExtinf
+            } else {
+                ParseWarningReason::UnrecognizedLine
+            };
+            warnings.push(ParseWarning {
+                line_number: line_num + 1,
+                reason,
+                raw_line: line.to_string(),
+            });
+        }
+        warnings.sort_by_key(|warning| warning.line_number);
+        if self.strict {
+            if let Some(error) = self.strict_error(&warnings) {
+                eprintln!("Error: {}", error);
+                record_span!("warnings", warnings.len());
+                self.parse_error = Some(error);
+                self.warnings = warnings;
+                self.streams_info_backup = None;
+                self.streams_info = vec![];
+                self.index = OnceLock::new();
+                return;
+            }
+        }
+        self.warnings = warnings;
+        if self.strict && !self.checks_while_parsing() {
+            streams_info
+                .iter()
+                .for_each(|info| self.run_entry_hooks(info));
+        }
+
+        #[cfg(feature = "net")]
+        if self.check_live {
+            streams_info = self
+                .check_streams_info_with(streams_info, |info| self.run_entry_hooks(info))
+                .await;
+        }
+
+        self.streams_info_backup = None;
+        self.streams_info = streams_info;
+        self.index = OnceLock::new();
+        record_span!("entries", self.streams_info.len());
+        record_span!("warnings", self.warnings.len());
+        self.complete_parse();
+    }
+
+    /// Returns whether the entries are checked while parsing, which requires the `net` feature.
+    fn checks_while_parsing(&self) -> bool {
+        cfg!(feature = "net") && self.check_live
+    }
+
+    /// Calls the hooks registered with `on_entry` on a parsed entry.
+    fn run_entry_hooks(&self, info: &Info) {
+        for hook in &self.entry_hooks {
+            hook(info);
+        }
+    }
+
+    /// Reports the end of a parse, unless it is one of the sources of `parse_sources`.
+    fn complete_parse(&self) {
+        if !self.merging_sources {
+            self.run_complete_hooks();
+            eprintln!("Parsing completed !!!");
+        }
+    }
+
+    /// Calls the hooks registered with `on_complete` on the parsed entries.
+    fn run_complete_hooks(&self) {
+        if !self.complete_hooks.is_empty() {
+            let stats = self.stats();
+            for hook in &self.complete_hooks {
+                hook(&stats);
+            }
+        }
+    }
+
+    /// Returns the error of the playlist in strict mode: the missing `#EXTM3U` header and the
+    /// entries missing their URL or `#EXTINF` line, if any.
+    fn strict_error(&self, warnings: &[ParseWarning]) -> Option<StrictParseError> {
+        let mut problems = vec![];
+        if let Some((line_num, line)) = self.lines_from(0).find(|(_, line)| !line.is_empty()) {
+            if !line.starts_with("#EXTM3U") {
+                problems.push(ParseWarning {
+                    line_number: line_num + 1,
+                    reason: ParseWarningReason::MissingHeader,
+                    raw_line: line.to_string(),
+                });
+            }
+        }
+        problems.extend(
+            warnings
+                .iter()
+                .filter(|warning| warning.reason != ParseWarningReason::UnrecognizedLine)
+                .cloned(),
+        );
+        (!problems.is_empty()).then_some(StrictParseError { problems })
+    }
+
+    /// Returns the directive starting each entry: `#EXT-X-STREAM-INF` for the variants of an HLS
+    /// master playlist, `#EXTINF` otherwise.
+    fn entry_directive(&self) -> &'static str {
+        match self.playlist_kind {
+            PlaylistKind::HlsMaster => "#EXT-X-STREAM-INF",
+            _ => "#EXTINF",
+        }
+    }
+
+    /// Parses an HLS media playlist as a single stream, located at the parsed location.
+    ///
+    /// The segments of a media playlist are parts of one stream rather than channels, so they do
+    /// not produce stream information of their own.
+    async fn parse_hls_media_lines(&mut self) {
+        self.warnings = vec![];
+        self.streams_info_backup = None;
+        self.streams_info = vec![];
+        self.index = OnceLock::new();
+        let location = match &self.base_location {
+            Some(location) => location.clone(),
+            None => {
+                eprintln!("HLS media playlist without location, no stream to add !!!");
+                return;
+            }
+        };
+        let playlist = HlsMediaPlaylist::parse(&self.content);
+        let mut info = Info {
+            title: title_from_location(&location),
+            duration: (!playlist.is_live()).then(|| playlist.total_duration()),
+            status: self.initial_status(&location, self.check_live),
+            source: Some(location.clone()),
+            url: location,
+            line_number: 1,
+            first_seen: self.parsed_at,
+            last_seen: self.parsed_at,
+            ..Default::default()
+        };
+        self.apply_overrides_to(std::slice::from_mut(&mut info));
+        if !self.checks_while_parsing() {
+            self.run_entry_hooks(&info);
+        }
+        self.streams_info = vec![info];
+        #[cfg(feature = "net")]
+        if self.check_live {
+            let streams_info = std::mem::take(&mut self.streams_info);
+            self.streams_info = self
+                .check_streams_info_with(streams_info, |info| self.run_entry_hooks(info))
+                .await;
+        }
+        record_span!("entries", self.streams_info.len());
+        self.complete_parse();
+    }
+
+    /// Returns when the playlist was last parsed, in seconds since the Unix epoch, or `None` if
+    /// nothing was parsed yet.
+    ///
+    /// The parsed entries have their `first_seen` and `last_seen` set to this time.
+    pub fn parsed_at(&self) -> Option<u64> {
+        self.parsed_at
+    }
+
+    /// Returns the type of the parsed playlist.
+    ///
+    /// The type is detected from the directives and attributes of the playlist and selects how it
+    /// is parsed: the variants of an HLS master playlist are parsed as streams, while an HLS media
+    /// playlist is parsed as a single stream located at the parsed URL or path. Each URL or path
+    /// of a plain M3U playlist, without `#EXTINF` lines, is parsed as a stream titled after its
+    /// file name.
+    ///
+    /// # Returns
+    ///
+    /// The `PlaylistKind` of the last parsed playlist.
+    ///
+    pub fn playlist_kind(&self) -> PlaylistKind {
+        self.playlist_kind
+    }
+
+    /// Returns the status of a stream before its availability is checked.
+    ///
+    /// Acestream links are "GOOD" unless they are checked with an Ace Stream engine, as are local
+    /// files unless they are verified during the check. Everything else is "BAD" until checked.
+    #[cfg_attr(not(feature = "net"), allow(unused_variables))]
+    fn initial_status(&self, location: &str, checking: bool) -> String {
+        #[cfg(feature = "net")]
+        let (check_acestream, check_local_files) = (
+            checking && self.acestream_engine.is_some(),
+            checking && self.check_local_files,
+        );
+        #[cfg(not(feature = "net"))]
+        let (check_acestream, check_local_files) = (false, false);
+
+        if STREAMS_REGEX.is_match(location) {
+            return String::from(if check_acestream { "BAD" } else { "GOOD" });
+        }
+        // Relative paths of a remote playlist are URLs that have to be checked like any other.
+        let is_local =
+            !self.is_valid_url(location) && !self.is_valid_url(&self.resolve_location(location));
+        if is_local && !check_local_files {
+            String::from("GOOD")
+        } else {
+            String::from("BAD")
+        }
+    }
+
+    /// Returns the stream checker, the Ace Stream engine and the liveness cache used by the checks,
+    /// which is an error if the HTTP client cannot be built, e.g. with an invalid root certificate.
+    #[cfg(feature = "net")]
+    async fn check_context(
+        &self,
+    ) -> reqwest::Result<(
+        Arc<dyn StreamChecker>,
+        Option<AceStreamEngine>,
+        Option<LivenessCache>,
+    )> {
+        let checker = match &self.stream_checker {
+            Some(checker) => Arc::clone(checker),
+            None => {
+                let client = self.http_client(self.check_timeout)?;
+                Arc::new(HttpChecker::with_client(client, &self.useragent))
+            }
+        };
+        let cache = self
+            .liveness_cache
+            .as_ref()
+            .map(|(path, ttl)| LivenessCache::load(path, *ttl));
+        let engine = match &self.acestream_engine {
+            Some(url) => {
+                let client = self.http_client(self.check_timeout)?;
+                AceStreamEngine::connect(client, url)
+                    .await
+                    .map_err(|e| eprintln!("Error: could not reach the Ace Stream engine: {}", e))
+                    .ok()
+            }
+            None => None,
+        };
+        Ok((checker, engine, cache))
+    }
+
+    /// Checks the streams concurrently and returns them in the order given, whatever the order in
+    /// which their checks finish, so that the parsed entries keep the order of the playlist.
+    #[cfg(feature = "net")]
+    async fn check_streams_info(&self, streams_info: Vec<Info>) -> Vec<Info> {
+        self.check_streams_info_with(streams_info, |_| {}).await
+    }
+
+    /// Checks the streams like `check_streams_info`, calling `on_checked` with each stream as soon
+    /// as its check finishes.
+    #[cfg(feature = "net")]
+    async fn check_streams_info_with(
+        &self,
+        streams_info: Vec<Info>,
+        on_checked: impl Fn(&Info),
+    ) -> Vec<Info> {
+        let (checker, engine, cache) = match self.check_context().await {
+            Ok(context) => context,
+            Err(e) => {
+                // The streams are left unchecked, with their status before the check.
+                eprintln!("Error: could not check the streams: {}", e);
+                streams_info.iter().for_each(on_checked);
+                return streams_info;
+            }
+        };
+        let on_checked = &on_checked;
+        let requests = streams_info.into_iter().map(|info| async {
+            let info = self
+                .check_stream(info, checker.as_ref(), engine.as_ref(), cache.as_ref())
+                .await;
+            on_checked(&info);
+            info
+        });
+        let streams_info = futures::future::join_all(requests).await;
+        if let Some(cache) = cache {
+            if let Err(e) = cache.save() {
+                eprintln!("Error: could not save the liveness cache: {}", e);
+            }
+        }
+        streams_info
+    }
+
+    /// Replaces the URL of each stream that is down by its first backup URL that is available.
+    ///
+    /// The backup URLs of the streams whose status is "BAD", e.g. after parsing with `check_live`
+    /// or `check_streams`, are checked in the same way. The replaced URL is moved to the end of
+    /// the backup URLs.
+    ///
+    /// # Returns
+    ///
+    /// The number of streams whose URL was replaced.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn promote_first_good_url(&mut self) -> usize {
+        let parser = &*self;
+        let candidates: Vec<(usize, Info)> = parser
+            .streams_info
+            .iter()
+            .enumerate()
+            .filter(|(_, info)| info.status == "BAD")
+            .flat_map(|(position, info)| {
+                info.backup_urls.iter().map(move |url| {
+                    let candidate = Info {
+                        url: url.clone(),
+                        status: parser.initial_status(url, true),
+                        check: None,
+                        ..info.clone()
+                    };
+                    (position, candidate)
+                })
+            })
+            .collect();
+        if candidates.is_empty() {
+            return 0;
+        }
+        let (positions, candidates): (Vec<usize>, Vec<Info>) = candidates.into_iter().unzip();
+        let checked = self.check_streams_info(candidates).await;
+
+        let mut promoted: BTreeMap<usize, Info> = BTreeMap::new();
+        for (position, candidate) in positions.into_iter().zip(checked) {
+            if candidate.status == "GOOD" {
+                promoted.entry(position).or_insert(candidate);
+            }
+        }
+        if promoted.is_empty() {
+            return 0;
+        }
+        self.save_backup();
+        for (position, candidate) in &promoted {
+            let info = &mut self.streams_info[*position];
+            let previous = std::mem::replace(&mut info.url, candidate.url.clone());
+            info.backup_urls.retain(|url| *url != candidate.url);
+            info.backup_urls.push(previous);
+            info.status = candidate.status.clone();
+            info.check = candidate.check.clone();
+            info.resolved_url = candidate.resolved_url.clone();
+        }
+        promoted.len()
+    }
+
+    /// Checks the availability of the current streams, yielding each result as soon as its check
+    /// finishes, e.g. to update the status of each channel in a UI while the others are checked.
+    ///
+    /// The checks are the same as `check_streams`, with the configured stream checker and
+    /// liveness cache, but their results are not stored in the stream information. Local files and
+    /// acestream links that would not be checked are not yielded.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of the position of each checked entry along with its `CheckResult`, in the order
+    /// the checks finish.
+    ///
+    #[cfg(feature = "net")]
+    pub fn check_live_stream(&self) -> impl futures::Stream<Item = (usize, CheckResult)> + '_ {
+        use futures::stream::{self, FuturesUnordered, StreamExt};
+
+        stream::once(self.check_context())
+            .filter_map(|context| async move {
+                context
+                    .map_err(|e| eprintln!("Error: could not check the streams: {}", e))
+                    .ok()
+            })
+            .flat_map(move |(checker, engine, cache)| {
+                let engine = Arc::new(engine);
+                let cache = Arc::new(cache);
+                let checks: FuturesUnordered<_> = self
+                    .streams_info
+                    .iter()
+                    .enumerate()
+                    .map(|(position, info)| {
+                        let info = Info {
+                            status: self.initial_status(&info.url, true),
+                            check: None,
+                            ..info.clone()
+                        };
+                        let checker = Arc::clone(&checker);
+                        let engine = Arc::clone(&engine);
+                        let cache = Arc::clone(&cache);
+                        async move {
+                            let info = self
+                                .check_stream(
+                                    info,
+                                    checker.as_ref(),
+                                    engine.as_ref().as_ref(),
+                                    cache.as_ref().as_ref(),
+                                )
+                                .await;
+                            info.check.map(|check| (position, check))
+                        }
+                    })
+                    .collect();
+                // Saves the liveness cache once every check finished.
+                let save = stream::once(async move {
+                    if let Some(cache) = cache.as_ref() {
+                        if let Err(e) = cache.save() {
+                            eprintln!("Error: could not save the liveness cache: {}", e);
+                        }
+                    }
+                    None
+                });
+                checks.chain(save)
+            })
+            .filter_map(|result| async move { result })
+    }
+
+    /// Checks the availability of the current streams again and updates their status.
+    ///
+    /// This performs the same check as parsing with `check_live`, using the configured stream
+    /// checker and liveness cache, without parsing the playlist again. Filters and sorting applied
+    /// to the stream information are kept.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn check_streams(&mut self) {
+        let streams_info: Vec<Info> = std::mem::take(&mut self.streams_info)
+            .into_iter()
+            .map(|info| Info {
+                status: self.initial_status(&info.url, true),
+                check: None,
+                ..info
+            })
+            .collect();
+        self.streams_info = self.check_streams_info(streams_info).await;
+    }
+
+    /// Parse the entries starting at the given `#EXTINF` lines, keeping their order.
+    ///
+    /// With the `parallel` feature, the entries are parsed on the rayon thread pool. Parsing is pure
+    /// CPU work, the availability checks are run afterwards on the async runtime.
+    #[cfg(feature = "parallel")]
+    fn parse_entries(&self, line_nums: &[usize]) -> Vec<Option<(Info, RangeInclusive<usize>)>> {
+        use rayon::prelude::*;
+
+        line_nums
+            .par_iter()
+            .map(|&line_num| self.parse_line(line_num))
+            .collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn parse_entries(&self, line_nums: &[usize]) -> Vec<Option<(Info, RangeInclusive<usize>)>> {
+        line_nums
+            .iter()
+            .map(|&line_num| self.parse_line(line_num))
+            .collect()
+    }
+
+    /// Returns the line at `line_num`, starting at 0, without surrounding whitespace.
+    fn line(&self, line_num: usize) -> &str {
+        let (start, end) = self.lines[line_num];
+        &self.content[start..end]
+    }
+
+    /// Returns the lines starting at `line_num` along with their line numbers.
+    fn lines_from(&self, line_num: usize) -> impl Iterator<Item = (usize, &str)> {
+        (line_num..self.lines.len()).map(|line_num| (line_num, self.line(line_num)))
+    }
+
+    /// Parses the entry starting at `line_num`, returning it along with the lines of its URLs.
+    fn parse_line(&self, line_num: usize) -> Option<(Info, RangeInclusive<usize>)> {
+        if self.playlist_kind == PlaylistKind::PlainM3u {
+            return Some((self.parse_plain_line(line_num), line_num..=line_num));
+        }
+
+        let line_info = self.line(line_num);
+        let mut found_link = None;
+
+        // The URL follows the #EXTINF line, possibly after directive lines such as #EXTVLCOPT.
+        for (url_line_num, line) in self.lines_from(line_num + 1) {
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with(self.entry_directive()) {
+                break;
+            }
+            if STREAMS_REGEX.is_match(line) || self.is_valid_url(line) {
+                found_link = Some((url_line_num, line.to_string()));
+                break;
+            } else if FILE_REGEX.is_match(line) || RELATIVE_PATH_REGEX.is_match(line) {
+                let stream_link = if self.canonicalize_paths {
+                    self.resolve_location(line)
+                } else {
+                    line.to_string()
+                };
+                found_link = Some((url_line_num, stream_link));
+                break;
+            }
+        }
+
+        let (url_line_num, stream_link) = found_link?;
+        let mut info = Info {
+            status: self.initial_status(&stream_link, self.check_live),
+            ..Default::default()
+        };
+
+        // Redundant feeds of the channel on the following URL lines.
+        let mut last_url_line_num = url_line_num;
+        for (backup_line_num, line) in self.lines_from(url_line_num + 1) {
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('#') || !self.is_stream_location(line) {
+                break;
+            }
+            let backup_url = if self.canonicalize_paths && !self.is_valid_url(line) {
+                self.resolve_location(line)
+            } else {
+                line.to_string()
+            };
+            info.backup_urls.push(backup_url);
+            last_url_line_num = backup_line_num;
+        }
+
+        info.url = stream_link;
+        info.parse_extinf(line_info);
+        if self.playlist_kind == PlaylistKind::HlsMaster {
+            info.title = hls::variant_title(line_info);
+        }
+        info.parse_directives(&self.get_directives(line_num, url_line_num));
+
+        // Raw lines
+        let raw: Vec<&str> = self
+            .lines_from(line_num)
+            .take(last_url_line_num - line_num + 1)
+            .map(|(_, line)| line)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let raw = raw.join("\n");
+        info.extra_attributes = self
+            .extractors
+            .iter()
+            .filter_map(|extractor| extractor.extract(line_info, &raw))
+            .collect();
+        info.raw = Some(raw);
+        info.raw_hash = hash_entry(&info.to_m3u_entry());
+
+        Some((info, url_line_num..=last_url_line_num))
+    }
+
+    /// Parses the URL or path at `line_num` of a plain M3U playlist.
+    ///
+    /// The title is the file name of the location without its extension, or the location itself
+    /// when it has no file name, e.g. for `http://example.com/`.
+    fn parse_plain_line(&self, line_num: usize) -> Info {
+        let line = self.line(line_num);
+        let url = if self.canonicalize_paths && !self.is_valid_url(line) {
+            self.resolve_location(line)
+        } else {
+            line.to_string()
+        };
+        let title = match title_from_location(line) {
+            title if title.is_empty() => line.to_string(),
+            title => title,
+        };
+        let mut info = Info {
+            title,
+            status: self.initial_status(&url, self.check_live),
+            url,
+            raw: Some(line.to_string()),
+            ..Default::default()
+        };
+        info.raw_hash = hash_entry(&info.to_m3u_entry());
+        info
+    }
+
+    /// Returns the directive lines of the entry starting at `line_num`: those between the
+    /// `#EXTINF` line and the URL line, and those directly preceding the `#EXTINF` line.
+    fn get_directives(&self, line_num: usize, url_line_num: usize) -> Vec<&str> {
+        let is_directive = |line: &&str| {
+            line.starts_with('#') && !line.starts_with("#EXTINF") && !line.starts_with("#EXTM3U")
+        };
+        let mut preceding: Vec<&str> = (0..line_num)
+            .rev()
+            .map(|line_num| self.line(line_num))
+            .filter(|line| !line.is_empty())
+            .take_while(is_directive)
+            .collect();
+        preceding.reverse();
+        let following = self
+            .lines_from(line_num + 1)
+            .take(url_line_num - line_num - 1)
+            .map(|(_, line)| line)
+            .filter(is_directive);
+        following.chain(preceding).collect()
+    }
+
+    #[cfg(feature = "net")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(url = %info.url, status, latency_ms))
+    )]
+    async fn check_stream(
+        &self,
+        mut info: Info,
+        checker: &dyn StreamChecker,
+        engine: Option<&AceStreamEngine>,
+        cache: Option<&LivenessCache>,
+    ) -> Info {
+        let location = self.resolve_location(&info.url);
+        let result = if info.status.eq("BAD") && !self.is_valid_url(&location) {
+            let available = self.check_local_file(&location).await;
+            CheckResult {
+                available,
+                error: (!available).then(|| String::from("File is missing, empty or unreadable")),
+                error_kind: (!available).then_some(CheckErrorKind::Other),
+                checked_at: cache::unix_now(),
+                ..Default::default()
+            }
+        } else if info.status.eq("BAD") {
+            match cache.and_then(|cache| cache.get(&location)) {
+                Some(result) => result,
+                None => {
+                    let result = if !STREAMS_REGEX.is_match(&location) {
+                        let headers = info.http_headers();
+                        let check = checker.check_with_headers(&location, &headers);
+                        self.check_with_timeout(check).await
+                    } else if let Some(engine) = engine {
+                        self.check_with_timeout(engine.check(&location)).await
+                    } else {
+                        CheckResult {
+                            error: Some(String::from("Ace Stream engine is not available")),
+                            error_kind: Some(CheckErrorKind::Other),
+                            checked_at: cache::unix_now(),
+                            ..Default::default()
+                        }
+                    };
+                    if let Some(cache) = cache {
+                        cache.insert(&location, &result);
+                    }
+                    result
+                }
+            }
+        } else {
+            return info;
+        };
+        if result.available {
+            info.status = "GOOD".to_string();
+        }
+        record_span!("status", info.status.as_str());
+        record_span!("latency_ms", result.latency_ms);
+        if self.record_resolved_url {
+            info.resolved_url = result.final_url.clone();
+        }
+        info.check = Some(result);
+        info
+    }
+
+    /// Waits for the check, giving up after the check timeout.
+    #[cfg(feature = "net")]
+    async fn check_with_timeout(&self, check: impl Future<Output = CheckResult>) -> CheckResult {
+        tokio::time::timeout(self.check_timeout, check)
+            .await
+            .unwrap_or_else(|_| CheckResult {
+                latency_ms: Some(self.check_timeout.as_millis() as u64),
+                error: Some(String::from("Check timed out")),
+                error_kind: Some(CheckErrorKind::Timeout),
+                checked_at: cache::unix_now(),
+                ..Default::default()
+            })
+    }
+
+    /// Returns whether the line is a URL, an acestream link or a path to a file.
+    fn is_stream_location(&self, line: &str) -> bool {
+        STREAMS_REGEX.is_match(line)
+            || self.is_valid_url(line)
+            || FILE_REGEX.is_match(line)
+            || RELATIVE_PATH_REGEX.is_match(line)
+    }
+
+    /// Resolves a relative path against the location of the parsed playlist. Absolute paths and
+    /// URLs are returned unchanged.
+    fn resolve_location(&self, location: &str) -> String {
+        if self.is_valid_url(location) || !RELATIVE_PATH_REGEX.is_match(location) {
+            return location.to_string();
+        }
+        match &self.base_location {
+            Some(base) if self.is_valid_url(base) => Url::parse(base)
+                .and_then(|base| base.join(location))
+                .map(|url| url.to_string())
+                .unwrap_or_else(|_| location.to_string()),
+            Some(base) => {
+                let directory = Path::new(base).parent().unwrap_or_else(|| Path::new(""));
+                let path = directory.join(location);
+                std::fs::canonicalize(&path)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .to_string()
+            }
+            None => location.to_string(),
+        }
+    }
+
+    #[cfg(feature = "net")]
+    async fn check_local_file(&self, path: &str) -> bool {
+        match tokio::fs::metadata(path).await {
+            Ok(metadata) => {
+                metadata.is_file()
+                    && (!self.require_non_empty_files || metadata.len() > 0)
+                    && tokio::fs::File::open(path).await.is_ok()
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn get_m3u_content(&self) -> String {
+        self.render_m3u(&self.streams_info)
+    }
+
+    fn get_raw_m3u_content(&self) -> String {
+        if self.streams_info.is_empty() {
+            return String::new();
+        }
+
+        let header = self
+            .lines_from(0)
+            .map(|(_, line)| line)
+            .find(|line| !line.is_empty())
+            .filter(|line| line.starts_with("#EXTM3U"))
+            .unwrap_or("#EXTM3U")
+            .to_string();
+        let content: Vec<String> = self
+            .streams_info
+            .iter()
+            .map(|stream_info| stream_info.to_raw_m3u_entry())
+            .collect();
+        let content = [header, content.join("\n")].join("\n");
+        match self.m3u_write_options.crlf {
+            true => content.replace('\n', "\r\n"),
+            false => content,
+        }
+    }
+
+    /// Returns the current stream information.
+    pub fn streams(&self) -> &[Info] {
+        &self.streams_info
+    }
+
+    /// Returns the current stream information for changing it in place, e.g. to edit, add or
+    /// remove entries.
+    ///
+    /// The lookups such as `get_by_url` see the changes.
+    ///
+    pub fn streams_mut(&mut self) -> &mut Vec<Info> {
+        self.save_backup();
+        &mut self.streams_info
+    }
+
+    /// Replaces the current stream information, e.g. with entries edited outside of the parser.
+    ///
+    /// # Arguments
+    ///
+    /// * `streams` - The new stream information.
+    ///
+    pub fn set_streams(&mut self, streams: Vec<Info>) {
+        self.save_backup();
+        self.streams_info = streams;
+    }
+
+    /// Resets the operations of the M3uParser by restoring the backup of stream information.
+    ///
+    /// This function restores the original state of the M3uParser by replacing the current
+    /// stream information with the backup. This can be useful when you want to undo any
+    /// modifications or filtering operations applied to the stream information.
+    ///
+    /// The backup is taken by the first operation changing the stream information after parsing,
+    /// so unchanged stream information is never held twice in memory. These operations are:
+    ///
+    /// * the filters, e.g. `filter_by`, `filter_by_tag`, `retrieve_by_country` or `take_range`,
+    ///   and `truncate`,
+    /// * `sort_by`, `sort_by_keys`, `sort_by_original_order` and the shuffling of
+    ///   `get_random_stream`,
+    /// * `tag_where`, `untag_where`, `auto_categorize`, `apply_rules` and `apply_blocklist`,
+    /// * `merge_channels`, `normalize_urls`, `prune_stale` and `sync_from`,
+    /// * `refresh_urls`, `resolve_hls_variant` and `promote_first_good_url`,
+    /// * `restore_snapshot`, `streams_mut` and `set_streams`.
+    ///
+    /// Changes made directly to `streams_info` before the first of them are therefore kept.
+    ///
+    pub fn reset_operations(&mut self) {
+        if let Some(backup) = self.streams_info_backup.take() {
+            self.streams_info = backup;
+            self.index = OnceLock::new();
+        }
+    }
+
+    /// Finds the clusters of duplicate streams without removing them, so they can be presented for
+    /// manual resolution.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A `DedupeKey` telling what makes two streams duplicates.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<Vec<&Info>>` with one cluster per duplicated key, ordered by first occurrence, each
+    /// holding the streams in their playlist order. Streams without duplicate are left out.
+    ///
+    pub fn find_duplicates(&self, key: DedupeKey) -> Vec<Vec<&Info>> {
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let mut clusters: Vec<Vec<&Info>> = vec![];
+        for info in &self.streams_info {
+            let value = key.value(info);
+            if value.is_empty() {
+                continue;
+            }
+            match positions.get(&value) {
+                Some(&position) => clusters[position].push(info),
+                None => {
+                    positions.insert(value, clusters.len());
+                    clusters.push(vec![info]);
+                }
+            }
+        }
+        clusters.retain(|cluster| cluster.len() > 1);
+        clusters
+    }
+
+    /// Rewrites the stream URLs in a canonical form, so that equivalent URLs written differently
+    /// are caught when removing duplicates by URL.
+    ///
+    /// Schemes and hosts are lowercased, default ports removed, `.` and `..` path segments
+    /// resolved and needlessly percent-encoded characters decoded. Entries whose URL is not a
+    /// valid URL, such as local paths, are left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `sort_query` - Whether to also sort the query parameters by name.
+    ///
+    /// # Returns
+    ///
+    /// The number of URLs changed.
+    ///
+    pub fn normalize_urls(&mut self, sort_query: bool) -> usize {
+        let urls: Vec<Option<String>> = self
+            .streams_info
+            .iter()
+            .map(|info| {
+                canonical::canonicalize(&info.url, sort_query).filter(|url| *url != info.url)
+            })
+            .collect();
+        let count = urls.iter().flatten().count();
+        if count > 0 {
+            self.save_backup();
+            for (info, url) in self.streams_info.iter_mut().zip(urls) {
+                if let Some(url) = url {
+                    info.url = url;
+                }
+            }
+        }
+        count
+    }
+
+    /// Merges the entries of the same channel, e.g. from several sources, into a single entry
+    /// whose backup URLs hold the alternate URLs.
+    ///
+    /// The URL of the merged entry is the best one: a "GOOD" stream first, then the one with the
+    /// lowest check latency, then the first in playlist order. The merged entry takes the place of
+    /// the first entry of the channel, with its logo taken from the other entries if missing and
+    /// the tags of all of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A `MergeKey` telling what makes two entries the same channel.
+    ///
+    /// # Returns
+    ///
+    /// The number of entries merged into another one.
+    ///
+    pub fn merge_channels(&mut self, key: MergeKey) -> usize {
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let mut channels: Vec<Vec<&Info>> = vec![];
+        for info in &self.streams_info {
+            let value = key.value(info);
+            if value.is_empty() {
+                channels.push(vec![info]);
+                continue;
+            }
+            match positions.get(&value) {
+                Some(&position) => channels[position].push(info),
+                None => {
+                    positions.insert(value, channels.len());
+                    channels.push(vec![info]);
+                }
+            }
+        }
+        let merged_count = self.streams_info.len() - channels.len();
+        if merged_count == 0 {
+            return 0;
+        }
+
+        let rank = |info: &Info| {
+            let latency = info.check.as_ref().and_then(|check| check.latency_ms);
+            (info.status != "GOOD", latency.unwrap_or(u64::MAX))
+        };
+        let streams_info: Vec<Info> = channels
+            .into_iter()
+            .map(|mut entries| {
+                entries.sort_by_key(|info| rank(info));
+                let mut merged = entries[0].clone();
+                let alternates = entries
+                    .iter()
+                    .flat_map(|info| std::iter::once(&info.url).chain(&info.backup_urls));
+                let mut backup_urls: Vec<String> = vec![];
+                for url in alternates {
+                    if *url != merged.url && !backup_urls.contains(url) {
+                        backup_urls.push(url.clone());
+                    }
+                }
+                merged.backup_urls = backup_urls;
+                for info in &entries[1..] {
+                    if merged.logo.is_empty() {
+                        merged.logo = info.logo.clone();
+                    }
+                    merged.tags.extend(info.tags.iter().cloned());
+                }
+                merged.source_index = entries.iter().map(|info| info.source_index).min().unwrap();
+                merged
+            })
+            .collect();
+        self.save_backup();
+        self.streams_info = streams_info;
+        merged_count
+    }
+
+    /// Groups the streams by the time zone of their country, e.g. to pick the feeds airing at
+    /// sensible local times.
+    ///
+    /// # Returns
+    ///
+    /// A `BTreeMap<&str, Vec<&Info>>` from each IANA time zone, e.g. "Europe/London", to its
+    /// streams in their playlist order. Streams without time zone are left out.
+    ///
+    pub fn group_by_timezone(&self) -> BTreeMap<&str, Vec<&Info>> {
+        let mut groups: BTreeMap<&str, Vec<&Info>> = BTreeMap::new();
+        for info in &self.streams_info {
+            if let Some(timezone) = &info.timezone {
+                groups.entry(timezone).or_default().push(info);
+            }
+        }
+        groups
+    }
+
+    /// Adds keywords assigning a category in `auto_categorize`, e.g. "discovery" for
+    /// "Documentary". Keywords match whole words of the titles, ignoring case and accents, and are
+    /// tried before the built-in ones in the order added.
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - The category assigned to the streams matching a keyword.
+    /// * `keywords` - The keywords of the category.
+    ///
+    pub fn add_category_keywords(&mut self, category: &str, keywords: &[&str]) {
+        let keywords = keywords
+            .iter()
+            .map(|keyword| search::normalize(keyword.trim()))
+            .filter(|keyword| !keyword.is_empty());
+        match self
+            .category_keywords
+            .iter_mut()
+            .find(|(name, _)| name == category)
+        {
+            Some((_, existing)) => existing.extend(keywords),
+            None => self
+                .category_keywords
+                .push((category.to_string(), keywords.collect())),
+        }
+    }
+
+    /// Assigns a category to the streams without one, guessed from the keywords in their title or
+    /// `tvg-name`.
+    ///
+    /// Many playlists have no `group-title`, which leaves players with a single "Uncategorized"
+    /// group. The built-in keywords cover news, sports, kids, music and movies in several
+    /// languages, and can be extended with `add_category_keywords`. Streams matching no keyword
+    /// are left uncategorized.
+    ///
+    /// # Returns
+    ///
+    /// The number of streams that were categorized.
+    ///
+    pub fn auto_categorize(&mut self) -> usize {
+        let categories: Vec<Option<String>> = self
+            .streams_info
+            .iter()
+            .map(|info| match info.category.is_empty() {
+                true => categorize::categorize(info, &self.category_keywords),
+                false => None,
+            })
+            .collect();
+        let count = categories.iter().flatten().count();
+        if count > 0 {
+            self.save_backup();
+            for (info, category) in self.streams_info.iter_mut().zip(categories) {
+                if let Some(category) = category {
+                    info.categories = vec![category.clone()];
+                    info.category = category;
+                }
+            }
+        }
+        count
+    }
+
+    /// Adds a tag to the streams matching the predicate.
+    ///
+    /// Tags label streams beyond the fixed fields, e.g. as favorites, blocked or kids-safe. They
+    /// are exported in JSON and, in M3U, in the `x-tags` attribute, from which they are parsed
+    /// back.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - A function returning `true` for the streams to tag.
+    /// * `tag` - The tag to add.
+    ///
+    /// # Returns
+    ///
+    /// The number of streams that did not have the tag and were tagged.
+    ///
+    pub fn tag_where<F: Fn(&Info) -> bool>(&mut self, predicate: F, tag: &str) -> usize {
+        let untagged = |info: &Info| !info.tags.contains(tag) && predicate(info);
+        let count = self
+            .streams_info
+            .iter()
+            .filter(|info| untagged(info))
+            .count();
+        if count > 0 {
+            self.save_backup();
+            for info in self.streams_info.iter_mut() {
+                if untagged(info) {
+                    info.tags.insert(tag.to_string());
+                }
+            }
+        }
+        count
+    }
+
+    /// Removes a tag from the streams matching the predicate.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - A function returning `true` for the streams to untag.
+    /// * `tag` - The tag to remove.
+    ///
+    /// # Returns
+    ///
+    /// The number of streams that had the tag and were untagged.
+    ///
+    pub fn untag_where<F: Fn(&Info) -> bool>(&mut self, predicate: F, tag: &str) -> usize {
+        let tagged = |info: &Info| info.tags.contains(tag) && predicate(info);
+        let count = self.streams_info.iter().filter(|info| tagged(info)).count();
+        if count > 0 {
+            self.save_backup();
+            for info in self.streams_info.iter_mut() {
+                if tagged(info) {
+                    info.tags.remove(tag);
+                }
+            }
+        }
+        count
+    }
+
+    /// Marks the streams matching the predicate as favorites, by tagging them "favorite".
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - A function returning `true` for the favorite streams.
+    ///
+    /// # Returns
+    ///
+    /// The number of streams newly marked as favorites.
+    ///
+    pub fn mark_favorite<F: Fn(&Info) -> bool>(&mut self, predicate: F) -> usize {
+        self.tag_where(predicate, FAVORITE_TAG)
+    }
+
+    /// Keeps only the streams having the given tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag the streams must have, e.g. "favorite".
+    ///
+    pub fn filter_by_tag(&mut self, tag: &str) {
+        self.save_backup();
+        self.streams_info.retain(|info| info.tags.contains(tag));
+    }
+
+    /// Saves a copy of the current stream information under the given name.
+    ///
+    /// Unlike the single backup restored by `reset_operations`, any number of named snapshots can
+    /// be kept, e.g. after parsing, after removing duplicates and after filtering, to come back to
+    /// any of these points with `restore_snapshot`. Saving a snapshot under an existing name
+    /// replaces it. Snapshots are kept when a new playlist is parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the snapshot.
+    ///
+    pub fn save_snapshot(&mut self, name: &str) {
+        let streams_info = self.streams_info.clone();
+        match self.snapshots.iter_mut().find(|(saved, _)| saved == name) {
+            Some((_, snapshot)) => *snapshot = streams_info,
+            None => self.snapshots.push((name.to_string(), streams_info)),
+        }
+    }
+
+    /// Replaces the current stream information with a copy of the named snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the snapshot, as given to `save_snapshot`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the snapshot exists and was restored, `false` otherwise.
+    ///
+    pub fn restore_snapshot(&mut self, name: &str) -> bool {
+        let Some((_, snapshot)) = self.snapshots.iter().find(|(saved, _)| saved == name) else {
+            return false;
+        };
+        let streams_info = snapshot.clone();
+        self.save_backup();
+        self.streams_info = streams_info;
+        true
+    }
+
+    /// Removes the named snapshot.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the snapshot existed.
+    ///
+    pub fn remove_snapshot(&mut self, name: &str) -> bool {
+        let count = self.snapshots.len();
+        self.snapshots.retain(|(saved, _)| saved != name);
+        self.snapshots.len() != count
+    }
+
+    /// Returns the names of the saved snapshots, in the order they were first saved.
+    pub fn list_snapshots(&self) -> Vec<&str> {
+        self.snapshots
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Keeps a copy of the stream information, unless one was already taken since parsing.
+    ///
+    /// Every operation changing the stream information calls this first, which also drops the
+    /// index used by `get_by_tvg_id` and `get_by_url`.
+    fn save_backup(&mut self) {
+        self.index = OnceLock::new();
+        if self.streams_info_backup.is_none() {
+            self.streams_info_backup = Some(self.streams_info.clone());
+        }
+    }
+
+    fn get_info_key(&self, key: &str, key_splitter: &str, nested_key: bool) -> Option<InfoKey> {
+        let (key_0, key_1) = if nested_key {
+            match key.split(key_splitter).collect::<Vec<&str>>()[..] {
+                [key0, key1] => (key0, key1),
+                _ => {
+                    eprintln!("Nested key must be in the format <key><key_splitter><nested_key>");
+                    return None;
+                }
+            }
+        } else {
+            (key, "")
+        };
+
+        let info_key = InfoKey::from_keys(key_0, key_1);
+        if info_key.is_none() {
+            eprintln!("{} key is not present.", key);
+        }
+        info_key
+    }
+
+    fn compile_filters(&self, filters: &[&str]) -> Vec<Regex> {
+        filters
+            .iter()
+            .map(|filter| Regex::new(filter).unwrap())
+            .collect()
+    }
+
+    /// Filters the stream information based on the specified key and filters.
+    ///
+    /// This function applies filtering operations to the stream information based on the provided key
+    /// and filters. The key represents the attribute of the stream information that will be filtered,
+    /// and the filters specify the conditions that the attribute should match. The function allows
+    /// filtering based on nested keys and provides options to retrieve or exclude the matching
+    /// stream information.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The attribute key to filter by. Valid values are: "title", "logo", "url", "category",
+    ///   "tvg", "country", "language", "status", and "source".
+    /// * `filters` - A vector of filter strings. The stream information will be filtered based on
+    ///   these conditions.
+    /// * `key_splitter` - The delimiter used to split the key for nested filtering. Set it to an empty
+    ///   string (`""`) if nested filtering is not required.
+    /// * `retrieve` - A boolean value indicating whether to retrieve the matching stream information
+    ///   (`true`) or exclude it from the result (`false`).
+    /// * `nested_key` - A boolean value indicating whether the key represents a nested key. If `true`,
+    ///   the key will be split using the `key_splitter`, and filtering will be applied to the nested
+    ///   key. If `false`, the key will be treated as a single key for filtering.
+    ///
+    /// # Panics
+    ///
+    /// The function will panic in the following scenarios:
+    ///
+    /// * If the nested key is provided but not in the format `<key><key_splitter><nested_key>`.
+    /// * If the provided key is not one of the valid keys ("title", "logo", "url", "category",
+    ///   "tvg", "country", "language", "status", "source").
+    ///
+    pub fn filter_by(
+        &mut self,
+        key: &str,
+        filters: Vec<&str>,
+        key_splitter: &str,
+        retrieve: bool,
+        nested_key: bool,
+    ) {
+        let info_key = match self.get_info_key(key, key_splitter, nested_key) {
+            Some(info_key) => info_key,
+            None => return,
+        };
+
+        if filters.is_empty() {
+            eprintln!("Filter word/s missing!!!");
+            return;
+        }
+
+        let re_filters = self.compile_filters(&filters);
+
+        self.save_backup();
+        self.streams_info.retain(|stream_info| {
+            if retrieve {
+                re_filters
+                    .iter()
+                    .any(|filter| info_key.is_match(stream_info, filter))
+            } else {
+                re_filters
+                    .iter()
+                    .all(|filter| !info_key.is_match(stream_info, filter))
+            }
+        });
+    }
+
+    /// Filters the stream information based on several keys at once.
+    ///
+    /// Each `(key, filters)` pair behaves like a retrieving `filter_by` call: a stream matches the
+    /// pair if its attribute matches any of the filters. A stream is kept only if it matches every
+    /// pair, so `[(InfoKey::Category, vec!["Sports"]), (InfoKey::CountryCode, vec!["GB"])]` keeps
+    /// the British sports channels in a single pass.
+    ///
+    /// # Arguments
+    ///
+    /// * `filters` - A slice of `(InfoKey, Vec<&str>)` pairs. The filter strings are regular
+    ///   expressions matched against the value of the corresponding key.
+    ///
+    /// # Panics
+    ///
+    /// The function will panic if any of the filter strings is not a valid regular expression.
+    ///
+    pub fn filter_by_many(&mut self, filters: &[(InfoKey, Vec<&str>)]) {
+        if filters.is_empty() || filters.iter().any(|(_, values)| values.is_empty()) {
+            eprintln!("Filter word/s missing!!!");
+            return;
+        }
+
+        let re_filters: Vec<(InfoKey, Vec<Regex>)> = filters
+            .iter()
+            .map(|(info_key, values)| (*info_key, self.compile_filters(values)))
+            .collect();
+
+        self.save_backup();
+        self.streams_info.retain(|stream_info| {
+            re_filters.iter().all(|(info_key, regexes)| {
+                regexes
+                    .iter()
+                    .any(|filter| info_key.is_match(stream_info, filter))
+            })
+        });
+    }
+
+    /// Keeps at most `n` streams per value of the key, e.g. the first 20 channels of each country.
+    ///
+    /// Streams whose status is "GOOD" are kept first, then streams in their current order. The
+    /// kept streams stay in their current order. Streams without a value for the key, e.g. without
+    /// `tvg-country`, are all kept. The value is compared as a whole, so a stream of several
+    /// countries such as "uk;IN" is in its own group.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The attribute the streams are grouped by, usually `InfoKey::Category` or
+    ///   `InfoKey::CountryCode`.
+    /// * `n` - The maximum number of streams kept in each group.
+    ///
+    pub fn limit_per_group(&mut self, key: InfoKey, n: usize) {
+        self.limit_per_group_by(key, n, |a, b| {
+            (b.status == "GOOD").cmp(&(a.status == "GOOD"))
+        })
+    }
+
+    /// Keeps at most `n` streams per value of the key, preferring the streams that sort first.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The attribute the streams are grouped by.
+    /// * `n` - The maximum number of streams kept in each group.
+    /// * `compare` - The comparator ranking the streams of a group; the `n` smallest are kept and
+    ///   ties are broken by the current order. The kept streams stay in their current order, and
+    ///   the streams without a value for the key are all kept, as with `limit_per_group`.
+    ///
+    pub fn limit_per_group_by<F>(&mut self, key: InfoKey, n: usize, mut compare: F)
+    where
+        F: FnMut(&Info, &Info) -> Ordering,
+    {
+        let mut groups: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut kept = vec![false; self.streams_info.len()];
+        for (index, info) in self.streams_info.iter().enumerate() {
+            match key.value(info) {
+                "" => kept[index] = true,
+                value => groups.entry(value).or_default().push(index),
+            }
+        }
+        for mut indices in groups.into_values() {
+            indices.sort_by(|&a, &b| compare(&self.streams_info[a], &self.streams_info[b]));
+            for index in indices.into_iter().take(n) {
+                kept[index] = true;
+            }
+        }
+        if kept.iter().all(|kept| *kept) {
+            return;
+        }
+
+        self.save_backup();
+        let mut kept = kept.into_iter();
+        self.streams_info
+            .retain(|_| kept.next().unwrap_or_default());
+    }
+
+    /// Overrides a field of a stream, now and after every following parse.
+    ///
+    /// Overrides are stored apart from the stream information and applied to every parsed
+    /// playlist, so renaming a channel or fixing its logo is not lost when the provider playlist
+    /// is downloaded again. Use `save_overrides` and `load_overrides` to keep them between runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The URL or the `tvg-id` of the streams to override.
+    /// * `field` - The name of the field, e.g. "title", "logo", "category" or "tvg-id".
+    /// * `value` - The value of the field.
+    ///
+    pub fn set_override(&mut self, target: &str, field: &str, value: &str) {
+        if InfoKey::from_name(field).is_none() {
+            eprintln!("{} key is not present.", field);
+            return;
+        }
+        self.overrides
+            .entry(target.to_string())
+            .or_default()
+            .insert(field.to_string(), value.to_string());
+        self.apply_overrides();
+    }
+
+    /// Removes an override set with `set_override`.
+    ///
+    /// The stream information keeps the overridden value until the playlist is parsed again.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The URL or the `tvg-id` of the override.
+    /// * `field` - The name of the field of the override.
+    ///
+    pub fn remove_override(&mut self, target: &str, field: &str) {
+        if let Some(fields) = self.overrides.get_mut(target) {
+            fields.remove(field);
+            if fields.is_empty() {
+                self.overrides.remove(target);
+            }
+        }
+    }
+
+    /// Returns the overrides, by URL or `tvg-id` and then by field name.
+    pub fn overrides(&self) -> &BTreeMap<String, BTreeMap<String, String>> {
+        &self.overrides
+    }
+
+    /// Saves the overrides to a JSON file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file. If the file already exists, it will be overwritten.
+    ///
+    pub fn save_overrides(&self, path: &str) -> std::io::Result<()> {
+        let content =
+            serde_json::to_string_pretty(&self.overrides).map_err(std::io::Error::from)?;
+        File::create(path)?.write_all(content.as_bytes())
+    }
+
+    /// Loads the overrides from a JSON file saved with `save_overrides`, replacing the current
+    /// ones, and applies them to the current stream information.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` which is an error if the file cannot be read or is invalid.
+    ///
+    pub fn load_overrides(&mut self, path: &str) -> std::io::Result<()> {
+        let overrides: BTreeMap<String, BTreeMap<String, String>> =
+            serde_json::from_str(&read_to_string(path)?).map_err(std::io::Error::from)?;
+        self.overrides = overrides;
+        self.apply_overrides();
+        Ok(())
+    }
+
+    /// Applies the overrides to the current stream information and its backup.
+    fn apply_overrides(&mut self) {
+        let mut streams_info = std::mem::take(&mut self.streams_info);
+        self.apply_overrides_to(&mut streams_info);
+        self.streams_info = streams_info;
+        if let Some(mut backup) = self.streams_info_backup.take() {
+            self.apply_overrides_to(&mut backup);
+            self.streams_info_backup = Some(backup);
+        }
+        self.index = OnceLock::new();
+    }
+
+    fn apply_overrides_to(&self, streams_info: &mut [Info]) {
+        if self.overrides.is_empty() {
+            return;
+        }
+        for info in streams_info {
+            let by_url = self.overrides.get(&info.url);
+            let by_tvg_id = match info.tvg.id.as_str() {
+                "" => None,
+                id => self.overrides.get(id),
+            };
+            // Overrides by URL are more specific, so they are applied last.
+            for fields in by_tvg_id.into_iter().chain(by_url) {
+                for (field, value) in fields {
+                    if let Some(key) = InfoKey::from_name(field) {
+                        key.set_value(info, value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Updates the stream information from a freshly parsed provider playlist.
+    ///
+    /// Streams are matched by `tvg-id`, or else by URL. Streams that were not edited since parsing
+    /// are replaced by their provider version. For edited streams, the policy decides whether the
+    /// local title, logo, categories and other descriptive fields are kept, in which case only the
+    /// URL and status are updated, and every field that differs from the provider is reported as a
+    /// conflict. Provider streams without a match are appended; local streams without a match are
+    /// kept and reported as missing.
+    ///
+    /// Matched streams keep their `first_seen` time and take the `last_seen` time of the provider,
+    /// so missing streams can be expired once their `last_seen` time is too old.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The parser holding the fresh provider playlist.
+    /// * `policy` - How edited streams are updated.
+    ///
+    /// # Returns
+    ///
+    /// A `SyncReport` with the number of updated and added streams, the missing streams and the
+    /// conflicts.
+    ///
+    pub fn sync_from(&mut self, provider: &M3uParser, policy: SyncPolicy) -> SyncReport {
+        self.save_backup();
+        sync::sync(&mut self.streams_info, &provider.streams_info, policy)
+    }
+
+    /// Compares the stream information with a newer version of the playlist, e.g. to report the
+    /// nightly changes of a provider playlist.
+    ///
+    /// Streams are matched by `tvg-id`, or else by URL. Matched streams whose URL or descriptive
+    /// fields differ are reported as changed. Neither parser is modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `new` - The parser holding the newer version of the playlist.
+    ///
+    /// # Returns
+    ///
+    /// A `PlaylistDiff` with the added, removed and changed streams, which can be exported with
+    /// `to_json`, `to_markdown` or `to_m3u`.
+    ///
+    pub fn diff(&self, new: &M3uParser) -> PlaylistDiff {
+        diff::diff(&self.streams_info, &new.streams_info)
+    }
+
+    /// Removes the streams that were not seen in any source within the given time window.
+    ///
+    /// A stream is seen when it is parsed or updated by `sync_from`, which sets its `last_seen`
+    /// time. Streams without a `last_seen` time, e.g. added by hand, are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age` - How long ago a stream must have been last seen to be kept.
+    ///
+    /// # Returns
+    ///
+    /// The number of removed streams.
+    ///
+    pub fn prune_stale(&mut self, max_age: Duration) -> usize {
+        let oldest = cache::unix_now().saturating_sub(max_age.as_secs());
+        let is_stale = |info: &Info| info.last_seen.is_some_and(|seen| seen < oldest);
+        let stale = self
+            .streams_info
+            .iter()
+            .filter(|info| is_stale(info))
+            .count();
+        if stale > 0 {
+            self.save_backup();
+            self.streams_info.retain(|info| !is_stale(info));
+        }
+        stale
+    }
+
+    /// Applies the rules to the current stream information, in order.
+    ///
+    /// Each rule whose conditions match a stream sets the fields of the stream or removes it, so
+    /// mappings maintained by hand, e.g. "titles starting with ESPN are Sports channels with the
+    /// tvg-id ESPN.us", can be re-applied after every playlist update. When several rules match a
+    /// stream, the later rules override the earlier ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - The rules, usually loaded with `Rules::load`.
+    ///
+    /// # Returns
+    ///
+    /// The number of streams that were changed or removed, or 0 if the rules are invalid.
+    ///
+    pub fn apply_rules(&mut self, rules: &Rules) -> usize {
+        let compiled = match rules.compile() {
+            Ok(compiled) => compiled,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return 0;
+            }
+        };
+
+        let mut streams_info = self.streams_info.clone();
+        let mut affected = 0;
+        streams_info.retain_mut(|info| {
+            let mut changed = false;
+            for rule in &compiled {
+                if !rule.matches(info) {
+                    continue;
+                }
+                if rule.removes() {
+                    affected += 1;
+                    return false;
+                }
+                changed |= rule.apply(info);
+            }
+            affected += changed as usize;
+            true
+        });
+        if affected > 0 {
+            self.save_backup();
+            self.streams_info = streams_info;
+        }
+        affected
+    }
+
+    /// Removes or tags the streams blocked by the blocklist, e.g. adult channels.
+    ///
+    /// A stream is blocked if its title, `tvg-name` or category contains one of the keywords, if
+    /// it is in one of the categories or if one of the patterns matches it. Blocked streams are
+    /// removed, or tagged if the blocklist has a tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocklist` - The blocklist, usually loaded with `Blocklist::load`.
+    ///
+    /// # Returns
+    ///
+    /// The number of blocked streams, or 0 if the blocklist is invalid.
+    ///
+    pub fn apply_blocklist(&mut self, blocklist: &Blocklist) -> usize {
+        let compiled = match blocklist.compile() {
+            Ok(compiled) => compiled,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return 0;
+            }
+        };
+
+        match &blocklist.tag {
+            Some(tag) => self.tag_where(|info| compiled.blocks(info), tag),
+            None => {
+                let count = self.streams_info.len();
+                let streams_info: Vec<Info> = self
+                    .streams_info
+                    .iter()
+                    .filter(|info| !compiled.blocks(info))
+                    .cloned()
+                    .collect();
+                let blocked = count - streams_info.len();
+                if blocked > 0 {
+                    self.save_backup();
+                    self.streams_info = streams_info;
+                }
+                blocked
+            }
+        }
+    }
+
+    /// Applies the settings of a configuration: the guards of the outputs, the timeouts and the
+    /// `User-Agent` header.
+    ///
+    /// Settings that are not set in the configuration keep their current values, as do timeouts
+    /// of 0 seconds, which are reported. The check settings require the `net` feature and are
+    /// ignored without it.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration, usually loaded with `ParserConfig::load`.
+    ///
+    pub fn apply_config(&mut self, config: &ParserConfig) {
+        if let Some(min_entries) = config.min_entries {
+            self.set_min_entries(min_entries);
+        }
+        if config.backup_retention.is_some() {
+            self.set_backup_retention(config.backup_retention);
+        }
+        #[cfg(feature = "net")]
+        {
+            let timeout = |name: &str, secs: Option<u64>| match secs {
+                Some(0) => {
+                    eprintln!("Invalid {}: 0", name);
+                    None
+                }
+                secs => secs.map(Duration::from_secs),
+            };
+            if let Some(timeout) = timeout("check.timeout_secs", config.check.timeout_secs) {
+                self.set_check_timeout(timeout);
+            }
+            if let Some(timeout) =
+                timeout("check.fetch_timeout_secs", config.check.fetch_timeout_secs)
+            {
+                self.set_fetch_timeout(timeout);
+            }
+            if let Some(user_agent) = &config.check.user_agent {
+                self.set_user_agent(user_agent);
+            }
+        }
+    }
+
+    /// Applies the settings given by environment variables, e.g. to tune a container deployment
+    /// without changing its code or its configuration file.
+    ///
+    /// The variables override the settings set before, so this is called last, e.g. after the
+    /// settings of a configuration file in `run_config`. Invalid values are reported and ignored. The variables are:
+    ///
+    /// * `M3U_PARSER_TIMEOUT` - The timeout of each stream check, in seconds.
+    /// * `M3U_PARSER_FETCH_TIMEOUT` - The timeout of downloading a playlist, in seconds.
+    /// * `M3U_PARSER_USER_AGENT` - The `User-Agent` header of the stream checks.
+    /// * `M3U_PARSER_PROXY` - The URL of the HTTP or HTTPS proxy of all requests, see `set_proxy`.
+    ///
+    /// They all concern HTTP requests, so this does nothing without the `net` feature.
+    ///
+    pub fn apply_env(&mut self) {
+        self.apply_env_from(|name| std::env::var(name).ok());
+    }
+
+    #[cfg_attr(not(feature = "net"), allow(unused_variables, unused_mut))]
+    fn apply_env_from(&mut self, mut var: impl FnMut(&str) -> Option<String>) {
+        #[cfg(feature = "net")]
+        {
+            let mut timeout = |name: &str| {
+                let value = var(name)?;
+                let timeout = value
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|secs| *secs > 0.0)
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+                if timeout.is_none() {
+                    eprintln!("Invalid {}: {}", name, value);
+                }
+                timeout
+            };
+            if let Some(timeout) = timeout("M3U_PARSER_TIMEOUT") {
+                self.set_check_timeout(timeout);
+            }
+            if let Some(timeout) = timeout("M3U_PARSER_FETCH_TIMEOUT") {
+                self.set_fetch_timeout(timeout);
+            }
+            if let Some(user_agent) = var("M3U_PARSER_USER_AGENT") {
+                self.set_user_agent(&user_agent);
+            }
+            if let Some(proxy) = var("M3U_PARSER_PROXY") {
+                if let Err(e) = self.set_proxy(&proxy) {
+                    eprintln!("Invalid M3U_PARSER_PROXY: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Runs the pipeline of a configuration: applies its settings, parses and merges its sources,
+    /// applies its filters in order and writes its outputs.
+    ///
+    /// The settings of the environment variables, see `apply_env`, override the ones of the
+    /// configuration.
+    ///
+    /// Sources that cannot be read are reported and skipped, like with `fetch_all`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration, usually loaded with `ParserConfig::load`.
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` with the `MultiParseReport` of the sources, which is an error if an
+    /// output format is not recognised, if an output would be overwritten with fewer entries than
+    /// the minimum or if writing an output fails.
+    ///
+    pub async fn run_config(&mut self, config: &ParserConfig) -> std::io::Result<MultiParseReport> {
+        self.apply_config(config);
+        self.apply_env();
+        let sources: Vec<&str> = config.sources.iter().map(String::as_str).collect();
+        #[cfg(feature = "net")]
+        let report = self
+            .fetch_all(
+                &sources,
+                config.per_host,
+                config.check.live,
+                config.enforce_schema,
+            )
+            .await;
+        #[cfg(not(feature = "net"))]
+        let report = {
+            let mut contents = vec![];
+            for source in &sources {
+                contents.push(self.read_location(source).await);
+            }
+            self.parse_sources(&sources, contents, config.check.live, config.enforce_schema)
+                .await
+        };
+
+        for filter in &config.filters {
+            let values = filter.values.iter().map(String::as_str).collect();
+            let nested_key = filter.key.contains('-');
+            self.filter_by(&filter.key, values, "-", filter.retrieve, nested_key);
+        }
+
+        for output in &config.outputs {
+            // Rendered first, so that an unknown format does not truncate an existing file.
+            let mut content = vec![];
+            self.to_writer(&mut content, &output.format())?;
+            self.check_overwrite(&output.path, self.streams_info.len())?;
+            let mut file = self.create_output(&output.path)?;
+            file.write_all(&content)?;
+            file.flush()?;
+        }
+        Ok(report)
+    }
+
+    /// Keeps only the first `n` streams of the current stream information, e.g. to export a
+    /// preview playlist of the first 100 channels.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of streams to keep.
+    ///
+    pub fn truncate(&mut self, n: usize) {
+        self.take_range(0..n);
+    }
+
+    /// Removes the first `n` streams of the current stream information.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of streams to remove.
+    ///
+    pub fn skip(&mut self, n: usize) {
+        self.take_range(n..usize::MAX);
+    }
+
+    /// Keeps only the streams in the given range of positions of the current stream information.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The positions of the streams to keep, starting at 0. The parts of the range past
+    ///   the end of the stream information are ignored.
+    ///
+    pub fn take_range(&mut self, range: std::ops::Range<usize>) {
+        let len = self.streams_info.len();
+        let end = range.end.min(len);
+        let start = range.start.min(end);
+        if start == 0 && end == len {
+            return;
+        }
+
+        self.save_backup();
+        self.streams_info.truncate(end);
+        self.streams_info.drain(..start);
+    }
+
+    /// Sorts the stream information based on the specified key and sorting options.
+    ///
+    /// This function sorts the stream information based on the provided key and sorting options. The key
+    /// represents the attribute of the stream information that will be used for sorting. The function
+    /// allows sorting based on nested keys and provides options to specify the sorting order.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The attribute key to sort by. Valid values are: "title", "logo", "url", "category",
+    ///   "tvg", "country", "language", "status", and "source".
+    /// * `key_splitter` - The delimiter used to split the key for nested sorting. Set it to an empty
+    ///   string (`""`) if nested sorting is not required.
+    /// * `asc` - A boolean value indicating the sorting order. If `true`, the stream information will be
+    ///   sorted in ascending order based on the specified key. If `false`, the stream information will
+    ///   be sorted in descending order.
+    /// * `nested_key` - A boolean value indicating whether the key represents a nested key. If `true`,
+    ///   the key will be split using the `key_splitter`, and sorting will be applied to the nested key.
+    ///   If `false`, the key will be treated as a single key for sorting.
+    ///
+    /// # Panics
+    ///
+    /// The function will panic in the following scenarios:
+    ///
+    /// * If the nested key is provided but not in the format `<key><key_splitter><nested_key>`.
+    /// * If the provided key is not one of the valid keys ("title", "logo", "url", "category",
+    ///   "tvg", "country", "language", "status", "source").
+    ///
+    pub fn sort_by(&mut self, key: &str, key_splitter: &str, asc: bool, nested_key: bool) {
+        let info_key = match self.get_info_key(key, key_splitter, nested_key) {
+            Some(info_key) => info_key,
+            None => return,
+        };
+
+        self.save_backup();
+        let mut streams_info = std::mem::take(&mut self.streams_info);
+        streams_info.sort_by(|a, b| {
+            let a_value = info_key.value(a);
+            let b_value = info_key.value(b);
+
+            if asc {
+                self.compare_values(a_value, b_value)
+            } else {
+                self.compare_values(b_value, a_value)
+            }
+        });
+        self.streams_info = streams_info;
+    }
+
+    /// Sorts the stream information based on several keys.
+    ///
+    /// The stream information is sorted by the first key, and ties are broken by the following keys
+    /// in order, e.g. `[(InfoKey::Category, SortOrder::Ascending), (InfoKey::Title, SortOrder::Ascending)]`
+    /// sorts by category and then by title within each category.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - A slice of `(InfoKey, SortOrder)` pairs, from the most to the least significant key.
+    ///
+    pub fn sort_by_keys(&mut self, keys: &[(InfoKey, SortOrder)]) {
+        self.save_backup();
+        let mut streams_info = std::mem::take(&mut self.streams_info);
+        streams_info.sort_by(|a, b| {
+            keys.iter()
+                .map(|(info_key, order)| {
+                    let ordering = self.compare_values(info_key.value(a), info_key.value(b));
+                    match order {
+                        SortOrder::Ascending => ordering,
+                        SortOrder::Descending => ordering.reverse(),
+                    }
+                })
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+        self.streams_info = streams_info;
+    }
+
+    /// Sorts the stream information back into the order of the source playlist.
+    ///
+    /// This function uses the `source_index` recorded for each stream at parse time, so the
+    /// original ordering can be restored after any number of sorts without discarding filters.
+    ///
+    pub fn sort_by_original_order(&mut self) {
+        self.save_backup();
+        self.streams_info
+            .sort_by_key(|stream_info| stream_info.source_index);
+    }
+
+    /// Enables or disables natural ordering for `sort_by` and `sort_by_keys`.
+    ///
+    /// With natural ordering, numbers inside the values are compared by their numeric value, so
+    /// "Channel 2" sorts before "Channel 10". It is disabled by default, in which case values are
+    /// compared lexicographically.
+    ///
+    /// # Arguments
+    ///
+    /// * `natural` - A boolean indicating whether to use natural ordering.
+    ///
+    pub fn set_natural_sort(&mut self, natural: bool) {
+        self.natural_sort = natural;
+        #[cfg(feature = "collation")]
+        if let Some(locale) = self.sort_locale.clone() {
+            self.collator = sort::new_collator(&locale, natural).ok();
+        }
+    }
+
+    /// Sets the locale used to collate values in `sort_by` and `sort_by_keys`.
+    ///
+    /// By default values are compared by their bytes, which puts accented letters and letters such
+    /// as "Ñ" after "Z". With a locale set, values are compared according to the collation rules of
+    /// that locale instead. Natural ordering, if enabled, is applied on top of the locale rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - A BCP 47 language tag such as "es", "tr" or "zh-Hans", or `None` to go back to
+    ///   byte order.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the locale is invalid or not supported.
+    ///
+    #[cfg(feature = "collation")]
+    pub fn set_sort_locale(&mut self, locale: Option<&str>) -> Result<(), Box<dyn Error>> {
+        self.collator = match locale {
+            Some(locale) => Some(sort::new_collator(locale, self.natural_sort)?),
+            None => None,
+        };
+        self.sort_locale = locale.map(|locale| locale.to_string());
+        Ok(())
+    }
+
+    /// Removes stream information based on the specified file extensions.
+    ///
+    /// This function removes stream information based on the file extensions specified in the `extensions`
+    /// parameter. It internally calls the `filter_by` function with the "url" attribute as the key and
+    /// filters the stream information that matches any of the provided extensions.
+    ///
+    /// # Arguments
+    ///
+    /// * `extensions` - A vector of file extensions to be removed. Each extension should be a string.
+    ///
+    pub fn remove_by_extension(&mut self, extensions: Vec<&str>) {
+        self.filter_by("url", extensions, "-", false, false)
+    }
+
+    /// Retrieves stream information based on the specified file extensions.
+    ///
+    /// This function retrieves stream information based on the file extensions specified in the `extensions`
+    /// parameter. It internally calls the `filter_by` function with the "url" attribute as the key and
+    /// filters the stream information that matches any of the provided extensions.
+    ///
+    /// # Arguments
+    ///
+    /// * `extensions` - A vector of file extensions to be retrieved. Each extension should be a string.
+    ///
+    pub fn retrieve_by_extension(&mut self, extensions: Vec<&str>) {
+        self.filter_by("url", extensions, "-", true, false)
+    }
+
+    /// Removes stream information based on the specified categories.
+    ///
+    /// This function removes stream information based on the categories specified in the `categories`
+    /// parameter. It internally calls the `filter_by` function with the "category" attribute as the key
+    /// and filters out the stream information that matches any of the provided categories.
+    ///
+    /// # Arguments
+    ///
+    /// * `categories` - A vector of categories to be removed. Each category should be a string.
+    ///
+    pub fn remove_by_category(&mut self, categories: Vec<&str>) {
+        self.filter_by("category", categories, "-", false, false)
+    }
+
+    /// Retrieves stream information based on the specified categories.
+    ///
+    /// This function retrieves stream information based on the categories specified in the `categories`
+    /// parameter. It internally calls the `filter_by` function with the "category" attribute as the key
+    /// and filters the stream information that matches any of the provided categories.
+    ///
+    /// # Arguments
+    ///
+    /// * `categories` - A vector of categories to be retrieved. Each category should be a string.
+    ///
+    pub fn retrieve_by_category(&mut self, categories: Vec<&str>) {
+        self.filter_by("category", categories, "-", true, false)
+    }
+
+    /// Removes stream information based on the specified country codes.
+    ///
+    /// This function removes stream information based on the country codes specified in the `codes`
+    /// parameter. It internally calls the `filter_by` function with the nested "country-code" key
+    /// and filters out the stream information that matches any of the provided codes.
+    ///
+    /// # Arguments
+    ///
+    /// * `codes` - A vector of country codes (e.g. "US", "GB") to be removed.
+    ///
+    pub fn remove_by_country(&mut self, codes: Vec<&str>) {
+        self.filter_by("country-code", codes, "-", false, true)
+    }
+
+    /// Retrieves stream information based on the specified country codes.
+    ///
+    /// This function retrieves stream information based on the country codes specified in the `codes`
+    /// parameter. It internally calls the `filter_by` function with the nested "country-code" key
+    /// and filters the stream information that matches any of the provided codes.
+    ///
+    /// # Arguments
+    ///
+    /// * `codes` - A vector of country codes (e.g. "US", "GB") to be retrieved.
+    ///
+    pub fn retrieve_by_country(&mut self, codes: Vec<&str>) {
+        self.filter_by("country-code", codes, "-", true, true)
+    }
+
+    /// Removes stream information based on the specified regions.
+    ///
+    /// A region is a continent (e.g. "Europe"), a UN M49 subregion (e.g. "Western Asia"), a
+    /// common group such as "Latin America", or an ISO 3166-2 subdivision code (e.g. "US-CA")
+    /// matching the `subdivision` attribute. Names are matched ignoring case.
+    ///
+    /// # Arguments
+    ///
+    /// * `regions` - A vector of regions to be removed.
+    ///
+    pub fn remove_by_region(&mut self, regions: Vec<&str>) {
+        self.filter_by_region(regions, false)
+    }
+
+    /// Retrieves stream information based on the specified regions.
+    ///
+    /// A region is a continent (e.g. "Europe"), a UN M49 subregion (e.g. "Western Asia"), a
+    /// common group such as "Latin America", or an ISO 3166-2 subdivision code (e.g. "US-CA")
+    /// matching the `subdivision` attribute. Names are matched ignoring case.
+    ///
+    /// # Arguments
+    ///
+    /// * `regions` - A vector of regions to be retrieved.
+    ///
+    pub fn retrieve_by_region(&mut self, regions: Vec<&str>) {
+        self.filter_by_region(regions, true)
+    }
+
+    fn filter_by_region(&mut self, regions: Vec<&str>, retrieve: bool) {
+        let regions: Vec<&str> = regions
+            .into_iter()
+            .filter(|region| {
+                let known = geo::is_region(region) || geo::is_subdivision(region.trim());
+                if !known {
+                    eprintln!("Unknown region: {}", region);
+                }
+                known
+            })
+            .collect();
+        if regions.is_empty() {
+            eprintln!("Filter word/s missing!!!");
+            return;
+        }
+        self.save_backup();
+        self.streams_info
+            .retain(|info| regions.iter().any(|region| geo::matches(info, region)) == retrieve);
+    }
+
+    /// Removes stream information based on the specified language codes.
+    ///
+    /// This function removes stream information based on the language codes specified in the `codes`
+    /// parameter. It internally calls the `filter_by` function with the nested "language-code" key
+    /// and filters out the stream information that matches any of the provided codes.
+    ///
+    /// # Arguments
+    ///
+    /// * `codes` - A vector of language codes (e.g. "EN", "FR") to be removed.
+    ///
+    pub fn remove_by_language(&mut self, codes: Vec<&str>) {
+        self.filter_by("language-code", codes, "-", false, true)
+    }
+
+    /// Retrieves stream information based on the specified language codes.
+    ///
+    /// This function retrieves stream information based on the language codes specified in the `codes`
+    /// parameter. It internally calls the `filter_by` function with the nested "language-code" key
+    /// and filters the stream information that matches any of the provided codes.
+    ///
+    /// # Arguments
+    ///
+    /// * `codes` - A vector of language codes (e.g. "EN", "FR") to be retrieved.
+    ///
+    pub fn retrieve_by_language(&mut self, codes: Vec<&str>) {
+        self.filter_by("language-code", codes, "-", true, true)
+    }
+
+    /// Retrieves only the streams whose status is "GOOD".
+    ///
+    /// The status is only meaningful if the playlist was parsed with `check_live` enabled; otherwise
+    /// only acestream links and local files are marked as "GOOD".
+    ///
+    pub fn retrieve_good_streams(&mut self) {
+        self.filter_by("status", vec!["^GOOD$"], "-", true, false)
+    }
+
+    /// Removes the streams whose status is "BAD".
+    ///
+    /// The status is only meaningful if the playlist was parsed with `check_live` enabled; otherwise
+    /// every stream that is not an acestream link or a local file is considered "BAD".
+    ///
+    pub fn remove_bad_streams(&mut self) {
+        self.filter_by("status", vec!["^BAD$"], "-", false, false)
+    }
+
+    /// Returns the total duration of the entries in seconds, e.g. the runtime of a music or VOD
+    /// playlist. Entries with an unknown duration (`-1`), such as live streams, count as 0.
+    pub fn total_duration(&self) -> f64 {
+        self.streams_info
+            .iter()
+            .filter_map(|info| info.duration)
+            .sum()
+    }
+
+    /// Retrieves only the entries whose duration in seconds is in the range, e.g. `1.0..` to drop
+    /// zero-length tracks. Entries with an unknown duration (`-1`), such as live streams, are
+    /// removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of durations to retrieve, e.g. `60.0..=600.0`.
+    ///
+    pub fn filter_by_duration(&mut self, range: impl RangeBounds<f64>) {
+        self.save_backup();
+        self.streams_info.retain(|info| {
+            info.duration
+                .is_some_and(|duration| range.contains(&duration))
+        });
+    }
+
+    /// Retrieves only the streams checked recently, telling the streams that are down from the
+    /// ones whose status is stale. Streams never checked are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The maximum age of the last check, e.g. one day.
+    ///
+    pub fn filter_by_checked_since(&mut self, duration: Duration) {
+        let since = cache::unix_now().saturating_sub(duration.as_secs());
+        self.save_backup();
+        self.streams_info.retain(|info| {
+            info.check
+                .as_ref()
+                .is_some_and(|check| check.checked_at >= since)
+        });
+    }
+
+    /// Probes each stream with ffprobe to capture its codecs, resolution, bitrate and audio tracks.
+    ///
+    /// A successful HTTP response does not tell whether a stream actually plays, so this function
+    /// runs `ffprobe`, which must be installed and on the `PATH`, against every stream and stores
+    /// the result in `Info.media`. Streams that cannot be probed get `None`. Acestream links are
+    /// skipped. Each probe is limited by the check timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `concurrency` - The maximum number of ffprobe processes running at the same time.
+    ///
+    #[cfg(feature = "ffprobe")]
+    pub async fn probe_streams(&mut self, concurrency: usize) {
+        use futures::StreamExt;
+
+        let locations: Vec<Option<String>> = self
+            .streams_info
+            .iter()
+            .map(|info| {
+                if STREAMS_REGEX.is_match(&info.url) {
+                    None
+                } else {
+                    Some(self.resolve_location(&info.url))
+                }
+            })
+            .collect();
+        let timeout = self.check_timeout;
+        let media: Vec<Option<MediaInfo>> = futures::stream::iter(locations)
+            .map(|location| async move {
+                match location {
+                    Some(location) => media::probe(&location, timeout).await,
+                    None => None,
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+        for (info, media) in self.streams_info.iter_mut().zip(media) {
+            info.media = media;
+        }
+    }
+
+    /// Measures the throughput of each "GOOD" stream by downloading its first seconds, and records
+    /// it in `Info.check.bitrate_kbps`, e.g. to keep the streams playable on mobile data.
+    ///
+    /// This is opt-in as it downloads a lot: HLS streams are sampled by downloading their segments
+    /// and the other URLs by reading the response body, until the duration is elapsed. Streams that
+    /// cannot be downloaded get no bitrate; local files and acestream links are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - How long to download each stream, e.g. 5 seconds.
+    /// * `concurrency` - The maximum number of streams downloaded at the same time.
+    ///
+    #[cfg(feature = "net")]
+    pub async fn measure_bitrate(&mut self, duration: Duration, concurrency: usize) {
+        use futures::StreamExt;
+
+        let client = match self.http_client(duration + self.check_timeout) {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Error: could not create the HTTP client: {}", e);
+                return;
+            }
+        };
+        let parser = &*self;
+        let client = &client;
+        let bitrates: Vec<Option<u64>> = futures::stream::iter(&parser.streams_info)
+            .map(|info| async move {
+                let location = parser.resolve_location(&info.url);
+                if info.status != "GOOD" || !parser.is_valid_url(&location) {
+                    return None;
+                }
+                let mut headers: Vec<(String, String)> = vec![];
+                if info.user_agent.is_none() {
+                    headers.push(("User-Agent".to_string(), parser.useragent.clone()));
+                }
+                headers.extend(
+                    info.http_headers()
+                        .into_iter()
+                        .map(|(name, value)| (name.to_string(), value.to_string())),
+                );
+                let sampler = bitrate::BitrateSampler {
+                    client,
+                    headers,
+                    duration,
+                };
+                sampler.sample(&location).await
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+        for (info, bitrate) in self.streams_info.iter_mut().zip(bitrates) {
+            if let Some(bitrate) = bitrate {
+                let check = info.check.get_or_insert_with(|| CheckResult {
+                    available: true,
+                    checked_at: cache::unix_now(),
+                    ..Default::default()
+                });
+                check.bitrate_kbps = Some(bitrate);
+            }
+        }
+    }
+
+    /// Returns aggregate counts over the current stream information.
+    ///
+    /// Besides the number of "GOOD" and "BAD" streams, the failed checks are counted by kind, e.g.
+    /// timeouts, DNS or TLS failures and HTTP errors, which tells a problem with the local network
+    /// from a problem of the provider.
+    ///
+    /// # Returns
+    ///
+    /// The `PlaylistStats` of the current stream information.
+    ///
+    pub fn stats(&self) -> PlaylistStats {
+        PlaylistStats::new(&self.streams_info)
+    }
+
+    /// Returns an immutable snapshot of the current stream information.
+    ///
+    /// The snapshot is cheap to clone and can be shared between threads, e.g. wrapped in an `Arc`
+    /// and handed to the request handlers of a server, while the parser keeps being used to
+    /// refresh the playlist.
+    ///
+    /// # Returns
+    ///
+    /// A `PlaylistSnapshot` holding a copy of the stream information and the parse warnings.
+    ///
+    pub fn snapshot(&self) -> PlaylistSnapshot {
+        PlaylistSnapshot::new(
+            self.streams_info.clone(),
+            self.warnings.clone(),
+            self.enforce_schema,
+        )
+    }
+
+    /// Turns the parser into an immutable snapshot of its stream information without copying it.
+    pub fn into_snapshot(self) -> PlaylistSnapshot {
+        PlaylistSnapshot::new(self.streams_info, self.warnings, self.enforce_schema)
+    }
+
+    /// Returns the stream information in a compact form.
+    ///
+    /// Repeated values such as categories, countries and languages are stored only once, which
+    /// considerably reduces the memory used by large playlists. The parser can be dropped afterwards
+    /// and the entries converted back with `InfoCompact::to_info` when needed.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<InfoCompact>` in the same order as the stream information.
+    ///
+    pub fn to_compact(&self) -> Vec<InfoCompact> {
+        let mut interner = Interner::new();
+        self.streams_info
+            .iter()
+            .map(|info| InfoCompact::from_info(info, &mut interner))
+            .collect()
+    }
+
+    /// Builds an index of the current streams by `tvg-id` and by URL.
+    ///
+    /// The index borrows the stream information, which makes joining the streams against an EPG
+    /// or a recording schedule linear instead of quadratic.
+    ///
+    /// # Returns
+    ///
+    /// A `StreamIndex` of the current stream information.
+    ///
+    pub fn build_index(&self) -> StreamIndex<'_> {
+        StreamIndex::new(&self.streams_info)
+    }
+
+    /// Returns the current stream with the given `tvg-id`, or the first one if several share it.
+    ///
+    /// The lookup uses an index built on the first call and rebuilt after the stream information
+    /// changes through the parser, e.g. by a filter. Changes made directly to `streams_info` are
+    /// not tracked; use `build_index` after such changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The `tvg-id` to look up.
+    ///
+    pub fn get_by_tvg_id(&self, id: &str) -> Option<&Info> {
+        let position = *self.position_index().by_tvg_id.get(id)?;
+        self.streams_info
+            .get(position)
+            .filter(|info| info.tvg.id == id)
+    }
+
+    /// Returns the current stream with the given URL, or the first one if several share it.
+    ///
+    /// The lookup uses the same index as `get_by_tvg_id`.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL to look up, as found in the playlist.
+    ///
+    pub fn get_by_url(&self, url: &str) -> Option<&Info> {
+        let position = *self.position_index().by_url.get(url)?;
+        self.streams_info
+            .get(position)
+            .filter(|info| info.url == url)
+    }
+
+    fn position_index(&self) -> &PositionIndex {
+        self.index
+            .get_or_init(|| PositionIndex::new(&self.streams_info))
+    }
+
+    /// Searches the current streams by title, `tvg-name` and category.
+    ///
+    /// The search ignores case and accents, so "tele" finds "Télé Monte-Carlo". Exact matches rank
+    /// first, then prefixes, word prefixes, substrings and finally fuzzy matches in which the
+    /// characters of the query appear in order, e.g. "bbcone" for "BBC One". Matches in the title
+    /// rank above matches in the `tvg-name` and the category.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The text to search for.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<(&Info, f64)>` with the matching streams and their score between 0 and 1, ordered
+    /// by decreasing score and then by their current order.
+    ///
+    pub fn search(&self, query: &str) -> Vec<(&Info, f64)> {
+        let query = search::normalize(query.trim());
+        if query.is_empty() {
+            return vec![];
+        }
+        let mut results: Vec<(&Info, f64)> = self
+            .streams_info
+            .iter()
+            .map(|info| (info, search::score(info, &query)))
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        results.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        results
+    }
+
+    /// Returns the warnings collected while parsing the playlist.
+    ///
+    /// Each warning points at a line of the source playlist that did not produce stream
+    /// information, such as an `#EXTINF` line without a URL or a URL without an `#EXTINF` line,
+    /// so that the source playlist can be fixed.
+    ///
+    /// # Returns
+    ///
+    /// A `&[ParseWarning]` ordered by line number.
+    ///
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Validates the stream information and reports the problems found.
+    ///
+    /// This function reports entries with missing titles, empty tvg-ids, duplicate URLs, malformed
+    /// logo URLs and unknown country codes, as well as the `#EXTINF` lines that the parser skipped
+    /// because no valid URL or path follows them. It works on the current stream information, so
+    /// filters applied beforehand are taken into account.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<ValidationIssue>` ordered by line number. An empty vector means no problem was found.
+    ///
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        validate::validate(&self.streams_info, &self.warnings)
+    }
+
+    /// Retrieves the stream information in JSON format.
+    ///
+    /// This function returns the stream information in JSON format. The JSON can be either
+    /// pretty-formatted or compact depending on the `preety` parameter. If the playlist was parsed
+    /// without enforcing the schema, attributes that were not found are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `pretty` - A boolean indicating whether to format the JSON output in a pretty, human-readable way.
+    ///
+    /// # Returns
+    ///
+    /// A `serde_json::Result<String>` representing the JSON output. If the serialization to JSON is successful,
+    /// the result will contain the JSON string. Otherwise, an error indicating the reason for the failure
+    /// will be returned.
+    ///
+    pub fn get_json(&self, preety: bool) -> serde_json::Result<String> {
+        self.get_json_with_options(&JsonOptions {
+            pretty: preety,
+            ..Default::default()
+        })
+    }
+
+    /// Retrieves the stream information in JSON format using the given serialization options.
+    ///
+    /// This function allows the JSON output to match a fixed contract, e.g. by dropping empty
+    /// fields, using camel case keys or flattening the nested `tvg`, `country` and `language`
+    /// objects. If the playlist was parsed without enforcing the schema, empty fields are always
+    /// dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - A `JsonOptions` struct describing how the stream information is serialized.
+    ///
+    /// # Returns
+    ///
+    /// A `serde_json::Result<String>` representing the JSON output.
+    ///
+    pub fn get_json_with_options(&self, options: &JsonOptions) -> serde_json::Result<String> {
+        self.render_json_with_options(&self.streams_info, options)
+    }
+
+    /// Renders the given stream information as an M3U playlist, using the write options of the
+    /// parser.
+    ///
+    /// Unlike `to_file`, this serializes an arbitrary subset of the streams, such as the results
+    /// of `search`, without filtering the parser first.
+    ///
+    /// # Arguments
+    ///
+    /// * `streams_info` - The stream information to render.
+    ///
+    /// # Returns
+    ///
+    /// A `String` with the M3U playlist, which is empty if there is no stream information.
+    ///
+    pub fn render_m3u(&self, streams_info: &[Info]) -> String {
+        write::render_m3u(streams_info, &self.m3u_write_options)
+    }
+
+    /// Renders the given stream information in JSON format, like `get_json` does for the streams
+    /// of the parser.
+    ///
+    /// # Arguments
+    ///
+    /// * `streams_info` - The stream information to render.
+    /// * `pretty` - A boolean indicating whether to format the JSON output in a pretty, human-readable way.
+    ///
+    /// # Returns
+    ///
+    /// A `serde_json::Result<String>` representing the JSON output.
+    ///
+    pub fn render_json(&self, streams_info: &[Info], pretty: bool) -> serde_json::Result<String> {
+        self.render_json_with_options(
+            streams_info,
+            &JsonOptions {
+                pretty,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Renders the given stream information in JSON format using the given serialization options,
+    /// like `get_json_with_options` does for the streams of the parser.
+    ///
+    /// # Arguments
+    ///
+    /// * `streams_info` - The stream information to render.
+    /// * `options` - A `JsonOptions` struct describing how the stream information is serialized.
+    ///
+    /// # Returns
+    ///
+    /// A `serde_json::Result<String>` representing the JSON output.
+    ///
+    pub fn render_json_with_options(
+        &self,
+        streams_info: &[Info],
+        options: &JsonOptions,
+    ) -> serde_json::Result<String> {
+        let options = JsonOptions {
+            skip_empty: options.skip_empty || !self.enforce_schema,
+            ..options.clone()
+        };
+        json::to_string(streams_info, &options)
+    }
+
+    /// Returns the SHA-256 hash of the current stream information, as a lowercase hexadecimal
+    /// string.
+    ///
+    /// The hash covers the playlist as rendered by `to_file` in the M3U format, so it ignores the
+    /// formatting of the source playlist, unknown attributes and the status of the streams. It
+    /// lets caching layers skip regenerating derived artifacts, such as EPG mappings, when a
+    /// refreshed playlist did not change. Use `Info::content_hash` to detect changed entries.
+    ///
+    pub fn content_hash(&self) -> String {
+        sha256_hex(&render_m3u(&self.streams_info))
+    }
+
+    /// Retrieves a vector containing all stream information.
+    ///
+    /// This function returns a deep clone of the internal `streams_info` vector, which
+    /// contains all the stream information.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<Info>` containing all stream information. If there is no stream information
+    /// available, an empty vector will be returned.
+    ///
+    pub fn get_vector(&self) -> Vec<Info> {
+        self.streams_info.clone()
+    }
+
+    /// Retrieves a page of the stream information.
+    ///
+    /// This function returns a slice of the stream information without cloning it, which makes it
+    /// suitable for serving large playlists incrementally.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_no` - The zero-based number of the page to retrieve.
+    /// * `page_size` - The number of streams per page.
+    ///
+    /// # Returns
+    ///
+    /// A `&[Info]` containing the streams of the requested page. If the page is out of range or
+    /// `page_size` is zero, an empty slice will be returned.
+    ///
+    pub fn page(&self, page_no: usize, page_size: usize) -> &[Info] {
+        let start = page_no
+            .saturating_mul(page_size)
+            .min(self.streams_info.len());
+        let end = start.saturating_add(page_size).min(self.streams_info.len());
+        &self.streams_info[start..end]
+    }
+
+    /// Returns an iterator over the stream information in chunks of `size` streams.
+    ///
+    /// The last chunk will be shorter if the number of streams is not a multiple of `size`.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The number of streams per chunk.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `size` is zero.
+    ///
+    pub fn chunks(&self, size: usize) -> std::slice::Chunks<'_, Info> {
+        self.streams_info.chunks(size)
+    }
+
+    /// Retrieves a random stream from the available stream information.
+    ///
+    /// This function randomly selects a stream from the available stream information.
+    /// The `random_shuffle` parameter determines whether to shuffle the stream information
+    /// before selecting a random stream. If the stream information is empty, `None` will be returned.
+    /// Note that shuffling reorders `streams_info` in place; use `random_stream` or `sample` to keep
+    /// the current order.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_shuffle` - A boolean indicating whether to shuffle the stream information before
+    ///   selecting a random stream.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<&Info>` representing the randomly selected stream. If a stream is successfully
+    /// selected, the result will contain a reference to the stream. Otherwise, if the stream
+    /// information is empty, `None` will be returned.
+    ///
+    #[cfg(feature = "random")]
+    pub fn get_random_stream(&mut self, random_shuffle: bool) -> Option<&Info> {
+        if self.streams_info.is_empty() {
+            eprintln!("No streams information so could not get any random stream.");
+            return None;
+        }
+        let mut rng = thread_rng();
+        if random_shuffle {
+            self.save_backup();
+            self.streams_info.shuffle(&mut rng);
+        }
+        Some(self.streams_info.choose(&mut rng).unwrap())
+    }
+
+    /// Retrieves a random stream without changing the order of the stream information.
+    ///
+    /// Unlike `get_random_stream`, this function never shuffles `streams_info`, so any sorting
+    /// applied beforehand is preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - An optional seed for the random number generator. Passing the same seed returns
+    ///   the same stream for the same stream information, which is useful for reproducible tests.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<&Info>` containing the randomly selected stream, or `None` if the stream
+    /// information is empty.
+    ///
+    #[cfg(feature = "random")]
+    pub fn random_stream(&self, seed: Option<u64>) -> Option<&Info> {
+        match seed {
+            Some(seed) => self.streams_info.choose(&mut StdRng::seed_from_u64(seed)),
+            None => self.streams_info.choose(&mut thread_rng()),
+        }
+    }
+
+    /// Retrieves `n` distinct random streams without changing the order of the stream information.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of streams to sample. If there are fewer than `n` streams, all of them
+    ///   are returned in random order.
+    /// * `seed` - An optional seed for the random number generator. Passing the same seed returns
+    ///   the same sample for the same stream information.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<&Info>` containing the sampled streams.
+    ///
+    #[cfg(feature = "random")]
+    pub fn sample(&self, n: usize, seed: Option<u64>) -> Vec<&Info> {
+        match seed {
+            Some(seed) => self
+                .streams_info
+                .choose_multiple(&mut StdRng::seed_from_u64(seed), n)
+                .collect(),
+            None => self
+                .streams_info
+                .choose_multiple(&mut thread_rng(), n)
+                .collect(),
+        }
+    }
+
+    /// Sets the options used to write the stream information as an M3U playlist.
+    ///
+    /// The options apply to `to_file`, `to_writer` and `export_favorites` in the M3U format, e.g.
+    /// to order or leave out attributes, to add `url-tvg` to the `#EXTM3U` header, to end lines
+    /// with `\r\n` or to write groups as `#EXTGRP` directives for players that require them.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `M3uWriteOptions` to use.
+    ///
+    pub fn set_m3u_write_options(&mut self, options: M3uWriteOptions) {
+        self.m3u_write_options = options;
+    }
+
+    /// Renders an XMLTV guide listing the channels without `tvg-id`, which a guide provider cannot
+    /// match, under the ids generated by the `stub_ids` write option, e.g. "bbc-one.stub".
+    ///
+    /// Together with `M3uWriteOptions::media_server`, this lets Plex and Jellyfin map every
+    /// channel of the playlist to a guide entry, which they require to list a channel. Only the
+    /// channels written with the current write options are listed.
+    ///
+    /// # Returns
+    ///
+    /// A `String` with the XMLTV document.
+    ///
+    pub fn xmltv_stub(&self) -> String {
+        let mut lines = vec![
+            String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"),
+            String::from("<tv generator-info-name=\"m3u_parser\">"),
+        ];
+        let streams_info = self.m3u_write_options.limit(&self.streams_info);
+        for info in streams_info.iter().filter(|info| info.tvg.id.is_empty()) {
+            lines.push(format!(
+                "  <channel id=\"{}\">",
+                html::escape(&write::stub_id(info))
+            ));
+            lines.push(format!(
+                "    <display-name>{}</display-name>",
+                html::escape(&info.title)
+            ));
+            if !info.logo.is_empty() {
+                lines.push(format!("    <icon src=\"{}\"/>", html::escape(&info.logo)));
+            }
+            lines.push(String::from("  </channel>"));
+        }
+        lines.push(String::from("</tv>"));
+        lines.join("\n") + "\n"
+    }
+
+    /// Sets whether the channels of the "html" format are listed under a heading per category.
+    ///
+    /// # Arguments
+    ///
+    /// * `grouped` - A boolean indicating whether to group the channels by category.
+    ///
+    pub fn set_html_grouped(&mut self, grouped: bool) {
+        self.html_grouped = grouped;
+    }
+
+    fn get_html_content(&self) -> String {
+        html::render_html(&self.streams_info, self.html_grouped)
+    }
+
+    /// Writes the stream information to a writer in the specified format.
+    ///
+    /// This function is the counterpart of `to_file` for arbitrary writers such as
+    /// `std::io::stdout()`, which allows the output to be piped into other commands.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer to write the stream information to.
+    /// * `format` - The output format, either "json", "m3u", "html", "md", "asx" or "wpl".
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` which is an error if the format is not recognised or if writing fails.
+    ///
+    pub fn to_writer<W: Write>(&self, writer: &mut W, format: &str) -> std::io::Result<()> {
+        let content = match format {
+            "json" => self.get_json(true).map_err(std::io::Error::from)?,
+            "m3u" => self.get_m3u_content(),
+            "html" => self.get_html_content(),
+            "md" => markdown::render_markdown(&self.streams_info),
+            "asx" => asx::render_asx(&self.streams_info),
+            "wpl" => asx::render_wpl(&self.streams_info),
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Unrecognised format: {}", format),
+                ))
+            }
+        };
+        writer.write_all(content.as_bytes())?;
+        writer.flush()
+    }
+
+    /// Writes the stream information to a writer using a template rendered for each stream, e.g.
+    /// to produce HTML channel pages, TVHeadend configurations or nginx maps.
+    ///
+    /// `{title}`, `{url}`, `{logo}`, `{category}`, `{status}`, `{source}`, `{tvg.id}`,
+    /// `{tvg.name}`, `{tvg.url}`, `{country.code}`, `{country.name}`, `{language.code}` and
+    /// `{language.name}` are replaced by the fields of the stream, as are `{duration}`,
+    /// `{comment}`, `{tags}`, `{display_title}` and `{extra.<field>}` for the extra attributes.
+    /// `{index}` is the position of the stream, starting at 0. `{if tvg.id}...{else}...{end}`
+    /// renders its content only if the field is not empty, the optional `{else}` part otherwise,
+    /// and `{if !logo}` negates the condition. `{{` and `}}` are written as `{` and `}`.
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The template, e.g. `"{tvg.id} {url}\n"`.
+    /// * `writer` - The writer to write the rendered streams to.
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` which is an error if the template is invalid, in which case nothing is
+    /// written, or if writing fails.
+    ///
+    pub fn export_with_template<W: Write>(
+        &self,
+        template: &str,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let template = template::Template::parse(template)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        for (index, stream_info) in self.streams_info.iter().enumerate() {
+            writer.write_all(template.render(stream_info, index).as_bytes())?;
+        }
+        writer.flush()
+    }
+
+    /// Writes the stream information to a file as an M3U playlist, entry by entry.
+    ///
+    /// Unlike `to_file`, the playlist is never held in memory as a whole, so exporting millions of
+    /// entries keeps memory use flat. The output is the same as `to_file` in the M3U format,
+    /// following the options set with `set_m3u_write_options`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the playlist file. If the file already exists, it will be
+    ///   overwritten.
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` which is an error if writing fails.
+    ///
+    pub fn write_m3u_streaming(&self, path: &str) -> std::io::Result<()> {
+        self.check_overwrite(path, self.streams_info.len())?;
+        let mut writer = BufWriter::new(self.create_output(path)?);
+        write::write_m3u(&mut writer, &self.streams_info, &self.m3u_write_options)?;
+        writer.flush()
+    }
+
+    /// Appends the stream information to an M3U playlist file.
+    ///
+    /// Incremental jobs can add the entries of each run to the same playlist without reading and
+    /// rewriting it. The `#EXTM3U` header is only written if the file does not exist yet or only
+    /// holds blank lines; otherwise the file must be an M3U playlist, i.e. start with `#EXTM3U`.
+    /// The entries follow the options set with `set_m3u_write_options`. With `channel_numbers`, the
+    /// file is read to number the appended entries after the ones it already has.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the playlist file, created if it does not exist.
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` with the number of appended entries, which is an error if the file is
+    /// not an M3U playlist or if reading or writing fails.
+    ///
+    pub fn to_file_append(&self, path: &str) -> std::io::Result<usize> {
+        use std::io::{BufRead, BufReader, Seek, SeekFrom};
+
+        if self.streams_info.is_empty() {
+            return Ok(0);
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let options = &self.m3u_write_options;
+        let mut content = String::new();
+        let mut first_line = None;
+        let mut existing_entries = 0;
+        let mut reader = BufReader::new(&file);
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            let trimmed = line.trim();
+            if trimmed.starts_with("#EXTINF") {
+                existing_entries += 1;
+            }
+            if first_line.is_none() && !trimmed.is_empty() {
+                first_line = Some(trimmed.trim_start_matches('\u{feff}').to_string());
+                if options.channel_numbers.is_none() {
+                    break;
+                }
+            }
+            line.clear();
+        }
+        // Only the header of a new file lacks the line ending preceding the first entry.
+        let new_file = first_line.is_none();
+        if new_file {
+            file.set_len(0)?;
+            content.push_str(&write::render_header(options));
+        } else {
+            if !first_line.is_some_and(|line| line.starts_with("#EXTM3U")) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Not an M3U playlist: {}", path),
+                ));
+            }
+            let mut last_byte = [0];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last_byte)?;
+            if last_byte[0] != b'\n' {
+                content.push_str(if options.crlf { "\r\n" } else { "\n" });
+            }
+        }
+
+        let mut writer = BufWriter::new(file);
+        writer.write_all(content.as_bytes())?;
+        let written = write::write_entries(
+            &mut writer,
+            &self.streams_info,
+            options,
+            new_file,
+            existing_entries,
+        )?;
+        writer.flush()?;
+        Ok(written)
+    }
+
+    /// Writes the stream information to a file as JSON, entry by entry.
+    ///
+    /// Unlike `get_json_with_options`, the JSON output is never held in memory as a whole, so
+    /// exporting millions of entries keeps memory use flat. The output is the same as
+    /// `get_json_with_options` with the same options.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the JSON file. If the file already exists, it will be overwritten.
+    /// * `options` - A `JsonOptions` struct describing how the stream information is serialized.
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` which is an error if writing fails.
+    ///
+    pub fn write_json_streaming(&self, path: &str, options: &JsonOptions) -> std::io::Result<()> {
+        let options = JsonOptions {
+            skip_empty: options.skip_empty || !self.enforce_schema,
+            ..options.clone()
+        };
+        self.check_overwrite(path, self.streams_info.len())?;
+        let mut writer = BufWriter::new(self.create_output(path)?);
+        json::write_array(&mut writer, &self.streams_info, &options)?;
+        writer.flush()
+    }
+
+    /// Saves the stream information to a file in the specified format.
+    ///
+    /// This function saves the stream information to a file with the given `filename` and `format`.
+    /// If the `filename` already contains a file extension, it will be used as the format. Otherwise,
+    /// the `format` parameter will be used as the file extension.
+    ///
+    /// The supported formats are "json", "m3u", "html", "md", "asx" and "wpl". For "json" format, the stream information will be
+    /// saved as a JSON string in a pretty printed format. For "m3u" format, the stream information will
+    /// be saved as an M3U playlist. For "html" format, the stream information will be saved as a
+    /// self-contained page showing the logo, title, category and status of each channel with a link
+    /// to its stream, grouped by category if enabled with `set_html_grouped`. For "md" format, the
+    /// stream information will be saved as a Markdown table with the title, category, country,
+    /// status and URL of each stream, e.g. to report dead channels in an issue; sort the streams
+    /// first to order the rows. For "asx" and "wpl" formats, the title and URL of the streams will be
+    /// saved as an ASX or WPL playlist of Windows Media Player.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - A string representing the name of the file to be saved. If the file already exists,
+    ///   it will be overwritten, after backing it up if enabled with `set_backup_retention`.
+    /// * `format` - A string representing the format in which the stream information should be saved. If
+    ///   the `filename` already contains a file extension, it will be used as the format.
+    ///   Otherwise, the `format` parameter will be used as the file extension.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if there is an error while converting the stream information to the specified format
+    /// or if there is an error while saving the file.
+    pub fn to_file(&self, filename: &str, format: &str) {
+        let format = if filename.contains(".") {
+            filename.split(".").last().unwrap_or(format)
+        } else {
+            format
+        };
+
+        let filename = match filename.to_lowercase().ends_with(format) {
+            true => filename.to_owned(),
+            false => format!("{}.{}", filename, format),
+        };
+
+        if self.streams_info.is_empty() {
+            eprintln!("Either parsing is not done or no stream info was found after parsing !!!");
+            return;
+        }
+
+        eprintln!("Saving to file: {}", filename);
+        match format {
+            "json" => {
+                let content = self.get_json(true).unwrap();
+                self.save_file(filename.as_str(), content.as_bytes());
+            }
+            "m3u" => {
+                let content = self.get_m3u_content();
+                self.save_file(filename.as_str(), content.as_bytes());
+            }
+            "html" => {
+                let content = self.get_html_content();
+                self.save_file(filename.as_str(), content.as_bytes());
+            }
+            "md" => {
+                let content = markdown::render_markdown(&self.streams_info);
+                self.save_file(filename.as_str(), content.as_bytes());
+            }
+            "asx" => {
+                let content = asx::render_asx(&self.streams_info);
+                self.save_file(filename.as_str(), content.as_bytes());
+            }
+            "wpl" => {
+                let content = asx::render_wpl(&self.streams_info);
+                self.save_file(filename.as_str(), content.as_bytes());
+            }
+            _ => eprintln!("Unrecognised format!!!"),
+        }
+    }
+
+    /// Saves the stream information to an M3U file, preserving the original lines of unmodified entries.
+    ///
+    /// Entries that were not modified since parsing are written exactly as they appeared in the
+    /// source playlist, including attributes unknown to the parser and directive lines such as
+    /// `#EXTVLCOPT`. Modified entries are regenerated. The `#EXTM3U` header line of the source
+    /// playlist is also preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - A string representing the name of the file to be saved. If the file already exists,
+    ///   it will be overwritten.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if there is an error while saving the file.
+    pub fn to_file_raw(&self, filename: &str) {
+        if self.streams_info.is_empty() {
+            eprintln!("Either parsing is not done or no stream info was found after parsing !!!");
+            return;
+        }
+
+        eprintln!("Saving to file: {}", filename);
+        let content = self.get_raw_m3u_content();
+        self.save_file(filename, content.as_bytes());
+    }
+
+    /// Exports a report of the availability checks of the streams to a file.
+    ///
+    /// Unlike `to_file`, the report is not a playlist: it lists the title, URL, status, HTTP status,
+    /// latency, error message and check timestamp of every stream, which is useful to attach to
+    /// provider support tickets. Streams that were not checked have empty check columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the report file. If the file already exists, it will be overwritten.
+    /// * `format` - The format of the report, either "csv" or "json".
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` which is an error if the format is not recognised or if writing fails.
+    ///
+    pub fn export_check_report(&self, path: &str, format: &str) -> std::io::Result<()> {
+        let report = report::check_report(&self.streams_info, format)?;
+        File::create(path)?.write_all(report.as_bytes())
+    }
+
+    /// Exports the favorite streams, tagged "favorite" e.g. with `mark_favorite`, as an M3U
+    /// playlist.
+    ///
+    /// The favorites playlist is meant to be saved alongside the full one: set-top boxes handle a
+    /// short curated list far better than a playlist of thousands of channels. The current stream
+    /// information is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the playlist file. If the file already exists, it will be
+    ///   overwritten.
+    ///
+    /// # Returns
+    ///
+    /// A `std::io::Result` with the number of exported streams, which is an error if writing fails.
+    ///
+    pub fn export_favorites(&self, path: &str) -> std::io::Result<usize> {
+        let favorites: Vec<Info> = self
+            .streams_info
+            .iter()
+            .filter(|info| info.tags.contains(FAVORITE_TAG))
+            .cloned()
+            .collect();
+        let content = write::render_m3u(&favorites, &self.m3u_write_options);
+        self.check_overwrite(path, favorites.len())?;
+        self.create_output(path)?.write_all(content.as_bytes())?;
+        Ok(favorites.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    #[cfg(feature = "net")]
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+    #[cfg(feature = "net")]
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[cfg(feature = "geo")]
+    use super::ValidationIssueKind;
+    use super::{
+        ApiOptions, Blocklist, CheckResult, Country, DedupeKey, FilterConfig, HttpFetch, Info,
+        InfoKey, JsonOptions, M3uAttribute, M3uParser, M3uWriteOptions, MergeKey, OutputConfig,
+        ParseWarningReason, ParserConfig, PlaylistKind, Rules, SortOrder, SyncPolicy,
+    };
+    #[cfg(feature = "net")]
+    use super::{CheckErrorKind, RedirectPolicy, StreamChecker, VariantStrategy};
+
+    const SAMPLE_PLAYLIST: &str = r#"#EXTM3U
+#EXTINF:-1 tvg-id="bbcone.uk" tvg-name="BBC One" tvg-country="GB" tvg-language="English" group-title="News",BBC One
+http://example.com/bbc-one.m3u8
+#EXTINF:-1 tvg-id="tf1.fr" tvg-name="TF1" tvg-country="FR" tvg-language="French" group-title="General",TF1
+http://example.com/tf1.m3u8
+#EXTINF:-1 tvg-id="local.np" tvg-name="Local" tvg-country="NP" tvg-language="Nepali" group-title="General",Local
+/media/videos/local.mp4
+"#;
+
+    /// Serves HTTP on a local port, answering each request with the response built by `respond`.
+    #[cfg(feature = "net")]
+    async fn serve(respond: fn(&str) -> String) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 8192];
+                    let read = socket.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                    let _ = socket.write_all(respond(&request).as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[cfg(feature = "net")]
+    fn http_response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            headers,
+            body.len(),
+            body
+        )
+    }
+
+    async fn parse_content(content: &str) -> M3uParser {
+        parse_content_with(content, true).await
+    }
+
+    async fn parse_content_with(content: &str, enforce_schema: bool) -> M3uParser {
+        let mut parser = M3uParser::new(None);
+        parser
+            .parse_m3u_content(content, false, enforce_schema)
+            .await;
+        parser
+    }
+
+    #[cfg(all(feature = "net", feature = "random"))]
+    #[tokio::test]
+    async fn test_m3u_parser() {
+        let mut parser = M3uParser::new(Some(Duration::from_secs(5)));
+        parser
+            .parse_m3u(
+                "https://iptv-org.github.io/iptv/index.country.m3u",
+                true,
+                true,
+            )
+            .await;
+
+        parser.filter_by("title", vec!["Metro TV"], "_", false, false);
+        parser.sort_by("title", "_", false, false);
+
+        assert!(
+            !parser
+                .streams_info
+                .iter()
+                .any(|info| info.title == "Metro TV"),
+            "Metro TV is available as a title"
+        );
+
+        let random_stream = parser.get_random_stream(true);
+        assert!(random_stream.is_some(), "Random stream should be available");
+
+        let file_path = "hello.m3u";
+        parser.to_file(file_path, "m3u");
+
+        // Assert that the file exists
+        assert!(fs::metadata(file_path).is_ok(), "Output file should exist");
+
+        // Clean up the temporary file
+        if let Err(err) = fs::remove_file(file_path) {
+            eprintln!("Failed to remove file: {}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_convenience_filters() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert_eq!(parser.streams_info.len(), 3);
+
+        parser.retrieve_by_country(vec!["GB", "FR"]);
+        assert_eq!(parser.streams_info.len(), 2);
+
+        parser.remove_by_language(vec!["FR"]);
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "BBC One");
+
+        parser.reset_operations();
+        parser.retrieve_good_streams();
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "Local");
+
+        parser.reset_operations();
+        parser.remove_bad_streams();
+        assert_eq!(parser.streams_info.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_region() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+
+        parser.retrieve_by_region(vec!["europe"]);
+        assert_eq!(parser.streams_info.len(), 2);
+
+        parser.remove_by_region(vec!["Northern Europe", "Atlantis"]);
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "TF1");
+
+        parser.reset_operations();
+        parser.retrieve_by_region(vec!["Southern Asia"]);
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "Local");
+
+        parser.reset_operations();
+        parser.retrieve_by_region(vec!["Atlantis"]);
+        assert_eq!(parser.streams_info.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_normalize_urls() {
+        let content = "#EXTM3U
+#EXTINF:-1,One
+HTTP://Example.com:80/live/../one.m3u8?token=1&a=2
+#EXTINF:-1,One again
+http://example.com/one.m3u8?a=2&token=1
+#EXTINF:-1,Local
+/media/videos/local.mp4
+";
+        let mut parser = parse_content(content).await;
+        assert_eq!(parser.normalize_urls(true), 1);
+        assert_eq!(
+            parser.streams_info[0].url,
+            "http://example.com/one.m3u8?a=2&token=1"
+        );
+        assert_eq!(parser.find_duplicates(DedupeKey::Url).len(), 1);
+        assert_eq!(parser.streams_info[2].url, "/media/videos/local.mp4");
+        assert_eq!(parser.normalize_urls(true), 0);
+
+        parser.reset_operations();
+        assert_eq!(parser.normalize_urls(false), 1);
+        assert_eq!(
+            parser.streams_info[0].url,
+            "http://example.com/one.m3u8?token=1&a=2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_urls() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert_eq!(parser.refresh_urls(false, 4).await, 0);
+
+        parser.set_url_refresher(|info: Info| async move {
+            match info.tvg.id.as_str() {
+                "local.np" => None,
+                id => Some(format!("http://example.com/{}.m3u8?token=fresh", id)),
+            }
+        });
+        parser.streams_info[1].check = Some(CheckResult {
+            http_status: Some(403),
+            ..Default::default()
+        });
+        assert_eq!(parser.refresh_urls(true, 4).await, 1);
+        assert_eq!(
+            parser.streams_info[0].url,
+            "http://example.com/bbc-one.m3u8"
+        );
+        assert_eq!(
+            parser.streams_info[1].url,
+            "http://example.com/tf1.fr.m3u8?token=fresh"
+        );
+        assert!(parser.streams_info[1].check.is_none());
+
+        assert_eq!(parser.refresh_urls(false, 4).await, 1);
+        assert_eq!(
+            parser.streams_info[0].url,
+            "http://example.com/bbcone.uk.m3u8?token=fresh"
+        );
+        assert_eq!(parser.streams_info[2].url, "/media/videos/local.mp4");
+
+        parser.set_url_refresher(|_: Info| async {
+            Some(String::from("/media/videos/refreshed.mp4"))
+        });
+        parser.streams_info[2].status = String::from("BAD");
+        parser.streams_info[2].check = Some(CheckResult {
+            http_status: Some(401),
+            ..Default::default()
+        });
+        assert_eq!(parser.refresh_urls(true, 1).await, 1);
+        assert_eq!(parser.streams_info[2].url, "/media/videos/refreshed.mp4");
+        assert_eq!(parser.streams_info[2].status, "GOOD");
+        assert!(parser.streams_info[2].check.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_checked_since() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        parser.streams_info[0].check = Some(CheckResult {
+            checked_at: 1700000000,
+            ..Default::default()
+        });
+        parser.streams_info[1].check = Some(CheckResult {
+            checked_at: crate::cache::unix_now() - 60,
+            ..Default::default()
+        });
+        assert_eq!(
+            parser.streams_info[0]
+                .check
+                .as_ref()
+                .unwrap()
+                .checked_at_utc(),
+            "2023-11-14T22:13:20Z"
+        );
+        assert!(!parser.get_m3u_content().contains("x-checked-at"));
+        // A check does not count as a modification of the entry.
+        assert_eq!(
+            Some(parser.streams_info[0].to_raw_m3u_entry()),
+            parser.streams_info[0].raw
+        );
+        let mut options = M3uWriteOptions::default();
+        options.attributes.push(M3uAttribute::CheckedAt);
+        parser.set_m3u_write_options(options);
+        assert!(parser
+            .get_m3u_content()
+            .contains(r#"group-title="News" x-checked-at="2023-11-14T22:13:20Z",BBC One"#));
+
+        parser.filter_by_checked_since(Duration::from_secs(3600));
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "TF1");
+    }
+
+    #[tokio::test]
+    async fn test_backup_urls() {
+        let content = "#EXTM3U
+#EXTINF:-1 tvg-id=\"one.uk\",One
+http://example.com/one.m3u8
+http://backup.example.com/one.m3u8
+
+http://mirror.example.com/one.m3u8
+#EXTINF:-1,Two
+http://example.com/two.m3u8
+";
+        let parser = parse_content(content).await;
+        assert!(parser.warnings().is_empty());
+        assert_eq!(parser.streams_info.len(), 2);
+        assert_eq!(
+            parser.streams_info[0].backup_urls,
+            vec![
+                "http://backup.example.com/one.m3u8",
+                "http://mirror.example.com/one.m3u8"
+            ]
+        );
+        assert!(parser.streams_info[1].backup_urls.is_empty());
+        assert!(parser.get_m3u_content().contains(
+            ",One\nhttp://example.com/one.m3u8\nhttp://backup.example.com/one.m3u8\n\
+             http://mirror.example.com/one.m3u8\n#EXTINF"
+        ));
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_promote_first_good_url() {
+        struct MockChecker;
+
+        #[async_trait::async_trait]
+        impl StreamChecker for MockChecker {
+            async fn is_available(&self, url: &str) -> bool {
+                url.contains("mirror")
+            }
+        }
+
+        let content = "#EXTM3U
+#EXTINF:-1,One
+http://example.com/one.m3u8
+http://backup.example.com/one.m3u8
+http://mirror.example.com/one.m3u8
+#EXTINF:-1,Two
+http://example.com/two.m3u8
+http://backup.example.com/two.m3u8
+";
+        let mut parser = M3uParser::new(None);
+        parser.set_stream_checker(MockChecker);
+        parser.parse_m3u_content(content, true, true).await;
+        assert_eq!(parser.promote_first_good_url().await, 1);
+
+        let info = &parser.streams_info[0];
+        assert_eq!(info.url, "http://mirror.example.com/one.m3u8");
+        assert_eq!(info.status, "GOOD");
+        assert_eq!(
+            info.backup_urls,
+            vec![
+                "http://backup.example.com/one.m3u8",
+                "http://example.com/one.m3u8"
+            ]
+        );
+        assert_eq!(parser.streams_info[1].status, "BAD");
+        assert_eq!(parser.promote_first_good_url().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_merge_channels() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 tvg-id="one.uk",One
+http://a.example.com/one.m3u8
+http://a.example.com/one-backup.m3u8
+#EXTINF:-1 tvg-id="two.uk",Two
+http://a.example.com/two.m3u8
+#EXTINF:-1 tvg-id="one.uk" tvg-logo="http://example.com/one.png" x-tags="hd",One HD
+http://b.example.com/one.m3u8
+#EXTINF:-1 tvg-id="one.uk",One
+http://c.example.com/one.m3u8
+#EXTINF:-1,No id
+http://c.example.com/none.m3u8
+"#;
+        let mut parser = parse_content(content).await;
+        parser.streams_info[2].status = String::from("GOOD");
+        parser.streams_info[2].check = Some(CheckResult {
+            available: true,
+            latency_ms: Some(300),
+            ..Default::default()
+        });
+        parser.streams_info[3].status = String::from("GOOD");
+        parser.streams_info[3].check = Some(CheckResult {
+            available: true,
+            latency_ms: Some(100),
+            ..Default::default()
+        });
+
+        assert_eq!(parser.merge_channels(MergeKey::TvgId), 2);
+        let titles: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["One", "Two", "No id"]);
+
+        let one = &parser.streams_info[0];
+        assert_eq!(one.url, "http://c.example.com/one.m3u8");
+        assert_eq!(
+            one.backup_urls,
+            vec![
+                "http://b.example.com/one.m3u8",
+                "http://a.example.com/one.m3u8",
+                "http://a.example.com/one-backup.m3u8"
+            ]
+        );
+        assert_eq!(one.logo, "http://example.com/one.png");
+        assert!(one.tags.contains("hd"));
+        assert_eq!(one.source_index, 0);
+        assert_eq!(parser.merge_channels(MergeKey::TvgId), 0);
+
+        parser.reset_operations();
+        assert_eq!(parser.merge_channels(MergeKey::Title), 1);
+        assert_eq!(parser.streams_info.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_duration_filters() {
+        let content = "#EXTM3U
+#EXTINF:245.5,Artist - Song
+/music/song.mp3
+#EXTINF:0,Artist - Empty
+/music/empty.mp3
+#EXTINF:3600,Movie
+/videos/movie.mkv
+#EXTINF:-1,Live
+http://example.com/live.m3u8
+";
+        let mut parser = parse_content(content).await;
+        assert_eq!(parser.total_duration(), 3845.5);
+
+        parser.filter_by_duration(1.0..);
+        let titles: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Artist - Song", "Movie"]);
+
+        parser.reset_operations();
+        parser.filter_by_duration(..=600.0);
+        assert_eq!(parser.streams_info.len(), 2);
+        assert_eq!(parser.total_duration(), 245.5);
+    }
+
+    #[tokio::test]
+    async fn test_parse_hooks() {
+        use std::sync::{Arc, Mutex};
+
+        let titles = Arc::new(Mutex::new(vec![]));
+        let totals = Arc::new(Mutex::new(vec![]));
+        let mut parser = M3uParser::new(None);
+        let entry_titles = Arc::clone(&titles);
+        parser.on_entry(move |info| entry_titles.lock().unwrap().push(info.title.clone()));
+        let complete_totals = Arc::clone(&totals);
+        parser.on_complete(move |stats| complete_totals.lock().unwrap().push(stats.total));
+
+        parser.parse_m3u_content(SAMPLE_PLAYLIST, false, true).await;
+        assert_eq!(*titles.lock().unwrap(), vec!["BBC One", "TF1", "Local"]);
+        assert_eq!(*totals.lock().unwrap(), vec![3]);
+
+        parser
+            .parse_m3u_content(
+                "#EXTM3U\n#EXTINF:-1,Only\nhttp://example.com/only.m3u8\n",
+                false,
+                true,
+            )
+            .await;
+        assert_eq!(titles.lock().unwrap().len(), 4);
+        assert_eq!(*totals.lock().unwrap(), vec![3, 1]);
+
+        parser.set_strict(true);
+        parser
+            .parse_m3u_content("#EXTINF:-1,Rejected\n", false, true)
+            .await;
+        assert_eq!(titles.lock().unwrap().len(), 4);
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_parse_hooks_checked() {
+        use std::sync::{Arc, Mutex};
+
+        let statuses = Arc::new(Mutex::new(vec![]));
+        let mut parser = M3uParser::new(None);
+        parser.set_check_local_files(true, false);
+        let entry_statuses = Arc::clone(&statuses);
+        parser.on_entry(move |info| entry_statuses.lock().unwrap().push(info.status.clone()));
+        let complete_statuses = Arc::clone(&statuses);
+        parser.on_complete(move |_| assert_eq!(complete_statuses.lock().unwrap().len(), 1));
+
+        parser
+            .parse_m3u_content(
+                "#EXTM3U\n#EXTINF:-1,Missing\n/missing/m3u-parser/file.mp4\n",
+                true,
+                true,
+            )
+            .await;
+        assert_eq!(*statuses.lock().unwrap(), vec!["BAD"]);
+    }
+
+    #[tokio::test]
+    async fn test_group_by_timezone() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert_eq!(
+            parser.streams_info[0].timezone.as_deref(),
+            Some("Europe/London")
+        );
+        parser.streams_info[1].timezone = None;
+
+        let groups = parser.group_by_timezone();
+        assert_eq!(
+            groups.keys().copied().collect::<Vec<_>>(),
+            vec!["Asia/Kathmandu", "Europe/London"]
+        );
+        assert_eq!(groups["Asia/Kathmandu"][0].title, "Local");
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_many() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+
+        parser.filter_by_many(&[
+            (InfoKey::Category, vec!["General"]),
+            (InfoKey::CountryCode, vec!["FR", "GB"]),
+        ]);
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "TF1");
+
+        parser.reset_operations();
+        parser.filter_by_many(&[
+            (InfoKey::Category, vec!["General"]),
+            (InfoKey::Status, vec!["GOOD"]),
+        ]);
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "Local");
+    }
+
+    #[tokio::test]
+    async fn test_limit_per_group() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 tvg-country="GB",BBC One
+http://example.com/bbc-one.m3u8
+#EXTINF:-1 tvg-country="GB",BBC Two
+http://example.com/bbc-two.m3u8
+#EXTINF:-1 tvg-country="GB",Local GB
+/media/gb.mp4
+#EXTINF:-1 tvg-country="FR",TF1
+http://example.com/tf1.m3u8
+#EXTINF:-1,Unknown One
+http://example.com/unknown-one.m3u8
+#EXTINF:-1,Unknown Two
+http://example.com/unknown-two.m3u8
+"#;
+        let mut parser = parse_content(content).await;
+        let titles = |parser: &M3uParser| -> Vec<String> {
+            parser
+                .streams_info
+                .iter()
+                .map(|info| info.title.clone())
+                .collect()
+        };
+
+        parser.limit_per_group(InfoKey::CountryCode, 2);
+        assert_eq!(
+            titles(&parser),
+            vec!["BBC One", "Local GB", "TF1", "Unknown One", "Unknown Two"]
+        );
+
+        parser.reset_operations();
+        parser.limit_per_group_by(InfoKey::CountryCode, 1, |a, b| b.title.cmp(&a.title));
+        assert_eq!(
+            titles(&parser),
+            vec!["Local GB", "TF1", "Unknown One", "Unknown Two"]
+        );
+
+        parser.reset_operations();
+        parser.limit_per_group(InfoKey::CountryCode, 3);
+        assert!(parser.streams_info_backup.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_take_and_skip() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        let titles = |parser: &M3uParser| -> Vec<String> {
+            parser
+                .streams_info
+                .iter()
+                .map(|info| info.title.clone())
+                .collect()
+        };
+
+        parser.truncate(5);
+        assert!(parser.streams_info_backup.is_none());
+        parser.truncate(2);
+        assert_eq!(titles(&parser), vec!["BBC One", "TF1"]);
+        parser.skip(1);
+        assert_eq!(titles(&parser), vec!["TF1"]);
+
+        parser.reset_operations();
+        parser.take_range(1..10);
+        assert_eq!(titles(&parser), vec!["TF1", "Local"]);
+        parser.reset_operations();
+        parser.take_range(5..10);
+        assert!(parser.streams_info.is_empty());
+        parser.reset_operations();
+        assert_eq!(parser.streams_info.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_index() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert_eq!(parser.get_by_tvg_id("tf1.fr").unwrap().title, "TF1");
+        assert_eq!(
+            parser.get_by_url("/media/videos/local.mp4").unwrap().title,
+            "Local"
+        );
+        assert!(parser.get_by_tvg_id("missing").is_none());
+
+        parser.remove_by_country(vec!["FR"]);
+        assert!(parser.get_by_tvg_id("tf1.fr").is_none());
+        assert_eq!(parser.get_by_tvg_id("local.np").unwrap().title, "Local");
+        parser.sort_by("title", "_", false, false);
+        assert_eq!(parser.get_by_tvg_id("local.np").unwrap().title, "Local");
+
+        parser.reset_operations();
+        assert_eq!(parser.get_by_tvg_id("tf1.fr").unwrap().title, "TF1");
+        let index = parser.build_index();
+        assert_eq!(index.get_by_tvg_id("bbcone.uk").unwrap().title, "BBC One");
+        assert_eq!(index.by_url().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_apply_rules() {
+        let rules = Rules::from_json(
+            r#"{"rules": [
+                {"name": "French", "when": {"country-code": "^FR$"}, "set": {"category": "France"}},
+                {"when": {"title": "^Local$"}, "remove": true},
+                {"when": {"category": "France", "title": "TF1"}, "set": {"tvg-id": "TF1.fr"}}
+            ]}"#,
+        )
+        .unwrap();
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+
+        assert_eq!(parser.apply_rules(&rules), 2);
+        assert_eq!(parser.streams_info.len(), 2);
+        let tf1 = &parser.streams_info[1];
+        assert_eq!(
+            (tf1.category.as_str(), tf1.tvg.id.as_str()),
+            ("France", "TF1.fr")
+        );
+        assert_eq!(parser.apply_rules(&rules), 0);
+
+        parser.reset_operations();
+        assert_eq!(parser.streams_info[1].category, "General");
+    }
+
+    #[tokio::test]
+    async fn test_overrides() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        parser.set_override("tf1.fr", "title", "TF1 HD");
+        parser.set_override(
+            "http://example.com/tf1.m3u8",
+            "logo",
+            "http://example.com/tf1.png",
+        );
+        parser.set_override("tf1.fr", "group", "France");
+        assert_eq!(parser.overrides()["tf1.fr"].len(), 1);
+        assert_eq!(parser.streams_info[1].title, "TF1 HD");
+        assert_eq!(parser.streams_info[1].logo, "http://example.com/tf1.png");
+
+        let path = std::env::temp_dir().join("m3u_parser_overrides.json");
+        let path = path.to_str().unwrap();
+        parser.save_overrides(path).unwrap();
+
+        let mut parser = M3uParser::new(None);
+        parser.load_overrides(path).unwrap();
+        fs::remove_file(path).unwrap();
+        parser.parse_m3u_content(SAMPLE_PLAYLIST, false, true).await;
+        assert_eq!(parser.streams_info[1].title, "TF1 HD");
+        assert_eq!(parser.streams_info[1].logo, "http://example.com/tf1.png");
+
+        parser.remove_override("tf1.fr", "title");
+        parser.parse_m3u_content(SAMPLE_PLAYLIST, false, true).await;
+        assert_eq!(parser.streams_info[1].title, "TF1");
+        assert_eq!(parser.overrides().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sync_from() {
+        let provider_content = r#"#EXTM3U
+#EXTINF:-1 tvg-id="bbcone.uk" tvg-country="GB" group-title="News",BBC One HD
+http://example.com/bbc-one-hd.m3u8
+#EXTINF:-1 tvg-id="tf1.fr" tvg-name="TF1" tvg-country="FR" tvg-language="French" group-title="General",TF1
+http://example.com/tf1-new.m3u8
+#EXTINF:-1 tvg-id="arte.fr" group-title="Culture",Arte
+http://example.com/arte.m3u8
+"#;
+        let provider = parse_content(provider_content).await;
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        parser.streams_info[1].title = String::from("TF1 (mine)");
+
+        let report = parser.sync_from(&provider, SyncPolicy::KeepLocalEdits);
+        assert_eq!((report.updated, report.added), (2, 1));
+        assert_eq!(report.missing, vec!["/media/videos/local.mp4"]);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].field, InfoKey::Title);
+        assert_eq!(report.conflicts[0].provider, "TF1");
+
+        let entries: Vec<(&str, &str)> = parser
+            .streams_info
+            .iter()
+            .map(|info| (info.title.as_str(), info.url.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("BBC One HD", "http://example.com/bbc-one-hd.m3u8"),
+                ("TF1 (mine)", "http://example.com/tf1-new.m3u8"),
+                ("Local", "/media/videos/local.mp4"),
+                ("Arte", "http://example.com/arte.m3u8"),
+            ]
+        );
+        assert_eq!(parser.streams_info[3].source_index, 3);
+
+        parser.reset_operations();
+        parser.streams_info[1].title = String::from("TF1 (mine)");
+        parser.sync_from(&provider, SyncPolicy::PreferProvider);
+        assert_eq!(parser.streams_info[1].title, "TF1");
+    }
+
+    #[tokio::test]
+    async fn test_seen_times() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        let parsed_at = parser.parsed_at().unwrap();
+        assert!(M3uParser::new(None).parsed_at().is_none());
+        assert!(parser
+            .streams_info
+            .iter()
+            .all(|info| info.first_seen == Some(parsed_at) && info.last_seen == Some(parsed_at)));
+
+        let mut provider = parse_content(SAMPLE_PLAYLIST).await;
+        provider.streams_info.truncate(2);
+        for info in provider.streams_info.iter_mut() {
+            info.first_seen = Some(parsed_at + 60);
+            info.last_seen = Some(parsed_at + 60);
+        }
+        parser.streams_info[1].title = String::from("TF1 (mine)");
+        parser.sync_from(&provider, SyncPolicy::KeepLocalEdits);
+
+        let seen: Vec<(Option<u64>, Option<u64>)> = parser
+            .streams_info
+            .iter()
+            .map(|info| (info.first_seen, info.last_seen))
+            .collect();
+        assert_eq!(
+            seen,
+            vec![
+                (Some(parsed_at), Some(parsed_at + 60)),
+                (Some(parsed_at), Some(parsed_at + 60)),
+                (Some(parsed_at), Some(parsed_at)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prune_stale() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        let parsed_at = parser.parsed_at().unwrap();
+        parser.streams_info[0].last_seen = Some(parsed_at - 10 * 86400);
+        parser.streams_info[2].last_seen = None;
+
+        assert_eq!(parser.prune_stale(Duration::from_secs(7 * 86400)), 1);
+        let titles: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["TF1", "Local"]);
+        assert_eq!(parser.prune_stale(Duration::from_secs(7 * 86400)), 0);
+
+        parser.reset_operations();
+        assert_eq!(parser.streams_info.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_content_hash() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        let hash = parser.content_hash();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(
+            M3uParser::new(None).content_hash(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        let reformatted = SAMPLE_PLAYLIST.replace('\n', "\r\n\n");
+        let other = parse_content(&reformatted).await;
+        assert_eq!(other.content_hash(), hash);
+        let entry_hash = parser.streams_info[1].content_hash();
+
+        parser.streams_info[1].status = String::from("GOOD");
+        assert_eq!(parser.content_hash(), hash);
+        parser.streams_info[1].title = String::from("TF1 HD");
+        assert_ne!(parser.content_hash(), hash);
+        assert_ne!(parser.streams_info[1].content_hash(), entry_hash);
+        assert_eq!(
+            parser.streams_info[0].content_hash(),
+            other.streams_info[0].content_hash()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 group-title="News",BBC News
+http://example.com/bbc-news.m3u8
+#EXTINF:-1 tvg-name="Télé Monte-Carlo" group-title="General",TMC
+http://example.com/tmc.m3u8
+#EXTINF:-1 group-title="General",BBC One
+http://example.com/bbc-one.m3u8
+#EXTINF:-1 group-title="Sports",Eurosport
+http://example.com/eurosport.m3u8
+"#;
+        let parser = parse_content(content).await;
+        let search = |query| -> Vec<&str> {
+            parser
+                .search(query)
+                .into_iter()
+                .map(|(info, _)| info.title.as_str())
+                .collect()
+        };
+
+        assert_eq!(search("bbc one"), vec!["BBC One"]);
+        assert_eq!(search("News"), vec!["BBC News"]);
+        assert_eq!(search("tele"), vec!["TMC"]);
+        assert_eq!(search("sport"), vec!["Eurosport"]);
+        assert_eq!(search("bbc"), vec!["BBC News", "BBC One"]);
+        assert_eq!(search("bbcone"), vec!["BBC One"]);
+        assert!(search(" ").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sort_by_keys() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 group-title="Sports",Channel 10
+http://example.com/10.m3u8
+#EXTINF:-1 group-title="News",Channel 2
+http://example.com/2.m3u8
+#EXTINF:-1 group-title="Sports",Channel 2
+http://example.com/sports-2.m3u8
+#EXTINF:-1 group-title="News",Channel 1
+http://example.com/1.m3u8
+"#;
+        let mut parser = parse_content(content).await;
+        parser.set_natural_sort(true);
+        parser.sort_by_keys(&[
+            (InfoKey::Category, SortOrder::Descending),
+            (InfoKey::Title, SortOrder::Ascending),
+        ]);
+
+        let titles: Vec<(&str, &str)> = parser
+            .streams_info
+            .iter()
+            .map(|info| (info.category.as_str(), info.title.as_str()))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("Sports", "Channel 2"),
+                ("Sports", "Channel 10"),
+                ("News", "Channel 1"),
+                ("News", "Channel 2"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_page_and_chunks() {
+        let parser = parse_content(SAMPLE_PLAYLIST).await;
+
+        assert_eq!(parser.page(0, 2).len(), 2);
+        assert_eq!(parser.page(1, 2).len(), 1);
+        assert_eq!(parser.page(1, 2)[0].title, "Local");
+        assert!(parser.page(2, 2).is_empty());
+        assert!(parser.page(0, 0).is_empty());
+
+        let sizes: Vec<usize> = parser.chunks(2).map(|chunk| chunk.len()).collect();
+        assert_eq!(sizes, vec![2, 1]);
+    }
+
+    #[cfg(feature = "random")]
+    #[tokio::test]
+    async fn test_sample_and_random_stream() {
+        let parser = parse_content(SAMPLE_PLAYLIST).await;
+        let titles: Vec<String> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.title.clone())
+            .collect();
+
+        let sample = parser.sample(2, Some(42));
+        assert_eq!(sample.len(), 2);
+        assert_ne!(sample[0].url, sample[1].url);
+        let same_sample = parser.sample(2, Some(42));
+        assert_eq!(sample[0].url, same_sample[0].url);
+        assert_eq!(sample[1].url, same_sample[1].url);
+        assert_eq!(parser.sample(10, None).len(), 3);
+
+        assert_eq!(
+            parser.random_stream(Some(7)).unwrap().url,
+            parser.random_stream(Some(7)).unwrap().url
+        );
+
+        let titles_after: Vec<String> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.title.clone())
+            .collect();
+        assert_eq!(titles, titles_after);
+    }
+
+    #[tokio::test]
+    async fn test_enforce_schema() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 tvg-country="GB",BBC One
+http://example.com/bbc-one.m3u8
+"#;
+        let parser = parse_content_with(content, true).await;
+        let json: serde_json::Value =
+            serde_json::from_str(&parser.get_json(false).unwrap()).unwrap();
+        assert_eq!(json[0]["logo"], "");
+        assert_eq!(json[0]["tvg"]["id"], "");
+
+        let parser = parse_content_with(content, false).await;
+        let json: serde_json::Value =
+            serde_json::from_str(&parser.get_json(false).unwrap()).unwrap();
+        assert_eq!(json[0]["title"], "BBC One");
+        assert_eq!(json[0]["country"]["code"], "GB");
+        assert!(json[0].get("logo").is_none());
+        assert!(json[0].get("tvg").is_none());
+        #[cfg(feature = "geo")]
+        assert!(json[0]["country"].get("name").is_some());
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema() {
+        let schema = serde_json::to_value(M3uParser::json_schema()).unwrap();
+        assert_eq!(schema["type"], "array");
+        let schema = schema.to_string();
+        for field in ["title", "tvg", "country", "language", "status"] {
+            assert!(
+                schema.contains(field),
+                "{} is missing from the schema",
+                field
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backup_retention() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        parser.set_backup_retention(Some(1));
+        let directory = std::env::temp_dir().join("m3u_parser_backup_retention");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("playlist.m3u");
+        let path = path.to_str().unwrap();
+
+        fs::write(
+            path,
+            "#EXTM3U\n#EXTINF:-1,Good\nhttp://example.com/good.m3u8",
+        )
+        .unwrap();
+        parser.to_file(path, "m3u");
+        let mut names: Vec<String> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0], "playlist.m3u");
+        let backup = directory.join(&names[1]);
+        assert!(fs::read_to_string(backup).unwrap().contains("Good"));
+        assert!(fs::read_to_string(path).unwrap().contains("BBC One"));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_apply_env() {
+        let proxy = serve(|request| {
+            let body = if request.starts_with("GET http://playlist.invalid/") {
+                "#EXTM3U\n#EXTINF:-1,Proxied\nhttp://example.com/proxied.m3u8"
+            } else {
+                ""
+            };
+            http_response("200 OK", "", body)
+        })
+        .await;
+        let vars = std::collections::HashMap::from([
+            ("M3U_PARSER_TIMEOUT", String::from("2.5")),
+            ("M3U_PARSER_FETCH_TIMEOUT", String::from("1e20")),
+            ("M3U_PARSER_USER_AGENT", String::from("curator/1.0")),
+            ("M3U_PARSER_PROXY", proxy),
+        ]);
+        let mut parser = M3uParser::new(None);
+        parser.apply_env_from(|name| vars.get(name).cloned());
+        assert_eq!(parser.check_timeout, Duration::from_millis(2500));
+        assert_eq!(parser.fetch_timeout, Duration::from_secs(60));
+        assert_eq!(parser.useragent, "curator/1.0");
+
+        parser
+            .parse_m3u("http://playlist.invalid/index.m3u", false, true)
+            .await;
+        assert_eq!(parser.streams_info[0].title, "Proxied");
+
+        for value in ["soon", "0", "-1", "inf", "NaN"] {
+            parser.apply_env_from(|name| {
+                (name == "M3U_PARSER_FETCH_TIMEOUT").then(|| value.to_string())
+            });
+            assert_eq!(parser.fetch_timeout, Duration::from_secs(60));
+        }
+    }
+
+    /// Subscriber recording each span of the crate as its name followed by its fields.
+    #[cfg(feature = "tracing")]
+    #[derive(Default)]
+    struct SpanRecorder {
+        spans: std::sync::Mutex<Vec<(String, &'static tracing::Metadata<'static>)>>,
+        /// The IDs of the entered spans, the current span last.
+        entered: std::sync::Mutex<Vec<tracing::span::Id>>,
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+            metadata.target().starts_with("m3u_parser")
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut spans = self.spans.lock().unwrap();
+            let mut text = span.metadata().name().to_string();
+            span.record(
+                &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                    text.push_str(&format!(" {}={:?}", field, value));
+                },
+            );
+            spans.push((text, span.metadata()));
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            let (text, _) = &mut spans[span.into_u64() as usize - 1];
+            values.record(
+                &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                    text.push_str(&format!(" {}={:?}", field, value));
+                },
+            );
+        }
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, span: &tracing::span::Id) {
+            self.entered.lock().unwrap().push(span.clone());
+        }
+
+        fn exit(&self, span: &tracing::span::Id) {
+            let mut entered = self.entered.lock().unwrap();
+            if let Some(position) = entered.iter().rposition(|id| id == span) {
+                entered.remove(position);
+            }
+        }
+
+        fn current_span(&self) -> tracing_core::span::Current {
+            match self.entered.lock().unwrap().last() {
+                Some(id) => {
+                    let metadata = self.spans.lock().unwrap()[id.into_u64() as usize - 1].1;
+                    tracing_core::span::Current::new(id.clone(), metadata)
+                }
+                None => tracing_core::span::Current::none(),
+            }
+        }
+    }
+
+    #[cfg(all(feature = "net", feature = "tracing"))]
+    #[tokio::test]
+    async fn test_tracing_spans() {
+        let url = serve(|_| {
+            http_response(
+                "200 OK",
+                "",
+                "#EXTM3U\n#EXTINF:-1,Missing\n/media/videos/missing.mp4",
+            )
+        })
+        .await;
+        let recorder = Arc::new(SpanRecorder::default());
+        let _guard = tracing::subscriber::set_default(Arc::clone(&recorder));
+        let mut parser = M3uParser::new(None);
+        parser.set_check_local_files(true, false);
+        parser
+            .parse_m3u(&format!("{}/index.m3u", url), true, true)
+            .await;
+
+        let spans: Vec<String> = recorder
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(text, _)| text.clone())
+            .collect();
+        assert_eq!(spans.len(), 3);
+        assert!(spans[0].starts_with(&format!("read_url url=\"{}/index.m3u\"", url)));
+        assert!(spans[0].ends_with(" bytes=52"));
+        assert_eq!(
+            spans[1],
+            "parse_lines kind=IptvExtended entries=1 warnings=0"
+        );
+        assert_eq!(
+            spans[2],
+            "check_stream url=/media/videos/missing.mp4 status=\"BAD\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streams_accessors() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert_eq!(parser.streams().len(), 3);
+        assert!(parser.get_by_url("http://example.com/tf1.m3u8").is_some());
+
+        parser.streams_mut()[1].url = String::from("http://example.com/tf1-hd.m3u8");
+        assert!(parser.get_by_url("http://example.com/tf1.m3u8").is_none());
+        assert!(parser
+            .get_by_url("http://example.com/tf1-hd.m3u8")
+            .is_some());
+        parser.reset_operations();
+        assert_eq!(parser.streams()[1].url, "http://example.com/tf1.m3u8");
+
+        parser.set_streams(vec![]);
+        assert!(parser.streams().is_empty());
+        parser.reset_operations();
+        assert_eq!(parser.streams().len(), 3);
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_apply_config() {
+        let mut config = ParserConfig::default();
+        config.check.timeout_secs = Some(0);
+        config.check.fetch_timeout_secs = Some(10);
+        let mut parser = M3uParser::new(None);
+        parser.apply_config(&config);
+        assert_eq!(parser.check_timeout, Duration::from_secs(5));
+        assert_eq!(parser.fetch_timeout, Duration::from_secs(10));
+
+        config.check.timeout_secs = Some(3);
+        config.check.fetch_timeout_secs = Some(0);
+        parser.apply_config(&config);
+        assert_eq!(parser.check_timeout, Duration::from_secs(3));
+        assert_eq!(parser.fetch_timeout, Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_run_config() {
+        let directory = std::env::temp_dir().join("m3u_parser_run_config");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        let source = directory.join("source.m3u");
+        fs::write(&source, SAMPLE_PLAYLIST).unwrap();
+        let output = directory.join("curated.json");
+
+        let mut config = ParserConfig {
+            sources: vec![source.to_str().unwrap().to_string()],
+            filters: vec![FilterConfig {
+                key: String::from("country-code"),
+                values: vec![String::from("GB"), String::from("FR")],
+                retrieve: true,
+            }],
+            outputs: vec![OutputConfig {
+                path: output.to_str().unwrap().to_string(),
+                format: None,
+            }],
+            ..Default::default()
+        };
+        config.sources.push(String::from("/missing/source.m3u"));
+        let mut parser = M3uParser::new(None);
+        let report = parser.run_config(&config).await.unwrap();
+        assert_eq!(report.entries(), 3);
+        assert!(!report.is_complete());
+        assert_eq!(
+            report.failed().next().unwrap().source,
+            "/missing/source.m3u"
+        );
+        assert_eq!(parser.streams_info.len(), 2);
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        let written = fs::read_to_string(&output).unwrap();
+
+        // Neither an unknown format nor too few entries replace the existing output.
+        config.outputs[0].format = Some(String::from("m3uu"));
+        assert!(parser.run_config(&config).await.is_err());
+        assert_eq!(fs::read_to_string(&output).unwrap(), written);
+        config.outputs[0].format = None;
+        config.min_entries = Some(3);
+        let error = parser.run_config(&config).await.unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&output).unwrap(), written);
+
+        config.min_entries = Some(0);
+        config.backup_retention = Some(1);
+        parser.run_config(&config).await.unwrap();
+        let files = fs::read_dir(&directory).unwrap().count();
+        assert_eq!(files, 3);
+    }
+
+    #[tokio::test]
+    async fn test_min_entries() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        parser.set_min_entries(5);
+        let path = std::env::temp_dir().join("m3u_parser_min_entries.m3u");
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+
+        parser.to_file(path, "m3u");
+        assert!(fs::read_to_string(path).unwrap().contains("BBC One"));
+
+        fs::write(
+            path,
+            "#EXTM3U\n#EXTINF:-1,Good\nhttp://example.com/good.m3u8",
+        )
+        .unwrap();
+        parser.to_file(path, "m3u");
+        assert!(fs::read_to_string(path).unwrap().contains("Good"));
+        let error = parser.write_m3u_streaming(path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+
+        parser.set_force_overwrite(true);
+        parser.to_file(path, "m3u");
+        assert!(fs::read_to_string(path).unwrap().contains("BBC One"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_line_endings() {
+        let content = "\u{feff}#EXTM3U url-tvg=\"http://example.com/epg.xml\"\r\n\
+                       #EXTINF:-1 tvg-id=\"bbcone.uk\" group-title=\"News\",BBC One\r\n\
+                       http://example.com/bbc-one.m3u8\r\n";
+        let mut parser = parse_content(content).await;
+        assert_eq!(parser.playlist_kind(), PlaylistKind::IptvExtended);
+        assert_eq!(parser.streams_info[0].title, "BBC One");
+        assert_eq!(
+            parser.streams_info[0].url,
+            "http://example.com/bbc-one.m3u8"
+        );
+        assert!(parser.warnings().is_empty());
+
+        parser.set_m3u_write_options(M3uWriteOptions {
+            crlf: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            parser.get_raw_m3u_content(),
+            "#EXTM3U url-tvg=\"http://example.com/epg.xml\"\r\n\
+             #EXTINF:-1 tvg-id=\"bbcone.uk\" group-title=\"News\",BBC One\r\n\
+             http://example.com/bbc-one.m3u8"
+        );
+
+        // Classic Mac OS line endings, with a value that would otherwise span lines.
+        let content = "#EXTM3U\r#EXTINF:-1 tvg-id=\"tf1.fr\",TF1\rhttp://example.com/tf1.m3u8\r";
+        let parser = parse_content(content).await;
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].tvg.id, "tf1.fr");
+        assert_eq!(parser.streams_info[0].url, "http://example.com/tf1.m3u8");
+    }
+
+    #[tokio::test]
+    async fn test_to_file_raw() {
+        let content = r#"#EXTM3U url-tvg="http://example.com/epg.xml"
+#EXTINF:-1 tvg-id="bbcone.uk" catchup="default" group-title="News",BBC One
+#EXTVLCOPT:http-user-agent=Mozilla/5.0
+http://example.com/bbc-one.m3u8
+#EXTINF:-1 tvg-id="tf1.fr" group-title="General",TF1
+http://example.com/tf1.m3u8
+"#;
+        let mut parser = parse_content(content).await;
+        assert_eq!(parser.streams_info.len(), 2);
+        parser.streams_info[1].title = String::from("TF1 HD");
+
+        let file_path = std::env::temp_dir().join("to_file_raw_output.m3u");
+        parser.to_file_raw(file_path.to_str().unwrap());
+        let output = fs::read_to_string(&file_path).unwrap();
+        fs::remove_file(&file_path).unwrap();
+
+        assert_eq!(
+            output,
+            r#"#EXTM3U url-tvg="http://example.com/epg.xml"
+#EXTINF:-1 tvg-id="bbcone.uk" catchup="default" group-title="News",BBC One
+#EXTVLCOPT:http-user-agent=Mozilla/5.0
+http://example.com/bbc-one.m3u8
+#EXTINF:-1 tvg-id="tf1.fr" group-title="General",TF1 HD
+http://example.com/tf1.m3u8"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sort_by_original_order() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        let indices: Vec<usize> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.source_index)
+            .collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+
+        parser.sort_by("title", "_", false, false);
+        parser.remove_by_country(vec!["FR"]);
+        parser.sort_by_original_order();
+        let titles: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["BBC One", "Local"]);
+    }
+
+    #[test]
+    fn test_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<M3uParser>();
+        assert_send_sync::<super::PlaylistSnapshot>();
+    }
+
+    #[tokio::test]
+    async fn test_reset_operations() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert!(parser.streams_info_backup.is_none());
+
+        parser.remove_by_country(vec!["FR"]);
+        parser.sort_by("title", "_", false, false);
+        assert_eq!(parser.streams_info.len(), 2);
+
+        parser.reset_operations();
+        assert_eq!(parser.streams_info.len(), 3);
+        assert_eq!(parser.streams_info[1].title, "TF1");
+        assert!(parser.streams_info_backup.is_none());
+
+        parser.retrieve_by_country(vec!["GB"]);
+        assert_eq!(parser.streams_info.len(), 1);
+        parser.reset_operations();
+        assert_eq!(parser.streams_info.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_multiple_categories() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 group-title="Sports;HD;UK",Sky Sports
+http://example.com/sky-sports.m3u8
+#EXTINF:-1 group-title="News",BBC News
+http://example.com/bbc-news.m3u8
+"#;
+        let mut parser = parse_content(content).await;
+        assert_eq!(parser.streams_info[0].category, "Sports");
+        assert_eq!(
+            parser.streams_info[0].categories,
+            vec!["Sports", "HD", "UK"]
+        );
+        assert!(parser
+            .get_m3u_content()
+            .contains(r#"group-title="Sports;HD;UK""#));
+
+        parser.retrieve_by_category(vec!["^UK$"]);
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "Sky Sports");
+    }
+
+    #[tokio::test]
+    async fn test_audio_metadata() {
+        let content = r#"#EXTM3U
+#EXTINF:215,Daft Punk - One More Time
+#EXTALB:Discovery
+#EXTART:Daft Punk
+#EXTIMG:/music/discovery.jpg
+/music/one-more-time.mp3
+#EXTINF:-1,Radio
+http://example.com/radio.mp3
+"#;
+        let parser = parse_content(content).await;
+        let track = &parser.streams_info[0];
+        assert_eq!(track.duration, Some(215.0));
+        assert_eq!(track.album.as_deref(), Some("Discovery"));
+        assert_eq!(track.artist.as_deref(), Some("Daft Punk"));
+        assert_eq!(track.image.as_deref(), Some("/music/discovery.jpg"));
+        assert_eq!(parser.streams_info[1].duration, None);
+        assert_eq!(parser.streams_info[1].album, None);
+
+        assert_eq!(
+            parser.get_m3u_content(),
+            r#"#EXTM3U
+#EXTINF:215,Daft Punk - One More Time
+#EXTALB:Discovery
+#EXTART:Daft Punk
+#EXTIMG:/music/discovery.jpg
+/music/one-more-time.mp3
+#EXTINF:-1,Radio
+http://example.com/radio.mp3"#
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_check_local_files() {
+        let directory = std::env::temp_dir().join("m3u_parser_check_local_files");
+        fs::create_dir_all(&directory).unwrap();
+        let existing = directory.join("existing.mp3");
+        let empty = directory.join("empty.mp3");
+        fs::write(&existing, b"ID3").unwrap();
+        fs::write(&empty, b"").unwrap();
+        let missing = directory.join("missing.mp3");
+
+        let content = format!(
+            "#EXTM3U\n#EXTINF:-1,Existing\n{}\n#EXTINF:-1,Empty\n{}\n#EXTINF:-1,Missing\n{}\n",
+            existing.display(),
+            empty.display(),
+            missing.display()
+        );
+        let path = directory.join("playlist.m3u");
+        fs::write(&path, content).unwrap();
+
+        let mut parser = M3uParser::new(None);
+        parser.set_check_local_files(true, true);
+        parser.parse_m3u(path.to_str().unwrap(), true, true).await;
+        fs::remove_dir_all(&directory).unwrap();
+
+        let statuses: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.status.as_str())
+            .collect();
+        assert_eq!(statuses, vec!["GOOD", "BAD", "BAD"]);
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_stream_checker() {
+        struct MockChecker;
+
+        #[async_trait::async_trait]
+        impl StreamChecker for MockChecker {
+            async fn is_available(&self, url: &str) -> bool {
+                url.contains("bbc")
+            }
+        }
+
+        let mut parser = M3uParser::new(None);
+        parser.set_stream_checker(MockChecker);
+        parser.parse_m3u_content(SAMPLE_PLAYLIST, true, true).await;
+        let statuses: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.status.as_str())
+            .collect();
+        assert_eq!(statuses, vec!["GOOD", "BAD", "GOOD"]);
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_timeouts() {
+        struct SlowChecker;
+
+        #[async_trait::async_trait]
+        impl StreamChecker for SlowChecker {
+            async fn is_available(&self, _url: &str) -> bool {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                true
+            }
+        }
+
+        let mut parser = M3uParser::new(Some(Duration::from_millis(100)));
+        parser.set_stream_checker(SlowChecker);
+        parser.parse_m3u_content(SAMPLE_PLAYLIST, true, true).await;
+        let check = parser.streams_info[0].check.as_ref().unwrap();
+        assert_eq!(parser.streams_info[0].status, "BAD");
+        assert_eq!(check.error.as_deref(), Some("Check timed out"));
+
+        // A server accepting the connection but never answering.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/playlist.m3u", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let _connection = listener.accept().await;
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+        let mut parser = M3uParser::new(None);
+        parser.set_fetch_timeout(Duration::from_millis(200));
+        let started = std::time::Instant::now();
+        parser.parse_m3u(&url, false, true).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(parser.streams_info.is_empty());
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_redirects() {
+        let base = serve(|request| {
+            if request.starts_with("GET /redirect ") {
+                http_response("302 Found", "Location: /final\r\n", "")
+            } else {
+                http_response("200 OK", "", "#EXTM3U")
+            }
+        })
+        .await;
+        let content = format!("#EXTM3U\n#EXTINF:-1,Redirected\n{}/redirect\n", base);
+
+        let mut parser = M3uParser::new(None);
+        parser.set_record_resolved_url(true);
+        parser.parse_m3u_content(&content, true, true).await;
+        let info = &parser.streams_info[0];
+        assert_eq!(info.status, "GOOD");
+        assert_eq!(info.resolved_url, Some(format!("{}/final", base)));
+
+        let mut parser = M3uParser::new(None);
+        parser.set_redirect_policy(RedirectPolicy::None);
+        parser.parse_m3u_content(&content, true, true).await;
+        let info = &parser.streams_info[0];
+        assert_eq!(info.status, "BAD");
+        assert_eq!(info.check.as_ref().unwrap().http_status, Some(302));
+        assert_eq!(info.resolved_url, None);
+    }
+
+    #[tokio::test]
+    async fn test_playlist_kind() {
+        let parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert_eq!(parser.playlist_kind(), PlaylistKind::IptvExtended);
+
+        let parser = parse_content(
+            "#EXTM3U\n#EXT-X-VERSION:3\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=1280000,RESOLUTION=1280x720\nhigh/index.m3u8\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=640000\nlow/index.m3u8\n",
+        )
+        .await;
+        assert_eq!(parser.playlist_kind(), PlaylistKind::HlsMaster);
+        let variants: Vec<(&str, &str)> = parser
+            .streams_info
+            .iter()
+            .map(|info| (info.title.as_str(), info.url.as_str()))
+            .collect();
+        assert_eq!(
+            variants,
+            vec![
+                ("1280x720", "high/index.m3u8"),
+                ("640000 bps", "low/index.m3u8")
+            ]
+        );
+        assert!(parser.warnings().is_empty());
+
+        let file_path = std::env::temp_dir().join("m3u_parser_live.m3u8");
+        fs::write(
+            &file_path,
+            "#EXTM3U\n#EXT-X-TARGETDURATION:10\n#EXT-X-MEDIA-SEQUENCE:42\n\
+             #EXTINF:9.9,\nsegment42.ts\n#EXTINF:9.9,\nsegment43.ts\n",
+        )
+        .unwrap();
+        let mut parser = M3uParser::new(None);
+        parser
+            .parse_m3u(file_path.to_str().unwrap(), false, true)
+            .await;
+        fs::remove_file(&file_path).unwrap();
+        assert_eq!(parser.playlist_kind(), PlaylistKind::HlsMedia);
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "m3u_parser_live");
+        assert_eq!(parser.streams_info[0].duration, None);
+        assert_eq!(parser.streams_info[0].url, file_path.to_str().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_plain_m3u() {
+        let parser = parse_content(
+            "# Morning mix\nhttp://example.com/music/Track%201.mp3\n\n\
+             /media/podcasts/episode-12.ogg\nhttp://radio.example.com/\nnot a stream\n",
+        )
+        .await;
+        assert_eq!(parser.playlist_kind(), PlaylistKind::PlainM3u);
+        let entries: Vec<(&str, &str)> = parser
+            .streams_info
+            .iter()
+            .map(|info| (info.title.as_str(), info.url.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("Track%201", "http://example.com/music/Track%201.mp3"),
+                ("episode-12", "/media/podcasts/episode-12.ogg"),
+                ("http://radio.example.com/", "http://radio.example.com/"),
+            ]
+        );
+        assert_eq!(parser.streams_info[1].line_number, 4);
+        assert_eq!(parser.warnings().len(), 1);
+        assert_eq!(
+            parser.warnings()[0].reason,
+            ParseWarningReason::UnrecognizedLine
+        );
+        assert_eq!(
+            parser.get_m3u_content(),
+            "#EXTM3U\n#EXTINF:-1,Track%201\nhttp://example.com/music/Track%201.mp3\n\
+             #EXTINF:-1,episode-12\n/media/podcasts/episode-12.ogg\n\
+             #EXTINF:-1,http://radio.example.com/\nhttp://radio.example.com/"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_source() {
+        let path = std::env::temp_dir().join("m3u_parser_source.m3u");
+        fs::write(&path, SAMPLE_PLAYLIST).unwrap();
+        let path = path.to_string_lossy().to_string();
+        let mut parser = M3uParser::new(None);
+        parser.parse_m3u(&path, false, true).await;
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(parser.streams_info.len(), 3);
+        assert!(parser
+            .streams_info
+            .iter()
+            .all(|info| info.source.as_deref() == Some(path.as_str())));
+        let json: serde_json::Value =
+            serde_json::from_str(&parser.get_json(false).unwrap()).unwrap();
+        assert_eq!(json[0]["source"], path.as_str());
+
+        let parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert!(parser.streams_info[0].source.is_none());
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_checked_entries_keep_source_order() {
+        struct ReverseChecker;
+
+        // The first streams take the longest to check, so the checks finish in reverse order.
+        #[async_trait::async_trait]
+        impl StreamChecker for ReverseChecker {
+            async fn is_available(&self, url: &str) -> bool {
+                let number: u64 = url
+                    .trim_start_matches("http://example.com/")
+                    .trim_end_matches(".m3u8")
+                    .parse()
+                    .unwrap();
+                tokio::time::sleep(Duration::from_millis((20 - number) * 10)).await;
+                number.is_multiple_of(2)
+            }
+        }
+
+        let mut content = String::from("#EXTM3U\n");
+        for number in 0..20 {
+            content.push_str(&format!(
+                "#EXTINF:-1,Channel {}\nhttp://example.com/{}.m3u8\n",
+                number, number
+            ));
+        }
+        let mut parser = M3uParser::new(None);
+        parser.set_stream_checker(ReverseChecker);
+        parser.parse_m3u_content(&content, true, true).await;
+        parser.check_streams().await;
+
+        for (position, info) in parser.streams_info.iter().enumerate() {
+            assert_eq!(info.title, format!("Channel {}", position));
+            assert_eq!(info.source_index, position);
+            let status = if position % 2 == 0 { "GOOD" } else { "BAD" };
+            assert_eq!(info.status, status);
+        }
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_check_live_stream() {
+        use futures::StreamExt;
+
+        struct MockChecker;
+
+        #[async_trait::async_trait]
+        impl StreamChecker for MockChecker {
+            async fn is_available(&self, url: &str) -> bool {
+                if url.contains("tf1") {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                }
+                url.contains("bbc") || url.contains("tf1")
+            }
+        }
+
+        let mut parser = M3uParser::new(None);
+        parser.set_stream_checker(MockChecker);
+        parser.parse_m3u_content(SAMPLE_PLAYLIST, false, true).await;
+        let results: Vec<(usize, CheckResult)> = parser.check_live_stream().collect().await;
+        let positions: Vec<(usize, bool)> = results
+            .iter()
+            .map(|(position, check)| (*position, check.available))
+            .collect();
+        assert_eq!(positions, vec![(0, true), (1, true)]);
+        assert!(parser.streams_info[0].check.is_none());
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_geo_blocked() {
+        let base = serve(|request| {
+            if request.starts_with("GET /legal ") {
+                http_response("451 Unavailable For Legal Reasons", "", "")
+            } else if request.starts_with("GET /geo ") {
+                http_response("403 Forbidden", "X-Geo-Blocked: true\r\n", "")
+            } else if request.starts_with("GET /bucket ") {
+                http_response("403 Forbidden", "x-amz-bucket-region: eu-west-1\r\n", "")
+            } else {
+                http_response("403 Forbidden", "", "")
+            }
+        })
+        .await;
+        let content = format!(
+            "#EXTM3U\n#EXTINF:-1,Legal\n{}/legal\n#EXTINF:-1,Geo\n{}/geo\n\
+             #EXTINF:-1,Forbidden\n{}/forbidden\n#EXTINF:-1,Bucket\n{}/bucket\n",
+            base, base, base, base
+        );
+        let mut parser = M3uParser::new(None);
+        parser.parse_m3u_content(&content, true, true).await;
+        let kinds: Vec<Option<CheckErrorKind>> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.check.as_ref().unwrap().error_kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Some(CheckErrorKind::GeoBlocked),
+                Some(CheckErrorKind::GeoBlocked),
+                Some(CheckErrorKind::HttpStatus),
+                Some(CheckErrorKind::HttpStatus)
+            ]
+        );
+        assert!(parser.streams_info.iter().all(|info| info.status == "BAD"));
+        assert_eq!(parser.stats().check_errors[&CheckErrorKind::GeoBlocked], 2);
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_measure_bitrate() {
+        let base = serve(|request| {
+            let body = if request.starts_with("GET /master.m3u8 ") {
+                String::from("#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=800000\nmedia.m3u8\n")
+            } else if request.starts_with("GET /media.m3u8 ") {
+                String::from("#EXTM3U\n#EXTINF:4,\nseg1.ts\n#EXTINF:4,\nseg2.ts\n")
+            } else {
+                "x".repeat(20000)
+            };
+            http_response("200 OK", "", &body)
+        })
+        .await;
+        let content = format!(
+            "#EXTM3U\n#EXTINF:-1,HLS\n{}/master.m3u8\n#EXTINF:-1,TS\n{}/video.ts\n\
+             #EXTINF:-1,Down\n{}/down.ts\n",
+            base, base, base
+        );
+        let mut parser = parse_content(&content).await;
+        parser.streams_info[0].status = String::from("GOOD");
+        parser.streams_info[1].status = String::from("GOOD");
+        parser.measure_bitrate(Duration::from_millis(500), 2).await;
+
+        let bitrate = |position: usize| {
+            let check = parser.streams_info[position].check.as_ref();
+            check.and_then(|check| check.bitrate_kbps)
+        };
+        assert!(bitrate(0).unwrap() > 0);
+        assert!(bitrate(1).unwrap() > 0);
+        assert_eq!(bitrate(2), None);
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_fetch_all() {
+        let base = serve(|request| {
+            let body = if request.starts_with("GET /a.m3u ") {
+                "#EXTM3U\n#EXTINF:-1,A1\nhttp://example.com/a1.m3u8\n\
+                 #EXTINF:-1,A2\nhttp://example.com/a2.m3u8\n"
+            } else if request.starts_with("GET /bad.m3u ") {
+                "#EXTINF:-1,Bad\nhttp://example.com/bad.m3u8\n"
+            } else {
+                "#EXTM3U\n#EXTINF:-1,B1\nhttp://example.com/b1.m3u8\n"
+            };
+            http_response("200 OK", "", body)
+        })
+        .await;
+        let first = format!("{}/a.m3u", base);
+        let second = format!("{}/b.m3u", base);
+        let totals = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let complete_totals = std::sync::Arc::clone(&totals);
+        let mut parser = M3uParser::new(None);
+        parser.on_complete(move |stats| complete_totals.lock().unwrap().push(stats.total));
+        let report = parser
+            .fetch_all(&[&first, "/missing/c.m3u", &second], 1, false, true)
+            .await;
+        assert_eq!(*totals.lock().unwrap(), vec![3]);
+        let outcomes: Vec<(usize, bool)> = report
+            .sources
+            .iter()
+            .map(|source| (source.entries, source.is_ok()))
+            .collect();
+        assert_eq!(outcomes, vec![(2, true), (0, false), (1, true)]);
+        assert_eq!(report.succeeded().count(), 2);
+
+        let entries: Vec<(&str, Option<&str>)> = parser
+            .streams_info
+            .iter()
+            .map(|info| (info.title.as_str(), info.source.as_deref()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("A1", Some(first.as_str())),
+                ("A2", Some(first.as_str())),
+                ("B1", Some(second.as_str())),
+            ]
+        );
+
+        parser.filter_by("source", vec!["/b\\.m3u$"], "-", true, false);
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "B1");
+
+        parser.reset_operations();
+        parser.sort_by("title", "-", false, false);
+        parser.sort_by_original_order();
+        let titles: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["A1", "A2", "B1"]);
+
+        parser.set_strict(true);
+        let bad = format!("{}/bad.m3u", base);
+        let report = parser.fetch_all(&[&bad, &second], 1, false, true).await;
+        assert!(report.sources[0].error.is_some());
+        assert!(report.sources[1].is_ok());
+        assert!(parser.parse_error().is_none());
+        assert_eq!(parser.streams_info.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_to_file_append() {
+        let parser = parse_content(SAMPLE_PLAYLIST).await;
+        let path = std::env::temp_dir().join("m3u_parser_append.m3u");
+        let path = path.to_string_lossy().to_string();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(parser.to_file_append(&path).unwrap(), 3);
+        assert_eq!(fs::read_to_string(&path).unwrap(), parser.get_m3u_content());
+        assert_eq!(parser.to_file_append(&path).unwrap(), 3);
+        let content = parser.get_m3u_content();
+        let entries = content.strip_prefix("#EXTM3U\n").unwrap();
+        assert_eq!(
+            fs::read(&path).unwrap(),
+            format!("{}\n{}", content, entries).into_bytes()
+        );
+        let appended = parse_content(&fs::read_to_string(&path).unwrap()).await;
+        assert_eq!(appended.streams_info.len(), 6);
+        assert!(appended.warnings().is_empty());
+
+        fs::write(&path, "#EXTM3U\n").unwrap();
+        parser.to_file_append(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), parser.get_m3u_content());
+
+        fs::write(&path, " \n\n").unwrap();
+        parser.to_file_append(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), parser.get_m3u_content());
+
+        let mut numbered = parse_content(SAMPLE_PLAYLIST).await;
+        numbered.set_m3u_write_options(M3uWriteOptions::media_server());
+        numbered.to_file_append(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("tvg-chno=\"1\""));
+        assert!(content.contains("tvg-chno=\"4\""));
+        assert!(content.contains("tvg-chno=\"6\""));
+
+        fs::write(&path, "title,url\n").unwrap();
+        assert!(parser.to_file_append(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_streaming_writers() {
+        let parser = parse_content(SAMPLE_PLAYLIST).await;
+        let path = std::env::temp_dir().join("m3u_parser_streaming.m3u");
+        let path = path.to_string_lossy().to_string();
+        parser.write_m3u_streaming(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), parser.get_m3u_content());
+
+        let options = JsonOptions {
+            pretty: true,
+            ..Default::default()
+        };
+        parser.write_json_streaming(&path, &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            parser.get_json_with_options(&options).unwrap()
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_m3u_write_options() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        let hash = parser.content_hash();
+        parser.set_m3u_write_options(M3uWriteOptions {
+            attributes: vec![M3uAttribute::GroupTitle, M3uAttribute::TvgName],
+            header_attributes: vec![(
+                String::from("url-tvg"),
+                String::from("http://example.com/epg.xml"),
+            )],
+            crlf: true,
+            group_directive: true,
+            ..Default::default()
+        });
+        let mut output = vec![];
+        parser.to_writer(&mut output, "m3u").unwrap();
+        let content = String::from_utf8(output).unwrap();
+        assert!(content.starts_with(
+            "#EXTM3U url-tvg=\"http://example.com/epg.xml\"\r\n\
+             #EXTINF:-1 tvg-name=\"BBC One\",BBC One\r\n#EXTGRP:News\r\n"
+        ));
+        assert!(!content.contains("tvg-id"));
+        assert_eq!(parser.content_hash(), hash);
+
+        let reparsed = parse_content(&content).await;
+        assert_eq!(reparsed.streams_info[0].category, "News");
+        assert_eq!(reparsed.streams_info[0].title, "BBC One");
+    }
+
+    #[tokio::test]
+    async fn test_export_favorites() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert_eq!(parser.mark_favorite(|info| info.country.code != "FR"), 2);
+        assert_eq!(parser.mark_favorite(|info| info.title == "Local"), 0);
+
+        let path = std::env::temp_dir().join("m3u_parser_favorites.m3u");
+        let path = path.to_string_lossy().to_string();
+        assert_eq!(parser.export_favorites(&path).unwrap(), 2);
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(parser.streams_info.len(), 3);
+
+        let favorites = parse_content(&content).await;
+        let titles: Vec<&str> = favorites
+            .streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["BBC One", "Local"]);
+        assert!(favorites.streams_info[0].tags.contains("favorite"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_blocklist() {
+        let mut blocklist = Blocklist {
+            keywords: vec![String::from("tf1")],
+            categories: vec![String::from("news")],
+            ..Default::default()
+        };
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert_eq!(parser.apply_blocklist(&blocklist), 2);
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(parser.streams_info[0].title, "Local");
+
+        parser.reset_operations();
+        blocklist.tag = Some(String::from("blocked"));
+        assert_eq!(parser.apply_blocklist(&blocklist), 2);
+        assert_eq!(parser.streams_info.len(), 3);
+        assert!(parser.streams_info[1].tags.contains("blocked"));
+        assert!(parser.streams_info[2].tags.is_empty());
+    }
+
+    struct StaticFetch;
+
+    #[async_trait::async_trait]
+    impl HttpFetch for StaticFetch {
+        async fn fetch(
+            &self,
+            url: &str,
+        ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            match url {
+                "http://example.com/playlist.m3u" => Ok(SAMPLE_PLAYLIST.to_string()),
+                _ => Err(format!("Not found: {}", url).into()),
+            }
+        }
+    }
 
-        println!("Saving to file: {}", filename);
-        match format {
-            "json" => {
-                let content = self.get_json(true).unwrap();
-                self.save_file(filename.as_str(), content.as_bytes());
+    struct IptvOrgFetch;
+
+    #[async_trait::async_trait]
+    impl HttpFetch for IptvOrgFetch {
+        async fn fetch(
+            &self,
+            url: &str,
+        ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            let content = match url.strip_prefix("http://api.example.com/") {
+                Some("channels.json") => {
+                    r#"[{"id": "BBCOne.uk", "name": "BBC One", "country": "UK",
+                         "categories": ["general"], "languages": ["eng"]}]"#
+                }
+                Some("streams.json") => {
+                    r#"[{"channel": "BBCOne.uk", "url": "http://example.com/bbc.m3u8"}]"#
+                }
+                Some("languages.json") => r#"[{"code": "eng", "name": "English"}]"#,
+                Some("guides.json" | "countries.json") => "[]",
+                _ => return Err(format!("Not found: {}", url).into()),
+            };
+            Ok(content.to_string())
+        }
+    }
+
+    #[test]
+    fn test_iptv_org() {
+        let mut parser = M3uParser::new(None);
+        parser.set_http_fetch(IptvOrgFetch);
+        let options = ApiOptions {
+            base_url: String::from("http://api.example.com/"),
+            ..Default::default()
+        };
+        let titles = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let entry_titles = std::sync::Arc::clone(&titles);
+        parser.on_entry(move |info| entry_titles.lock().unwrap().push(info.title.clone()));
+        futures::executor::block_on(parser.load_iptv_org(&options)).unwrap();
+        assert_eq!(parser.streams_info.len(), 1);
+        assert_eq!(*titles.lock().unwrap(), vec!["BBC One"]);
+        let info = &parser.streams_info[0];
+        assert_eq!(info.title, "BBC One");
+        assert_eq!(info.language.name, "English");
+        assert_eq!(info.status, "BAD");
+        assert_eq!(info.source.as_deref(), Some("http://api.example.com/"));
+
+        let options = ApiOptions {
+            base_url: String::from("http://missing.example.com"),
+            ..Default::default()
+        };
+        assert!(futures::executor::block_on(parser.load_iptv_org(&options)).is_err());
+        assert_eq!(parser.streams_info.len(), 1);
+    }
+
+    #[test]
+    fn test_http_fetch() {
+        let mut parser = M3uParser::new(None);
+        parser.set_http_fetch(StaticFetch);
+        // Downloading with a custom fetcher does not need a tokio runtime.
+        futures::executor::block_on(parser.parse_m3u(
+            "http://example.com/playlist.m3u",
+            false,
+            true,
+        ));
+        assert_eq!(parser.streams_info.len(), 3);
+        assert_eq!(
+            parser.streams_info[0].source.as_deref(),
+            Some("http://example.com/playlist.m3u")
+        );
+
+        futures::executor::block_on(parser.parse_m3u(
+            "http://example.com/missing.m3u",
+            false,
+            true,
+        ));
+        assert_eq!(parser.streams_info.len(), 3);
+    }
+
+    #[test]
+    fn test_info_from_extinf() {
+        let info = Info::from_extinf(
+            r#"#EXTINF:-1 tvg-id="bbcone.uk" tvg-country="GB" group-title="News;UK",BBC One"#,
+            " http://example.com/bbc.m3u8 ",
+        )
+        .unwrap();
+        assert_eq!(info.title, "BBC One");
+        assert_eq!(info.tvg.id, "bbcone.uk");
+        assert_eq!(info.country.code, "GB");
+        assert_eq!(info.categories, vec!["News", "UK"]);
+        assert_eq!(info.url, "http://example.com/bbc.m3u8");
+        assert_eq!(info.status, "BAD");
+        assert_eq!(info.duration, None);
+
+        let info = Info::from_extinf("#EXTINF:120,Local", "/media/videos/local.mp4").unwrap();
+        assert_eq!(info.status, "GOOD");
+        assert_eq!(info.duration, Some(120.0));
+
+        assert!(Info::from_extinf("#EXTVLCOPT:x", "http://example.com/bbc.m3u8").is_err());
+        assert!(Info::from_extinf("#EXTINF:-1,BBC One", "not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tags() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        assert_eq!(
+            parser.tag_where(|info| info.category == "News", "favorite"),
+            1
+        );
+        assert_eq!(parser.tag_where(|_| true, "favorite"), 2);
+        assert_eq!(parser.tag_where(|info| info.title == "Local", "kids"), 1);
+        assert_eq!(
+            parser.untag_where(|info| info.title == "TF1", "favorite"),
+            1
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&parser.get_json(false).unwrap()).unwrap();
+        assert_eq!(json[2]["tags"], serde_json::json!(["favorite", "kids"]));
+        let content = parser.get_m3u_content();
+        assert!(content.contains(r#"group-title="News" x-tags="favorite",BBC One"#));
+
+        let mut reparsed = parse_content(&content).await;
+        assert!(reparsed.streams_info[2].tags.contains("kids"));
+        reparsed.filter_by_tag("favorite");
+        let titles: Vec<&str> = reparsed
+            .streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["BBC One", "Local"]);
+    }
+
+    #[tokio::test]
+    async fn test_named_snapshots() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        parser.save_snapshot("parsed");
+        parser.filter_by("country-code", vec!["FR"], "-", false, true);
+        parser.save_snapshot("filtered");
+        parser.truncate(1);
+        parser.save_snapshot("parsed");
+        assert_eq!(parser.list_snapshots(), vec!["parsed", "filtered"]);
+
+        assert!(parser.restore_snapshot("filtered"));
+        let titles: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["BBC One", "Local"]);
+        assert!(parser.restore_snapshot("parsed"));
+        assert_eq!(parser.streams_info.len(), 1);
+        assert!(!parser.restore_snapshot("missing"));
+
+        parser.reset_operations();
+        assert_eq!(parser.streams_info.len(), 3);
+        assert!(parser.remove_snapshot("parsed"));
+        assert!(!parser.remove_snapshot("parsed"));
+        assert_eq!(parser.list_snapshots(), vec!["filtered"]);
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_check_error_kinds() {
+        let base = serve(|request| {
+            if request.starts_with("GET /live.m3u8 ") {
+                http_response("200 OK", "", "")
+            } else {
+                http_response("404 Not Found", "", "")
             }
-            "m3u" => {
-                let content = self.get_m3u_content();
-                self.save_file(filename.as_str(), content.as_bytes());
+        })
+        .await;
+        let refused = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/refused.m3u8", listener.local_addr().unwrap())
+        };
+        let content = format!(
+            "#EXTM3U\n#EXTINF:-1,Live\n{base}/live.m3u8\n#EXTINF:-1,Gone\n{base}/gone.m3u8\n\
+             #EXTINF:-1,Refused\n{refused}\n#EXTINF:-1,Unknown\nhttp://stream.invalid/a.m3u8\n\
+             #EXTINF:-1,Local\n/media/videos/local.mp4\n"
+        );
+        let mut parser = M3uParser::new(None);
+        parser.parse_m3u_content(&content, true, true).await;
+
+        let kinds: Vec<Option<CheckErrorKind>> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.check.as_ref().and_then(|check| check.error_kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                None,
+                Some(CheckErrorKind::HttpStatus),
+                Some(CheckErrorKind::ConnectionRefused),
+                Some(CheckErrorKind::Dns),
+                None,
+            ]
+        );
+
+        let stats = parser.stats();
+        assert_eq!((stats.total, stats.good, stats.bad), (5, 2, 3));
+        assert_eq!(stats.checked, 4);
+        assert_eq!(stats.check_errors.len(), 3);
+        assert_eq!(stats.check_errors[&CheckErrorKind::Dns], 1);
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_acestream_engine() {
+        let engine = serve(|request| {
+            let body = if request.starts_with("GET /webui/api/service?method=get_version ") {
+                r#"{"result": {"version": "3.1.75"}, "error": null}"#
+            } else if request.starts_with("GET /ace/getstream?id=live&") {
+                r#"{"response": {"command_url": "http://127.0.0.1:1/cmd"}, "error": null}"#
+            } else {
+                r#"{"response": null, "error": "failed to load content"}"#
+            };
+            http_response("200 OK", "", body)
+        })
+        .await;
+        let content = "#EXTM3U\n#EXTINF:-1,Live\nacestream://live\n\
+                       #EXTINF:-1,Dead\nacestream://dead\n";
+
+        let mut parser = M3uParser::new(None);
+        parser.parse_m3u_content(content, true, true).await;
+        assert!(parser.streams_info.iter().all(|info| info.status == "GOOD"));
+
+        parser.set_acestream_engine(Some(&engine));
+        parser.parse_m3u_content(content, true, true).await;
+        let statuses: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.status.as_str())
+            .collect();
+        assert_eq!(statuses, vec!["GOOD", "BAD"]);
+        let check = parser.streams_info[1].check.as_ref().unwrap();
+        assert_eq!(check.error.as_deref(), Some("failed to load content"));
+
+        parser.set_acestream_engine(Some("http://127.0.0.1:1"));
+        parser.parse_m3u_content(content, true, true).await;
+        assert!(parser.streams_info.iter().all(|info| info.status == "BAD"));
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_parse_hls_media() {
+        let base = serve(|_| {
+            http_response(
+                "200 OK",
+                "",
+                "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXTINF:6.0,\nseg1.ts\n\
+                 #EXTINF:4.0,\nseg2.ts\n#EXT-X-ENDLIST\n",
+            )
+        })
+        .await;
+        let parser = M3uParser::new(None);
+        let playlist = parser
+            .parse_hls_media(&format!("{}/vod.m3u8", base))
+            .await
+            .unwrap();
+        assert_eq!(playlist.target_duration, Some(6));
+        assert_eq!(playlist.segment_count(), 2);
+        assert!(!playlist.is_live());
+        assert_eq!(playlist.total_duration(), 10.0);
+        assert!(parser.parse_hls_media("/missing/vod.m3u8").await.is_err());
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_resolve_hls_variant() {
+        let base = serve(|request| {
+            if request.starts_with("GET /master.m3u8 ") {
+                http_response(
+                    "200 OK",
+                    "",
+                    "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=2560000,RESOLUTION=1280x720\nmid.m3u8\n\
+                     #EXT-X-STREAM-INF:BANDWIDTH=640000,RESOLUTION=640x360\n/low/index.m3u8\n",
+                )
+            } else {
+                http_response(
+                    "200 OK",
+                    "",
+                    "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXTINF:6.0,\nseg.ts\n",
+                )
             }
-            _ => eprintln!("Unrecognised format!!!"),
+        })
+        .await;
+        let content = format!(
+            "#EXTM3U\n#EXTINF:-1,Master\n{base}/master.m3u8\n\
+             #EXTINF:-1,Media\n{base}/media.m3u8\n#EXTINF:-1,Radio\n{base}/radio.mp3\n"
+        );
+        let mut parser = parse_content(&content).await;
+
+        assert_eq!(
+            parser
+                .resolve_hls_variant(VariantStrategy::LowestBandwidth)
+                .await,
+            1
+        );
+        assert_eq!(
+            parser.streams_info[0].url,
+            format!("{}/low/index.m3u8", base)
+        );
+        assert_eq!(parser.streams_info[1].url, format!("{}/media.m3u8", base));
+
+        parser.reset_operations();
+        parser
+            .resolve_hls_variant(VariantStrategy::Closest(1920, 1080))
+            .await;
+        assert_eq!(parser.streams_info[0].url, format!("{}/mid.m3u8", base));
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_entry_headers() {
+        let base = serve(|request| {
+            let request = request.to_lowercase();
+            if request.contains("user-agent: vlc/3.0")
+                && request.contains("referer: http://example.com/")
+            {
+                http_response("200 OK", "", "")
+            } else {
+                http_response("403 Forbidden", "", "")
+            }
+        })
+        .await;
+        let content = format!(
+            "#EXTM3U\n#EXTINF:-1,Protected\n#EXTVLCOPT:http-user-agent=VLC/3.0\n\
+             #EXTVLCOPT:http-referrer=http://example.com/\n{base}/protected.m3u8\n\
+             #EXTINF:-1,Plain\n{base}/plain.m3u8\n"
+        );
+
+        let mut parser = M3uParser::new(None);
+        parser.parse_m3u_content(&content, true, true).await;
+        let info = &parser.streams_info[0];
+        assert_eq!(info.user_agent.as_deref(), Some("VLC/3.0"));
+        assert_eq!(info.referrer.as_deref(), Some("http://example.com/"));
+        assert_eq!(info.status, "GOOD");
+        assert_eq!(parser.streams_info[1].status, "BAD");
+        assert!(parser
+            .get_m3u_content()
+            .contains("Protected\n#EXTVLCOPT:http-user-agent=VLC/3.0\n"));
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_tls_options() {
+        let mut parser = M3uParser::new(None);
+        assert!(parser.add_root_certificate(b"not a certificate").is_err());
+        parser.set_danger_accept_invalid_certs(true);
+        assert!(parser.http_client(Duration::from_secs(1)).is_ok());
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_cookie_store() {
+        let base = serve(|request| {
+            if request.starts_with("GET /playlist.m3u ") {
+                http_response("200 OK", "Set-Cookie: session=abc; Path=/\r\n", "#EXTM3U\n")
+            } else if request.contains("session=abc") {
+                http_response("200 OK", "", "")
+            } else {
+                http_response("403 Forbidden", "", "")
+            }
+        })
+        .await;
+        let content = format!("#EXTM3U\n#EXTINF:-1,Session\n{}/stream.m3u8\n", base);
+        let playlist = format!("{}/playlist.m3u", base);
+
+        let mut parser = M3uParser::new(None);
+        parser.parse_m3u(&playlist, false, true).await;
+        parser.parse_m3u_content(&content, true, true).await;
+        assert_eq!(parser.streams_info[0].status, "BAD");
+
+        parser.set_cookie_store(true);
+        parser.parse_m3u(&playlist, false, true).await;
+        parser.parse_m3u_content(&content, true, true).await;
+        assert_eq!(parser.streams_info[0].status, "GOOD");
+
+        let mut parser = M3uParser::new(None);
+        parser.add_cookie("session=abc", &base).unwrap();
+        parser.parse_m3u_content(&content, true, true).await;
+        assert_eq!(parser.streams_info[0].status, "GOOD");
+        assert!(parser.add_cookie("session=abc", "not a url").is_err());
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_liveness_cache() {
+        struct CountingChecker(Arc<AtomicUsize>);
+
+        #[async_trait::async_trait]
+        impl StreamChecker for CountingChecker {
+            async fn is_available(&self, _url: &str) -> bool {
+                self.0.fetch_add(1, AtomicOrdering::SeqCst);
+                true
+            }
+        }
+
+        let cache = std::env::temp_dir().join("m3u_parser_parser_liveness_cache.json");
+        let _ = fs::remove_file(&cache);
+        let calls = Arc::new(AtomicUsize::new(0));
+        for _ in 0..2 {
+            let mut parser = M3uParser::new(None);
+            parser.set_stream_checker(CountingChecker(Arc::clone(&calls)));
+            parser.set_liveness_cache(cache.to_str(), Duration::from_secs(3600));
+            parser.parse_m3u_content(SAMPLE_PLAYLIST, true, true).await;
+            assert!(parser.streams_info.iter().all(|info| info.status == "GOOD"));
         }
+        fs::remove_file(&cache).unwrap();
+        // Only the two URLs of the first parse are checked, the second parse uses the cache.
+        assert_eq!(calls.load(AtomicOrdering::SeqCst), 2);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::fs;
-    use std::time::Duration;
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn test_relative_paths() {
+        let directory = std::env::temp_dir().join("m3u_parser_relative_paths");
+        fs::create_dir_all(directory.join("music")).unwrap();
+        fs::write(directory.join("music").join("song.mp3"), b"ID3").unwrap();
+        let path = directory.join("playlists").join("playlist.m3u");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            "#EXTM3U\n#EXTINF:120,Song\n../music/song.mp3\n#EXTINF:60,Missing\nmissing.mp3\n",
+        )
+        .unwrap();
 
-    use super::M3uParser;
+        let mut parser = M3uParser::new(None);
+        parser.set_check_local_files(true, false);
+        parser.parse_m3u(path.to_str().unwrap(), true, true).await;
+        assert_eq!(parser.streams_info.len(), 2);
+        assert_eq!(parser.streams_info[0].url, "../music/song.mp3");
+        assert_eq!(parser.streams_info[0].status, "GOOD");
+        assert_eq!(parser.streams_info[1].status, "BAD");
+
+        parser.set_canonicalize_paths(true);
+        parser.parse_m3u(path.to_str().unwrap(), false, true).await;
+        let expected = fs::canonicalize(directory.join("music").join("song.mp3")).unwrap();
+        assert_eq!(parser.streams_info[0].url, expected.to_string_lossy());
+        fs::remove_dir_all(&directory).unwrap();
+    }
 
+    #[cfg(feature = "geo")]
     #[tokio::test]
-    async fn test_m3u_parser() {
-        let mut parser = M3uParser::new(Some(Duration::from_secs(5)));
+    async fn test_validate() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 tvg-id="bbcone.uk" tvg-country="GB" tvg-logo="http://example.com/bbc.png",BBC One
+http://example.com/bbc-one.m3u8
+#EXTINF:-1 tvg-country="XX" tvg-logo="not a url",BBC One Copy
+http://example.com/bbc-one.m3u8
+#EXTINF:-1 tvg-id="broken",Broken
+#EXTINF:-1 tvg-id="untitled"
+http://example.com/untitled.m3u8
+"#;
+        let parser = parse_content(content).await;
+        let issues: Vec<(Option<usize>, ValidationIssueKind)> = parser
+            .validate()
+            .into_iter()
+            .map(|issue| (issue.line_number, issue.kind))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (Some(4), ValidationIssueKind::EmptyTvgId),
+                (Some(4), ValidationIssueKind::DuplicateUrl),
+                (Some(4), ValidationIssueKind::MalformedLogoUrl),
+                (Some(4), ValidationIssueKind::UnknownCountryCode),
+                (Some(6), ValidationIssueKind::UnparsedEntry),
+                (Some(7), ValidationIssueKind::MissingTitle),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_warnings() {
+        let content = r#"#EXTM3U
+#EXTINF:-1,Broken
+#EXTINF:-1,BBC One
+http://example.com/bbc-one.m3u8
+this is not a url
+http://example.com/orphan.m3u8
+"#;
+        let parser = parse_content(content).await;
+        assert_eq!(parser.streams_info.len(), 1);
+
+        let warnings: Vec<(usize, ParseWarningReason, &str)> = parser
+            .warnings()
+            .iter()
+            .map(|warning| {
+                (
+                    warning.line_number,
+                    warning.reason,
+                    warning.raw_line.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (2, ParseWarningReason::MissingUrl, "#EXTINF:-1,Broken"),
+                (5, ParseWarningReason::UnrecognizedLine, "this is not a url"),
+                (
+                    6,
+                    ParseWarningReason::UrlWithoutExtinf,
+                    "http://example.com/orphan.m3u8"
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_attributes() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 tvg-id="bbcone.uk" catchup-source="http://example.com/catchup/{utc}",BBC One
+#KODIPROP:inputstream.adaptive.license_key=http://example.com/license
+http://example.com/bbc-one.m3u8
+#EXTINF:-1,ITV
+http://example.com/itv.m3u8
+"#;
+        let mut parser = M3uParser::new(None);
+        parser.extract_attribute("catchup-source", "catchup_source");
         parser
-            .parse_m3u(
-                "https://iptv-org.github.io/iptv/index.country.m3u",
-                true,
-                true,
+            .extract_regex(
+                "^#KODIPROP:inputstream.adaptive.license_key=(.+)$",
+                "license_key",
             )
-            .await;
+            .unwrap();
+        assert!(parser.extract_regex("(", "broken").is_err());
+        parser.parse_m3u_content(content, false, true).await;
 
-        parser.filter_by("title", vec!["Metro TV"], "_", false, false);
-        parser.sort_by("title", "_", false, false);
+        let extra = &parser.streams_info[0].extra_attributes;
+        assert_eq!(extra["catchup_source"], "http://example.com/catchup/{utc}");
+        assert_eq!(extra["license_key"], "http://example.com/license");
+        assert!(parser.streams_info[1].extra_attributes.is_empty());
 
-        assert!(
-            !parser
-                .streams_info
+        let json: serde_json::Value =
+            serde_json::from_str(&parser.get_json(false).unwrap()).unwrap();
+        assert_eq!(
+            json[0]["extra_attributes"]["license_key"],
+            "http://example.com/license"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_render_subset() {
+        let parser = parse_content(SAMPLE_PLAYLIST).await;
+        let results: Vec<Info> = parser
+            .search("bbc")
+            .into_iter()
+            .map(|(info, _)| info.clone())
+            .collect();
+        assert!(!results.is_empty() && results.len() < parser.streams_info.len());
+
+        let m3u = parser.render_m3u(&results);
+        assert!(m3u.starts_with("#EXTM3U\n"));
+        assert_eq!(m3u.matches("#EXTINF").count(), results.len());
+        assert_eq!(
+            parser.render_m3u(&parser.streams_info),
+            parser.get_m3u_content()
+        );
+        assert_eq!(parser.render_m3u(&[]), "");
+
+        let json: Vec<Info> =
+            serde_json::from_str(&parser.render_json(&results, false).unwrap()).unwrap();
+        assert_eq!(json.len(), results.len());
+        assert_eq!(
+            parser.render_json(&parser.streams_info, true).unwrap(),
+            parser.get_json(true).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_flag_emoji() {
+        let country = |code: &str| Country {
+            code: code.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(country("GB").flag_emoji().unwrap(), "🇬🇧");
+        assert_eq!(country(" uk ").flag_emoji().unwrap(), "🇬🇧");
+        assert_eq!(country("fr;be").codes(), vec!["FR", "BE"]);
+        assert_eq!(country("fr;be").flag_emoji().unwrap(), "🇫🇷");
+        assert!(country("").flag_emoji().is_none());
+        assert!(country("GBR").flag_emoji().is_none());
+
+        let info = Info {
+            title: String::from("BBC One HD"),
+            country: country("gb"),
+            ..Default::default()
+        };
+        assert_eq!(info.display_title(), "🇬🇧 BBC One HD");
+        let info = Info {
+            country: Country::default(),
+            ..info
+        };
+        assert_eq!(info.display_title(), "BBC One HD");
+
+        let info = Info::from_extinf(
+            "#EXTINF:-1 tvg-country=\"GB\",BBC One",
+            "http://example.com/bbc.m3u8",
+        )
+        .unwrap();
+        let mut parser = M3uParser::new(None);
+        parser.set_m3u_write_options(M3uWriteOptions {
+            flag_titles: true,
+            ..Default::default()
+        });
+        assert!(parser.render_m3u(&[info]).contains(",🇬🇧 BBC One\n"));
+    }
+
+    #[tokio::test]
+    async fn test_asx_and_wpl() {
+        let asx = r#"<asx version="3.0">
+  <entry><title>Jazz Radio</title><ref href="http://example.com/jazz.mp3"/></entry>
+  <entry><title>News</title><ref href="http://example.com/news.m3u8"/></entry>
+</asx>"#;
+        let parser = parse_content(asx).await;
+        let titles: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Jazz Radio", "News"]);
+
+        let mut output = vec![];
+        parser.to_writer(&mut output, "wpl").unwrap();
+        let reparsed = parse_content(&String::from_utf8(output).unwrap()).await;
+        assert_eq!(reparsed.streams_info[1].url, "http://example.com/news.m3u8");
+        assert_eq!(reparsed.streams_info[1].title, "news");
+    }
+
+    #[tokio::test]
+    async fn test_xmltv_stub() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 tvg-id="bbcone.uk",BBC One
+http://example.com/bbc-one.m3u8
+#EXTINF:-1 tvg-logo="http://example.com/a&b.png",A&B Music
+http://example.com/ab.m3u8
+"#;
+        let mut parser = parse_content(content).await;
+        parser.set_m3u_write_options(M3uWriteOptions::media_server());
+        assert!(parser
+            .get_m3u_content()
+            .contains("tvg-chno=\"2\" channel-id=\"2\" tvg-id=\"a-b-music.stub\""));
+        assert_eq!(
+            parser.xmltv_stub(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <tv generator-info-name=\"m3u_parser\">\n\
+             \x20 <channel id=\"a-b-music.stub\">\n\
+             \x20   <display-name>A&amp;B Music</display-name>\n\
+             \x20   <icon src=\"http://example.com/a&amp;b.png\"/>\n\
+             \x20 </channel>\n\
+             </tv>\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_markdown_export() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        parser.sort_by("title", "-", true, false);
+        let mut output = vec![];
+        parser.to_writer(&mut output, "md").unwrap();
+        let markdown = String::from_utf8(output).unwrap();
+        let rows: Vec<&str> = markdown.lines().skip(2).collect();
+        assert_eq!(
+            rows,
+            vec![
+                "| BBC One | News | GB | BAD | http://example.com/bbc-one.m3u8 |",
+                "| Local | General | NP | GOOD | /media/videos/local.mp4 |",
+                "| TF1 | General | FR | BAD | http://example.com/tf1.m3u8 |",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_html_export() {
+        let mut parser = parse_content(SAMPLE_PLAYLIST).await;
+        parser.set_html_grouped(true);
+        let mut output = vec![];
+        parser.to_writer(&mut output, "html").unwrap();
+        let html = String::from_utf8(output).unwrap();
+        assert!(html.contains("<h1>Channels (3)</h1>"));
+        assert!(html.contains("<h2>General (2)</h2>"));
+        assert!(html.contains("<a href=\"http://example.com/tf1.m3u8\">TF1</a>"));
+    }
+
+    #[tokio::test]
+    async fn test_export_with_template() {
+        let parser = parse_content(SAMPLE_PLAYLIST).await;
+        let mut output = vec![];
+        parser
+            .export_with_template(
+                "{if tvg.id}map {tvg.id} {url};{else}# {title}{end}\n",
+                &mut output,
+            )
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 3);
+        assert_eq!(
+            output.lines().next(),
+            Some("map bbcone.uk http://example.com/bbc-one.m3u8;")
+        );
+
+        let mut output = vec![];
+        let error = parser
+            .export_with_template("{if title}", &mut output)
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_comments() {
+        let content = r#"#EXTM3U
+# note: dies every weekend
+# note: ask the provider
+#EXTINF:-1,BBC One
+http://example.com/bbc-one.m3u8
+#EXTINF:-1,TF1
+http://example.com/tf1.m3u8
+"#;
+        let mut parser = parse_content(content).await;
+        assert_eq!(
+            parser.streams_info[0].comment.as_deref(),
+            Some("dies every weekend\nask the provider")
+        );
+        assert!(parser.streams_info[1].comment.is_none());
+        assert_eq!(parser.get_raw_m3u_content(), content.trim_end());
+
+        parser.streams_info[1].comment = Some(String::from("backup of ch. 4"));
+        let written = parser.get_m3u_content();
+        assert!(written.contains("# note: backup of ch. 4\n#EXTINF:-1,TF1\n"));
+
+        let reparsed = parse_content(&written).await;
+        let comments: Vec<Option<&str>> = reparsed
+            .streams_info
+            .iter()
+            .map(|info| info.comment.as_deref())
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                Some("dies every weekend\nask the provider"),
+                Some("backup of ch. 4")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicates() {
+        let content = r#"#EXTM3U
+#EXTINF:-1 tvg-id="bbcone.uk",BBC One
+http://example.com/bbc-one.m3u8
+#EXTINF:-1,TF1
+http://example.com/tf1.m3u8
+#EXTINF:-1 tvg-id="bbcone.uk", bbc one
+http://backup.example.com/bbc-one.m3u8
+#EXTINF:-1,Télé
+http://example.com/tf1.m3u8
+#EXTINF:-1,TELE
+http://example.com/tele.m3u8
+"#;
+        let parser = parse_content(content).await;
+        let titles = |key: DedupeKey| -> Vec<Vec<String>> {
+            parser
+                .find_duplicates(key)
                 .iter()
-                .any(|info| info.title == "Metro TV"),
-            "Metro TV is available as a title"
+                .map(|cluster| cluster.iter().map(|info| info.title.clone()).collect())
+                .collect()
+        };
+        assert_eq!(titles(DedupeKey::Url), vec![vec!["TF1", "Télé"]]);
+        assert_eq!(titles(DedupeKey::TvgId), vec![vec!["BBC One", "bbc one"]]);
+        assert_eq!(
+            titles(DedupeKey::Title),
+            vec![vec!["BBC One", "bbc one"], vec!["Télé", "TELE"]]
         );
+        assert_eq!(parser.streams_info.len(), 5);
+    }
 
-        let random_stream = parser.get_random_stream(true);
-        assert!(random_stream.is_some(), "Random stream should be available");
+    #[tokio::test]
+    async fn test_auto_categorize() {
+        let content = r#"#EXTM3U
+#EXTINF:-1,Euronews
+http://example.com/euronews.m3u8
+#EXTINF:-1 group-title="General",Sport Plus
+http://example.com/sport-plus.m3u8
+#EXTINF:-1,Discovery Channel
+http://example.com/discovery.m3u8
+#EXTINF:-1,Channel 5
+http://example.com/five.m3u8
+"#;
+        let mut parser = parse_content(content).await;
+        parser.add_category_keywords("Documentary", &["Discovery"]);
+        assert_eq!(parser.auto_categorize(), 2);
 
-        let file_path = "hello.m3u";
-        parser.to_file(file_path, "m3u");
+        let categories: Vec<&str> = parser
+            .streams_info
+            .iter()
+            .map(|info| info.category.as_str())
+            .collect();
+        assert_eq!(categories, vec!["News", "General", "Documentary", ""]);
+        assert_eq!(parser.streams_info[0].categories, vec!["News"]);
+        assert_eq!(parser.auto_categorize(), 0);
 
-        // Assert that the file exists
-        assert!(fs::metadata(file_path).is_ok(), "Output file should exist");
+        parser.reset_operations();
+        assert!(parser.streams_info[0].category.is_empty());
+    }
 
-        // Clean up the temporary file
-        if let Err(err) = fs::remove_file(file_path) {
-            eprintln!("Failed to remove file: {}", err);
-        }
+    #[tokio::test]
+    async fn test_strict() {
+        let content = "http://example.com/orphan.m3u8\n#EXTINF:-1,Broken\n#EXTINF:-1,BBC One\n\
+                       http://example.com/bbc-one.m3u8\n";
+        let mut parser = M3uParser::new(None);
+        parser.set_strict(true);
+        let error = parser
+            .parse_reader(content.as_bytes(), false, true)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "invalid playlist: line 1: missing #EXTM3U header; line 1: URL without #EXTINF line; \
+             line 2: #EXTINF line without URL"
+        );
+        assert!(parser.streams_info.is_empty());
+        assert_eq!(parser.parse_error().unwrap().problems.len(), 3);
+
+        let content = format!(
+            "\u{feff}#EXTM3U\n{}",
+            content.replace("#EXTINF:-1,Broken\n", "")
+        );
+        let content = content.replace("http://example.com/orphan.m3u8\n", "");
+        parser
+            .parse_reader(content.as_bytes(), false, true)
+            .await
+            .unwrap();
+        assert!(parser.parse_error().is_none());
+        assert_eq!(parser.streams_info.len(), 1);
+
+        let lenient = parse_content("#EXTINF:-1,BBC One\nhttp://example.com/bbc-one.m3u8").await;
+        assert!(lenient.parse_error().is_none());
+        assert_eq!(lenient.streams_info.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reader_and_writer() {
+        let mut parser = M3uParser::new(None);
+        parser
+            .parse_reader(SAMPLE_PLAYLIST.as_bytes(), false, true)
+            .await
+            .unwrap();
+        assert_eq!(parser.streams_info.len(), 3);
+
+        let mut output = Vec::new();
+        parser.to_writer(&mut output, "m3u").unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), parser.get_m3u_content());
+
+        let mut output = Vec::new();
+        parser.to_writer(&mut output, "json").unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 3);
+
+        assert!(parser.to_writer(&mut Vec::new(), "xml").is_err());
     }
 }
@@ -1,21 +1,160 @@
+use std::env;
+use std::fs::File;
+use std::future::Future;
+use std::io::{self, Write};
+use std::process;
 use std::time::Duration;
 
-use m3u_parser::M3uParser;
+use m3u_parser::{M3uParser, ParserConfig};
 
-#[tokio::main]
-async fn main() {
+const USAGE: &str = "Usage:
+    m3u-parser convert [--to FORMAT] [-o OUTPUT] [--check-live] INPUT
+    m3u-parser filter --key KEY --value REGEX... [--remove] [--to FORMAT] [-o OUTPUT]
+                      [--check-live] INPUT
+    m3u-parser run CONFIG
+
+INPUT is a path or URL of an M3U playlist, or `-` to read it from stdin.
+OUTPUT is the file to write to, or `-` (the default) to write to stdout.
+FORMAT is one of m3u, json, html, md, asx and wpl, guessed from the extension of OUTPUT.
+KEY is one of the keys accepted by `filter_by`, e.g. `title` or `country-code`.
+CONFIG is a TOML or JSON file listing the sources, filters, outputs and check settings.";
+
+#[derive(Default)]
+struct Args {
+    command: String,
+    input: Option<String>,
+    output: Option<String>,
+    format: Option<String>,
+    key: Option<String>,
+    values: Vec<String>,
+    remove: bool,
+    check_live: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = env::args().skip(1);
+    let mut parsed = Args {
+        command: args.next().ok_or("Missing command")?,
+        ..Default::default()
+    };
+    if !["convert", "filter", "run"].contains(&parsed.command.as_str()) {
+        return Err(format!("Unknown command: {}", parsed.command));
+    }
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("Missing value for {}", name));
+        match arg.as_str() {
+            "-o" | "--output" => parsed.output = Some(value(&arg)?),
+            "--to" => parsed.format = Some(value(&arg)?),
+            "--key" => parsed.key = Some(value(&arg)?),
+            "--value" => parsed.values.push(value(&arg)?),
+            "--remove" => parsed.remove = true,
+            "--check-live" => parsed.check_live = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option: {}", arg)),
+            _ if parsed.input.is_none() => parsed.input = Some(arg),
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+
+    if parsed.input.is_none() {
+        return Err(String::from(if parsed.command == "run" {
+            "Missing config"
+        } else {
+            "Missing input"
+        }));
+    }
+    if parsed.command == "filter" && (parsed.key.is_none() || parsed.values.is_empty()) {
+        return Err(String::from(
+            "filter requires --key and at least one --value",
+        ));
+    }
+    Ok(parsed)
+}
+
+/// Guess the output format from the extension of the output file, defaulting to M3U.
+fn output_format(args: &Args) -> String {
+    if let Some(format) = &args.format {
+        return format.clone();
+    }
+    match args.output.as_deref() {
+        Some(output) => match output.rsplit_once('.') {
+            Some((_, extension)) if ["json", "html", "md", "asx", "wpl"].contains(&extension) => {
+                extension.to_string()
+            }
+            _ => String::from("m3u"),
+        },
+        None => String::from("m3u"),
+    }
+}
+
+async fn run(args: Args) -> io::Result<()> {
     let mut parser = M3uParser::new(Some(Duration::from_secs(5)));
-    parser
-        .parse_m3u(
-            "https://iptv-org.github.io/iptv/index.country.m3u",
-            true,
-            true,
-        )
-        .await;
-    parser.filter_by("title", vec!["Metro TV"], "_", false, false);
-    parser.sort_by("title", "_", false, false);
-    // let json_value = m3u_parser.get_json(true).unwrap();
-    let random_stream = parser.get_random_stream(true);
-    println!("{:?}", random_stream.unwrap());
-    parser.to_file("hello.m3u", "m3u")
+    if args.command == "run" {
+        let path = args.input.as_deref().unwrap_or_default();
+        let config = ParserConfig::load(path)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let report = parser.run_config(&config).await?;
+        if report.succeeded().next().is_none() && !report.sources.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "none of the sources could be parsed",
+            ));
+        }
+        return Ok(());
+    }
+    parser.apply_env();
+    match args.input.as_deref() {
+        Some("-") => {
+            parser
+                .parse_reader(io::stdin().lock(), args.check_live, true)
+                .await?
+        }
+        Some(input) => parser.parse_m3u(input, args.check_live, true).await,
+        None => unreachable!(),
+    }
+
+    if let Some(key) = &args.key {
+        let values = args.values.iter().map(String::as_str).collect();
+        parser.filter_by(key, values, "-", !args.remove, key.contains('-'));
+    }
+
+    let format = output_format(&args);
+    match args.output.as_deref() {
+        None | Some("-") => parser.to_writer(&mut io::stdout().lock(), &format),
+        Some(output) => {
+            let mut file = File::create(output)?;
+            parser.to_writer(&mut file, &format)?;
+            file.flush()
+        }
+    }
+}
+
+/// Runs the future to completion. With the `net` feature, the HTTP client requires a tokio
+/// runtime; otherwise a plain executor is enough to read files.
+#[cfg(feature = "net")]
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Runtime::new().unwrap().block_on(future)
+}
+
+#[cfg(not(feature = "net"))]
+fn block_on<F: Future>(future: F) -> F::Output {
+    futures::executor::block_on(future)
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = block_on(run(args)) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
 }
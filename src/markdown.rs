@@ -0,0 +1,57 @@
+use crate::Info;
+
+/// Escapes the text for a cell of a Markdown table, where `|` ends the cell and line breaks end
+/// the row.
+fn escape_cell(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// Renders the stream information as a Markdown table with the title, category, country, status
+/// and URL of each stream, in the current order.
+pub(crate) fn render_markdown(streams_info: &[Info]) -> String {
+    let mut lines = vec![
+        String::from("| Title | Category | Country | Status | URL |"),
+        String::from("| --- | --- | --- | --- | --- |"),
+    ];
+    lines.extend(streams_info.iter().map(|info| {
+        let cells = [
+            &info.title,
+            &info.category,
+            &info.country.code,
+            &info.status,
+            &info.url,
+        ];
+        let cells: Vec<String> = cells.iter().map(|cell| escape_cell(cell)).collect();
+        format!("| {} |", cells.join(" | "))
+    }));
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_markdown;
+    use crate::{Country, Info};
+
+    #[test]
+    fn test_render_markdown() {
+        let info = Info {
+            title: String::from("News | Live"),
+            category: String::from("News"),
+            country: Country {
+                code: String::from("GB"),
+                ..Default::default()
+            },
+            status: String::from("BAD"),
+            url: String::from("http://example.com/news.m3u8"),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_markdown(&[info]),
+            "| Title | Category | Country | Status | URL |\n\
+             | --- | --- | --- | --- | --- |\n\
+             | News \\| Live | News | GB | BAD | http://example.com/news.m3u8 |\n"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+/// Struct representing an audio track of a stream.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AudioTrack {
+    pub codec: Option<String>,
+    /// The language of the track as tagged in the stream, e.g. "eng".
+    pub language: Option<String>,
+    pub channels: Option<u32>,
+}
+
+/// Struct representing the media information of a stream, as reported by ffprobe.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MediaInfo {
+    /// The container format, e.g. "hls" or "mpegts".
+    pub format: Option<String>,
+    pub video_codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// The overall bitrate in bits per second.
+    pub bitrate: Option<u64>,
+    pub audio_tracks: Vec<AudioTrack>,
+}
+
+impl MediaInfo {
+    /// Builds the media information from the output of
+    /// `ffprobe -print_format json -show_streams -show_format`.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The JSON output of ffprobe.
+    ///
+    /// # Returns
+    ///
+    /// A `serde_json::Result<MediaInfo>` which is an error if the output is not valid JSON.
+    ///
+    #[cfg(feature = "ffprobe")]
+    pub fn from_ffprobe_json(output: &str) -> serde_json::Result<MediaInfo> {
+        use serde_json::Value;
+
+        let output: Value = serde_json::from_str(output)?;
+        let string = |value: &Value| value.as_str().map(String::from);
+        let number = |value: &Value| {
+            value
+                .as_u64()
+                .or_else(|| value.as_str().and_then(|value| value.parse().ok()))
+        };
+
+        let mut media = MediaInfo {
+            format: string(&output["format"]["format_name"]),
+            bitrate: number(&output["format"]["bit_rate"]),
+            ..Default::default()
+        };
+        for stream in output["streams"].as_array().into_iter().flatten() {
+            match stream["codec_type"].as_str() {
+                Some("video") if media.video_codec.is_none() => {
+                    media.video_codec = string(&stream["codec_name"]);
+                    media.width = number(&stream["width"]).map(|width| width as u32);
+                    media.height = number(&stream["height"]).map(|height| height as u32);
+                    if media.bitrate.is_none() {
+                        media.bitrate = number(&stream["bit_rate"]);
+                    }
+                }
+                Some("audio") => media.audio_tracks.push(AudioTrack {
+                    codec: string(&stream["codec_name"]),
+                    language: string(&stream["tags"]["language"]),
+                    channels: number(&stream["channels"]).map(|channels| channels as u32),
+                }),
+                _ => {}
+            }
+        }
+        Ok(media)
+    }
+}
+
+/// Runs ffprobe on the given location and returns the media information of the stream.
+///
+/// # Arguments
+///
+/// * `location` - The URL or path of the stream.
+/// * `timeout` - The maximum time to wait for ffprobe.
+///
+/// # Returns
+///
+/// The `MediaInfo` of the stream, or `None` if ffprobe is not installed, timed out, or could not
+/// read the stream.
+///
+#[cfg(feature = "ffprobe")]
+pub async fn probe(location: &str, timeout: std::time::Duration) -> Option<MediaInfo> {
+    let output = tokio::process::Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-print_format",
+            "json",
+            "-show_streams",
+            "-show_format",
+        ])
+        .arg(location)
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(timeout, output).await.ok()?.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    MediaInfo::from_ffprobe_json(&String::from_utf8_lossy(&output.stdout)).ok()
+}
+
+#[cfg(all(test, feature = "ffprobe"))]
+mod tests {
+    use super::{AudioTrack, MediaInfo};
+
+    #[test]
+    fn test_from_ffprobe_json() {
+        let output = r#"{
+            "streams": [
+                {"codec_type": "video", "codec_name": "h264", "width": 1280, "height": 720},
+                {"codec_type": "audio", "codec_name": "aac", "channels": 2, "tags": {"language": "eng"}},
+                {"codec_type": "audio", "codec_name": "ac3", "channels": 6}
+            ],
+            "format": {"format_name": "hls", "bit_rate": "2500000"}
+        }"#;
+        let media = MediaInfo::from_ffprobe_json(output).unwrap();
+        assert_eq!(media.format.as_deref(), Some("hls"));
+        assert_eq!(media.video_codec.as_deref(), Some("h264"));
+        assert_eq!((media.width, media.height), (Some(1280), Some(720)));
+        assert_eq!(media.bitrate, Some(2_500_000));
+        assert_eq!(
+            media.audio_tracks,
+            vec![
+                AudioTrack {
+                    codec: Some(String::from("aac")),
+                    language: Some(String::from("eng")),
+                    channels: Some(2),
+                },
+                AudioTrack {
+                    codec: Some(String::from("ac3")),
+                    language: None,
+                    channels: Some(6),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::cache::unix_now;
+use crate::M3uParser;
+
+/// Struct representing a stream whose status changed between two checks.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusChange {
+    pub title: String,
+    pub url: String,
+    /// The status of the previous check, "GOOD" or "BAD".
+    pub previous: String,
+    /// The status of the latest check, "GOOD" or "BAD".
+    pub current: String,
+    /// Seconds since the Unix epoch.
+    pub checked_at: u64,
+}
+
+/// Struct representing the check history of a stream.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StreamHealth {
+    pub title: String,
+    pub checks: u64,
+    pub good_checks: u64,
+    pub last_status: String,
+    /// The most recent checks as `(checked_at, good)` pairs, oldest first.
+    pub history: VecDeque<(u64, bool)>,
+}
+
+impl StreamHealth {
+    /// Returns the share of checks in which the stream was available, between 0 and 1.
+    pub fn uptime(&self) -> f64 {
+        if self.checks == 0 {
+            return 0.0;
+        }
+        self.good_checks as f64 / self.checks as f64
+    }
+}
+
+type Callback = Box<dyn Fn(Vec<StatusChange>) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Struct re-checking the streams of a parser on a schedule.
+///
+/// Each check updates the status of the streams of the parser, records it in the history of each
+/// stream, keyed by URL, and sends a `StatusChange` to the subscribers for every stream that went
+/// from "GOOD" to "BAD" or back.
+pub struct Monitor {
+    parser: M3uParser,
+    interval: Duration,
+    history_size: usize,
+    health: HashMap<String, StreamHealth>,
+    events: broadcast::Sender<StatusChange>,
+    callbacks: Vec<Callback>,
+    webhooks: Vec<String>,
+}
+
+impl Monitor {
+    /// Creates a new monitor.
+    ///
+    /// # Arguments
+    ///
+    /// * `parser` - The parser holding the streams to monitor, with its stream checker, liveness
+    ///   cache and filters already set up.
+    /// * `interval` - The time between two checks.
+    ///
+    pub fn new(parser: M3uParser, interval: Duration) -> Self {
+        let (events, _) = broadcast::channel(1024);
+        Monitor {
+            parser,
+            interval,
+            history_size: 100,
+            health: HashMap::new(),
+            events,
+            callbacks: vec![],
+            webhooks: vec![],
+        }
+    }
+
+    /// Sets the number of recent checks kept in the history of each stream, 100 by default.
+    pub fn set_history_size(&mut self, size: usize) {
+        self.history_size = size;
+    }
+
+    /// Returns a receiver of the status changes detected by the following checks.
+    pub fn subscribe(&self) -> broadcast::Receiver<StatusChange> {
+        self.events.subscribe()
+    }
+
+    /// Registers an async callback invoked after each check in which some streams changed status.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The function called with the status changes of the check, e.g. to page an
+    ///   operator. The checks wait for the returned future to complete.
+    ///
+    pub fn on_change<F, Fut>(&mut self, callback: F)
+    where
+        F: Fn(Vec<StatusChange>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.callbacks
+            .push(Box::new(move |changes| Box::pin(callback(changes))));
+    }
+
+    /// Registers a webhook notified after each check in which some streams changed status.
+    ///
+    /// The status changes of the check are sent as a JSON array in the body of a POST request.
+    /// Failed requests are reported on stderr and not retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the webhook.
+    ///
+    pub fn add_webhook(&mut self, url: &str) {
+        self.webhooks.push(url.to_string());
+    }
+
+    /// Returns the monitored parser.
+    pub fn parser(&self) -> &M3uParser {
+        &self.parser
+    }
+
+    /// Returns the monitored parser, e.g. to parse an updated playlist between checks.
+    pub fn parser_mut(&mut self) -> &mut M3uParser {
+        &mut self.parser
+    }
+
+    /// Returns the check history of the streams, keyed by URL.
+    pub fn health(&self) -> &HashMap<String, StreamHealth> {
+        &self.health
+    }
+
+    /// Checks the streams once.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<StatusChange>` with the streams whose status changed since the previous check. Streams
+    /// checked for the first time are never reported.
+    ///
+    pub async fn check(&mut self) -> Vec<StatusChange> {
+        self.parser.check_streams().await;
+        let checked_at = unix_now();
+        let mut changes = Vec::new();
+
+        for info in &self.parser.streams_info {
+            let health = self.health.entry(info.url.clone()).or_default();
+            let good = info.status == "GOOD";
+            if health.checks > 0 && health.last_status != info.status {
+                changes.push(StatusChange {
+                    title: info.title.clone(),
+                    url: info.url.clone(),
+                    previous: health.last_status.clone(),
+                    current: info.status.clone(),
+                    checked_at,
+                });
+            }
+            health.title = info.title.clone();
+            health.checks += 1;
+            health.good_checks += good as u64;
+            health.last_status = info.status.clone();
+            health.history.push_back((checked_at, good));
+            while health.history.len() > self.history_size {
+                health.history.pop_front();
+            }
+        }
+
+        for change in &changes {
+            // Sending only fails when nobody is subscribed.
+            let _ = self.events.send(change.clone());
+        }
+        if !changes.is_empty() {
+            self.notify(&changes).await;
+        }
+        changes
+    }
+
+    async fn notify(&self, changes: &[StatusChange]) {
+        for callback in &self.callbacks {
+            callback(changes.to_vec()).await;
+        }
+        if self.webhooks.is_empty() {
+            return;
+        }
+        let client = match self.parser.http_client(self.parser.fetch_timeout) {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Error: could not notify the webhooks: {}", e);
+                return;
+            }
+        };
+        for webhook in &self.webhooks {
+            let result = client
+                .post(webhook)
+                .json(changes)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                eprintln!("Error: could not notify webhook {}: {}", webhook, e);
+            }
+        }
+    }
+
+    /// Checks the streams at the configured interval, starting immediately.
+    ///
+    /// This function never returns; spawn it or drop the future to stop monitoring.
+    ///
+    pub async fn run(&mut self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            self.check().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Monitor;
+    use crate::{M3uParser, StreamChecker};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct SwitchChecker(Arc<AtomicBool>);
+
+    #[async_trait::async_trait]
+    impl StreamChecker for SwitchChecker {
+        async fn is_available(&self, url: &str) -> bool {
+            url.contains("always") || self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_monitor() {
+        let up = Arc::new(AtomicBool::new(true));
+        let mut parser = M3uParser::new(None);
+        parser.set_stream_checker(SwitchChecker(Arc::clone(&up)));
+        parser
+            .parse_m3u_content(
+                "#EXTM3U\n#EXTINF:-1,Sports\nhttp://example.com/sports.m3u8\n\
+                 #EXTINF:-1,News\nhttp://example.com/always.m3u8\n",
+                false,
+                true,
+            )
+            .await;
+
+        let mut monitor = Monitor::new(parser, Duration::from_secs(60));
+        let mut events = monitor.subscribe();
+        let notified = Arc::new(Mutex::new(vec![]));
+        let sink = Arc::clone(&notified);
+        monitor.on_change(move |changes| {
+            let sink = Arc::clone(&sink);
+            async move { sink.lock().unwrap().push(changes) }
+        });
+        assert!(monitor.check().await.is_empty());
+
+        up.store(false, Ordering::SeqCst);
+        let changes = monitor.check().await;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].title, "Sports");
+        assert_eq!(
+            (changes[0].previous.as_str(), changes[0].current.as_str()),
+            ("GOOD", "BAD")
+        );
+        assert_eq!(events.try_recv().unwrap(), changes[0]);
+        assert_eq!(*notified.lock().unwrap(), vec![changes.clone()]);
+
+        assert!(monitor.check().await.is_empty());
+        assert_eq!(notified.lock().unwrap().len(), 1);
+        let health = &monitor.health()["http://example.com/sports.m3u8"];
+        assert_eq!((health.checks, health.good_checks), (3, 1));
+        assert_eq!(
+            monitor.health()["http://example.com/always.m3u8"].uptime(),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use serde::Serialize;
+
+/// Struct representing the outcome of one of the sources of `M3uParser::fetch_all`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceReport {
+    /// The path or URL of the playlist.
+    pub source: String,
+    /// The number of entries parsed from the playlist, 0 if it failed.
+    pub entries: usize,
+    /// Why the playlist could not be downloaded, read or parsed, e.g. an HTTP error or the error
+    /// of a playlist rejected in strict mode.
+    pub error: Option<String>,
+}
+
+impl SourceReport {
+    /// Returns whether the playlist was parsed.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Struct representing the outcome of parsing several playlists with `M3uParser::fetch_all`.
+///
+/// A failing source does not abort the others: the entries of the sources that were parsed are
+/// kept, and each failure is listed here.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MultiParseReport {
+    /// The outcome of each source, in the order of the sources.
+    pub sources: Vec<SourceReport>,
+}
+
+impl MultiParseReport {
+    /// Returns the sources that were parsed.
+    pub fn succeeded(&self) -> impl Iterator<Item = &SourceReport> {
+        self.sources.iter().filter(|source| source.is_ok())
+    }
+
+    /// Returns the sources that could not be downloaded, read or parsed.
+    pub fn failed(&self) -> impl Iterator<Item = &SourceReport> {
+        self.sources.iter().filter(|source| !source.is_ok())
+    }
+
+    /// Returns whether every source was parsed.
+    pub fn is_complete(&self) -> bool {
+        self.sources.iter().all(SourceReport::is_ok)
+    }
+
+    /// Returns the number of entries parsed from all the sources.
+    pub fn entries(&self) -> usize {
+        self.sources.iter().map(|source| source.entries).sum()
+    }
+}
@@ -0,0 +1,187 @@
+// The errors returned by the methods are converted by the code generated by `pymethods`, which
+// clippy flags.
+#![allow(clippy::useless_conversion)]
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+use crate::{json, JsonOptions, M3uParser};
+
+/// Words given to the methods filtering by extension or category, either a single string or a
+/// list of strings as accepted by the Python m3u-parser.
+#[derive(FromPyObject)]
+enum Words {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Words {
+    fn to_vec(&self) -> Vec<&str> {
+        match self {
+            Words::One(word) => vec![word.as_str()],
+            Words::Many(words) => words.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Python class wrapping `M3uParser`, with the method names and default arguments of the Python
+/// m3u-parser package.
+#[pyclass(name = "M3uParser")]
+struct PyM3uParser {
+    parser: M3uParser,
+    runtime: Runtime,
+}
+
+#[pymethods]
+impl PyM3uParser {
+    #[new]
+    #[pyo3(signature = (useragent = None, timeout = 5))]
+    fn new(useragent: Option<&str>, timeout: u64) -> PyResult<Self> {
+        let runtime = Runtime::new().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        let mut parser = M3uParser::new(Some(Duration::from_secs(timeout)));
+        if let Some(useragent) = useragent {
+            parser.set_user_agent(useragent);
+        }
+        Ok(PyM3uParser { parser, runtime })
+    }
+
+    #[pyo3(signature = (path, check_live = true, enforce_schema = true))]
+    fn parse_m3u(&mut self, py: Python<'_>, path: &str, check_live: bool, enforce_schema: bool) {
+        let PyM3uParser { parser, runtime } = self;
+        py.allow_threads(|| runtime.block_on(parser.parse_m3u(path, check_live, enforce_schema)));
+    }
+
+    #[pyo3(signature = (key, filters, key_splitter = "-", retrieve = true, nested_key = false))]
+    fn filter_by(
+        &mut self,
+        key: &str,
+        filters: Words,
+        key_splitter: &str,
+        retrieve: bool,
+        nested_key: bool,
+    ) {
+        self.parser
+            .filter_by(key, filters.to_vec(), key_splitter, retrieve, nested_key);
+    }
+
+    fn reset_operations(&mut self) {
+        self.parser.reset_operations();
+    }
+
+    fn remove_by_extension(&mut self, extension: Words) {
+        self.parser.remove_by_extension(extension.to_vec());
+    }
+
+    fn retrieve_by_extension(&mut self, extension: Words) {
+        self.parser.retrieve_by_extension(extension.to_vec());
+    }
+
+    fn remove_by_category(&mut self, filter_word: Words) {
+        self.parser.remove_by_category(filter_word.to_vec());
+    }
+
+    fn retrieve_by_category(&mut self, filter_word: Words) {
+        self.parser.retrieve_by_category(filter_word.to_vec());
+    }
+
+    #[pyo3(signature = (key, key_splitter = "-", asc = true, nested_key = false))]
+    fn sort_by(&mut self, key: &str, key_splitter: &str, asc: bool, nested_key: bool) {
+        self.parser.sort_by(key, key_splitter, asc, nested_key);
+    }
+
+    #[pyo3(signature = (pretty = true))]
+    fn get_json(&self, pretty: bool) -> PyResult<String> {
+        self.parser
+            .get_json(pretty)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Returns the stream information as a list of dictionaries.
+    fn get_list(&self, py: Python<'_>) -> PyResult<PyObject> {
+        from_json(py, &self.get_json(false)?)
+    }
+
+    /// Returns a random stream as a dictionary, or `None` if there are no streams.
+    #[pyo3(signature = (random_shuffle = true))]
+    fn get_random_stream(&mut self, py: Python<'_>, random_shuffle: bool) -> PyResult<PyObject> {
+        let options = JsonOptions {
+            skip_empty: !self.parser.enforce_schema,
+            ..Default::default()
+        };
+        match self.parser.get_random_stream(random_shuffle) {
+            Some(info) => {
+                let content = json::to_string(info, &options)
+                    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                from_json(py, &content)
+            }
+            None => Ok(py.None()),
+        }
+    }
+
+    #[pyo3(signature = (filename, format = "json"))]
+    fn to_file(&self, filename: &str, format: &str) {
+        self.parser.to_file(filename, format);
+    }
+
+    fn __len__(&self) -> usize {
+        self.parser.streams_info.len()
+    }
+}
+
+/// Converts JSON to Python objects with the `json` module of the standard library.
+fn from_json(py: Python<'_>, content: &str) -> PyResult<PyObject> {
+    let loads = py.import_bound("json")?.getattr("loads")?;
+    Ok(loads.call1((content,))?.unbind())
+}
+
+/// The `m3u_parser` Python module.
+#[pymodule]
+fn m3u_parser(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyM3uParser>()
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::prelude::*;
+    use pyo3::types::{PyDict, PyModule};
+
+    #[test]
+    fn test_python_module() {
+        let path = std::env::temp_dir().join("m3u_parser_python.m3u");
+        std::fs::write(
+            &path,
+            "#EXTM3U\n#EXTINF:-1 group-title=\"News\",BBC One\nhttp://example.com/bbc.m3u8\n\
+             #EXTINF:-1 group-title=\"Music\",Local\n/media/music/song.mp3\n",
+        )
+        .unwrap();
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new_bound(py, "m3u_parser").unwrap();
+            super::m3u_parser(&module).unwrap();
+            let locals = PyDict::new_bound(py);
+            locals.set_item("m3u_parser", module).unwrap();
+            locals.set_item("path", path.to_str().unwrap()).unwrap();
+            py.run_bound(
+                r#"
+parser = m3u_parser.M3uParser(timeout=1)
+parser.parse_m3u(path, check_live=False)
+assert len(parser) == 2
+parser.retrieve_by_category("News")
+streams = parser.get_list()
+assert [stream["title"] for stream in streams] == ["BBC One"]
+assert streams[0]["status"] == "BAD"
+parser.reset_operations()
+parser.remove_by_extension(["mp3"])
+assert parser.get_random_stream(random_shuffle=False)["title"] == "BBC One"
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+use serde::Serialize;
+use std::io::{Error, ErrorKind};
+
+use crate::Info;
+
+/// A row of the check report, one per stream.
+#[derive(Serialize)]
+struct CheckReportRow<'a> {
+    title: &'a str,
+    url: &'a str,
+    status: &'a str,
+    http_status: Option<u16>,
+    latency_ms: Option<u64>,
+    error: Option<&'a str>,
+    checked_at: Option<u64>,
+    source: Option<&'a str>,
+}
+
+impl<'a> CheckReportRow<'a> {
+    fn new(info: &'a Info) -> Self {
+        let check = info.check.as_ref();
+        CheckReportRow {
+            title: &info.title,
+            url: &info.url,
+            status: &info.status,
+            http_status: check.and_then(|check| check.http_status),
+            latency_ms: check.and_then(|check| check.latency_ms),
+            error: check.and_then(|check| check.error.as_deref()),
+            checked_at: check.map(|check| check.checked_at),
+            source: info.source.as_deref(),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let optional =
+            |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+        [
+            csv_field(self.title),
+            csv_field(self.url),
+            csv_field(self.status),
+            optional(self.http_status.map(u64::from)),
+            optional(self.latency_ms),
+            csv_field(self.error.unwrap_or_default()),
+            optional(self.checked_at),
+            csv_field(self.source.unwrap_or_default()),
+        ]
+        .join(",")
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders the check report of the streams.
+///
+/// # Arguments
+///
+/// * `streams_info` - The streams to report on.
+/// * `format` - The format of the report, either "csv" or "json".
+///
+/// # Returns
+///
+/// A `std::io::Result<String>` which is an error if the format is not recognised.
+///
+pub fn check_report(streams_info: &[Info], format: &str) -> std::io::Result<String> {
+    let rows = streams_info.iter().map(CheckReportRow::new);
+    match format {
+        "csv" => {
+            let mut lines = vec![String::from(
+                "title,url,status,http_status,latency_ms,error,checked_at,source",
+            )];
+            lines.extend(rows.map(|row| row.to_csv()));
+            Ok(lines.join("\n") + "\n")
+        }
+        "json" => {
+            let rows: Vec<CheckReportRow> = rows.collect();
+            serde_json::to_string_pretty(&rows).map_err(Error::from)
+        }
+        _ => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Unrecognised report format: {}", format),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_report;
+    use crate::{CheckResult, Info};
+
+    #[test]
+    fn test_check_report() {
+        let streams_info = vec![
+            Info {
+                title: String::from("News, Live"),
+                url: String::from("http://example.com/news.m3u8"),
+                status: String::from("BAD"),
+                check: Some(CheckResult {
+                    available: false,
+                    http_status: Some(404),
+                    latency_ms: Some(120),
+                    error: Some(String::from("HTTP status 404 Not Found")),
+                    checked_at: 1700000000,
+                    ..Default::default()
+                }),
+                source: Some(String::from("http://example.com/news.m3u")),
+                ..Default::default()
+            },
+            Info {
+                title: String::from("Local"),
+                url: String::from("/media/local.mp4"),
+                status: String::from("GOOD"),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            check_report(&streams_info, "csv").unwrap(),
+            "title,url,status,http_status,latency_ms,error,checked_at,source\n\
+             \"News, Live\",http://example.com/news.m3u8,BAD,404,120,HTTP status 404 Not Found,1700000000,http://example.com/news.m3u\n\
+             Local,/media/local.mp4,GOOD,,,,,\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&check_report(&streams_info, "json").unwrap()).unwrap();
+        assert_eq!(json[0]["http_status"], 404);
+        assert!(json[1]["checked_at"].is_null());
+
+        assert!(check_report(&streams_info, "xml").is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::read_to_string;
+
+use crate::{Info, InfoKey};
+
+/// Struct representing a rule changing the stream information that matches its conditions.
+///
+/// Fields are named like the keys of the command line, e.g. "title", "category", "tvg-id" or
+/// "country-code".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rule {
+    /// An optional name, used in error messages.
+    pub name: Option<String>,
+    /// The conditions, as regular expressions matched against the value of each field. A stream
+    /// matches the rule if it matches all the conditions; a rule without conditions matches every
+    /// stream.
+    pub when: BTreeMap<String, String>,
+    /// The values assigned to the fields of the matching streams.
+    pub set: BTreeMap<String, String>,
+    /// Whether the matching streams are removed.
+    pub remove: bool,
+}
+
+/// Struct representing an ordered list of rules, loadable from TOML or JSON.
+///
+/// ```toml
+/// [[rules]]
+/// name = "ESPN"
+/// when = { title = "^ESPN" }
+/// set = { category = "Sports", tvg-id = "ESPN.us" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    pub rules: Vec<Rule>,
+}
+
+/// A rule with its fields resolved and its regular expressions compiled.
+pub(crate) struct CompiledRule {
+    conditions: Vec<(InfoKey, Regex)>,
+    actions: Vec<(InfoKey, String)>,
+    remove: bool,
+}
+
+impl CompiledRule {
+    pub(crate) fn matches(&self, info: &Info) -> bool {
+        self.conditions
+            .iter()
+            .all(|(key, regex)| key.is_match(info, regex))
+    }
+
+    pub(crate) fn removes(&self) -> bool {
+        self.remove
+    }
+
+    /// Applies the actions of the rule, returning whether the stream information changed.
+    pub(crate) fn apply(&self, info: &mut Info) -> bool {
+        let mut changed = false;
+        for (key, value) in &self.actions {
+            if key.value(info) != value {
+                key.set_value(info, value);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl Rules {
+    /// Parses rules from JSON, e.g. `{"rules": [{"when": {"title": "^ESPN"}, "set": {...}}]}`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the JSON is invalid, a field is unknown or a condition is
+    /// not a valid regular expression.
+    ///
+    pub fn from_json(content: &str) -> Result<Self, Box<dyn Error>> {
+        let rules: Rules = serde_json::from_str(content)?;
+        rules.compile()?;
+        Ok(rules)
+    }
+
+    /// Parses rules from TOML, with one `[[rules]]` table per rule.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an error if the TOML is invalid, a field is unknown or a condition is
+    /// not a valid regular expression.
+    ///
+    pub fn from_toml(content: &str) -> Result<Self, Box<dyn Error>> {
+        let rules: Rules = toml::from_str(content)?;
+        rules.compile()?;
+        Ok(rules)
+    }
+
+    /// Loads rules from a file, parsed as JSON if its extension is ".json" and as TOML otherwise.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = read_to_string(path)?;
+        if path.to_lowercase().ends_with(".json") {
+            Rules::from_json(&content)
+        } else {
+            Rules::from_toml(&content)
+        }
+    }
+
+    /// Resolves the fields and compiles the regular expressions of the rules.
+    pub(crate) fn compile(&self) -> Result<Vec<CompiledRule>, String> {
+        self.rules
+            .iter()
+            .enumerate()
+            .map(|(position, rule)| {
+                let name = rule
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("#{}", position + 1));
+                let key = |field: &str| {
+                    InfoKey::from_name(field)
+                        .ok_or_else(|| format!("Rule {}: unknown field {}", name, field))
+                };
+                let conditions = rule
+                    .when
+                    .iter()
+                    .map(|(field, pattern)| {
+                        let regex = Regex::new(pattern)
+                            .map_err(|e| format!("Rule {}: invalid pattern: {}", name, e))?;
+                        Ok((key(field)?, regex))
+                    })
+                    .collect::<Result<_, String>>()?;
+                let actions = rule
+                    .set
+                    .iter()
+                    .map(|(field, value)| Ok((key(field)?, value.clone())))
+                    .collect::<Result<_, String>>()?;
+                Ok(CompiledRule {
+                    conditions,
+                    actions,
+                    remove: rule.remove,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rules;
+    use crate::Info;
+
+    #[test]
+    fn test_rules() {
+        let rules = Rules::from_toml(
+            r#"
+            [[rules]]
+            name = "ESPN"
+            when = { title = "^ESPN" }
+            set = { category = "Sports", tvg-id = "ESPN.us" }
+
+            [[rules]]
+            when = { category = "^Adult$" }
+            remove = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rules.rules.len(), 2);
+        assert_eq!(rules.rules[0].set["tvg-id"], "ESPN.us");
+        assert!(rules.rules[1].remove);
+
+        let compiled = rules.compile().unwrap();
+        let mut info = Info {
+            title: String::from("ESPN 2"),
+            ..Default::default()
+        };
+        assert!(compiled[0].matches(&info));
+        assert!(compiled[0].apply(&mut info));
+        assert_eq!(info.category, "Sports");
+        assert_eq!(info.categories, vec!["Sports"]);
+        assert_eq!(info.tvg.id, "ESPN.us");
+        assert!(!compiled[0].apply(&mut info));
+        assert!(!compiled[1].matches(&info));
+
+        let json = r#"{"rules": [{"when": {"title": "^CNN"}, "set": {"group": "News"}}]}"#;
+        let error = Rules::from_json(json).unwrap_err();
+        assert_eq!(error.to_string(), "Rule #1: unknown field group");
+        assert!(Rules::from_json(r#"{"rules": [{"when": {"title": "("}}]}"#).is_err());
+    }
+}
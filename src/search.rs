@@ -0,0 +1,78 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::Info;
+
+/// Lowercases the text and strips its accents, so "Télé Monte-Carlo" becomes "tele monte-carlo".
+pub(crate) fn normalize(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Returns how well the value matches the normalized query, between 0 (no match) and 1 (equal).
+fn match_score(value: &str, query: &str) -> f64 {
+    let value = normalize(value);
+    if value.is_empty() {
+        return 0.0;
+    }
+    if value == query {
+        return 1.0;
+    }
+    if value.starts_with(query) {
+        return 0.8;
+    }
+    if value
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(query))
+    {
+        return 0.6;
+    }
+    if value.contains(query) {
+        return 0.4;
+    }
+
+    // Fuzzy match: the characters of the query appear in order, e.g. "bbcone" in "bbc one".
+    let mut chars = value.chars();
+    if query.chars().all(|q| chars.any(|c| c == q)) {
+        let ratio = query.chars().count() as f64 / value.chars().count() as f64;
+        return 0.2 * ratio;
+    }
+    0.0
+}
+
+/// Returns the relevance of the stream information for the normalized query, or 0 if it does not
+/// match. Matches in the title weigh more than in the `tvg-name`, which weigh more than in the
+/// category.
+pub(crate) fn score(info: &Info, query: &str) -> f64 {
+    [
+        (info.title.as_str(), 1.0),
+        (info.tvg.name.as_str(), 0.9),
+        (info.category.as_str(), 0.5),
+    ]
+    .into_iter()
+    .map(|(value, weight)| match_score(value, query) * weight)
+    .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{match_score, normalize};
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Télé Monte-Carlo"), "tele monte-carlo");
+        assert_eq!(normalize("ČT24 Ñ"), "ct24 n");
+    }
+
+    #[test]
+    fn test_match_score() {
+        assert_eq!(match_score("BBC One", "bbc one"), 1.0);
+        assert_eq!(match_score("BBC One HD", "bbc one"), 0.8);
+        assert_eq!(match_score("The BBC One", "bbc"), 0.6);
+        assert_eq!(match_score("Sky Sports", "port"), 0.4);
+        assert!(match_score("BBC One", "bbco") > 0.0);
+        assert_eq!(match_score("BBC One", "cnn"), 0.0);
+    }
+}
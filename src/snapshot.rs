@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use crate::{json, render_m3u, Info, JsonOptions, ParseWarning};
+
+/// Struct representing an immutable view of the parsed stream information.
+///
+/// Cloning a snapshot only increments reference counts, so it can be shared freely between
+/// threads and request handlers. Create one with `M3uParser::snapshot` or
+/// `M3uParser::into_snapshot`.
+#[derive(Debug, Clone)]
+pub struct PlaylistSnapshot {
+    streams_info: Arc<[Info]>,
+    warnings: Arc<[ParseWarning]>,
+    enforce_schema: bool,
+}
+
+impl PlaylistSnapshot {
+    pub(crate) fn new(
+        streams_info: Vec<Info>,
+        warnings: Vec<ParseWarning>,
+        enforce_schema: bool,
+    ) -> Self {
+        PlaylistSnapshot {
+            streams_info: streams_info.into(),
+            warnings: warnings.into(),
+            enforce_schema,
+        }
+    }
+
+    /// Returns the stream information of the snapshot.
+    pub fn streams_info(&self) -> &[Info] {
+        &self.streams_info
+    }
+
+    /// Returns the warnings collected while parsing the playlist.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Returns the number of streams in the snapshot.
+    pub fn len(&self) -> usize {
+        self.streams_info.len()
+    }
+
+    /// Returns `true` if the snapshot holds no stream.
+    pub fn is_empty(&self) -> bool {
+        self.streams_info.is_empty()
+    }
+
+    /// Retrieves the stream information in JSON format using the given serialization options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - A `JsonOptions` struct describing how the stream information is serialized.
+    ///
+    /// # Returns
+    ///
+    /// A `serde_json::Result<String>` representing the JSON output.
+    ///
+    pub fn get_json_with_options(&self, options: &JsonOptions) -> serde_json::Result<String> {
+        let options = JsonOptions {
+            skip_empty: options.skip_empty || !self.enforce_schema,
+            ..options.clone()
+        };
+        json::to_string(&*self.streams_info, &options)
+    }
+
+    /// Retrieves the stream information as an M3U playlist.
+    pub fn get_m3u_content(&self) -> String {
+        render_m3u(&self.streams_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlaylistSnapshot;
+    use crate::{Info, JsonOptions};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_playlist_snapshot() {
+        let info = Info {
+            title: String::from("BBC One"),
+            url: String::from("http://example.com/bbc.m3u8"),
+            ..Default::default()
+        };
+        let snapshot = Arc::new(PlaylistSnapshot::new(vec![info], vec![], false));
+
+        let shared = Arc::clone(&snapshot);
+        let handle = std::thread::spawn(move || shared.get_m3u_content());
+        assert_eq!(
+            handle.join().unwrap(),
+            "#EXTM3U\n#EXTINF:-1,BBC One\nhttp://example.com/bbc.m3u8"
+        );
+
+        assert_eq!(snapshot.len(), 1);
+        let json = snapshot
+            .get_json_with_options(&JsonOptions::default())
+            .unwrap();
+        assert!(json.starts_with(r#"[{"title":"BBC One","url":"#));
+    }
+}
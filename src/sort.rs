@@ -0,0 +1,117 @@
+use std::cmp::Ordering;
+
+/// Compare two strings using natural ordering.
+///
+/// Runs of ASCII digits are compared by their numeric value instead of character by character,
+/// so "Channel 2" sorts before "Channel 10". All other characters are compared as usual.
+///
+/// # Arguments
+///
+/// * `a` - The first string to compare.
+/// * `b` - The second string to compare.
+///
+/// # Returns
+///
+/// The `Ordering` of `a` relative to `b`.
+///
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char)) => {
+                if a_char.is_ascii_digit() && b_char.is_ascii_digit() {
+                    let a_number = take_number(&mut a_chars);
+                    let b_number = take_number(&mut b_chars);
+                    let ordering = a_number
+                        .len()
+                        .cmp(&b_number.len())
+                        .then_with(|| a_number.cmp(&b_number));
+                    if ordering != Ordering::Equal {
+                        return ordering;
+                    }
+                } else {
+                    let ordering = a_char.cmp(b_char);
+                    if ordering != Ordering::Equal {
+                        return ordering;
+                    }
+                    a_chars.next();
+                    b_chars.next();
+                }
+            }
+        }
+    }
+}
+
+/// Create a collator for the given locale.
+///
+/// # Arguments
+///
+/// * `locale` - A BCP 47 language tag such as "es", "tr" or "zh-Hans".
+/// * `numeric` - A boolean indicating whether digits are compared by their numeric value.
+///
+/// # Returns
+///
+/// A `Result` containing the collator, or an error if the locale is invalid or not supported.
+///
+#[cfg(feature = "collation")]
+pub fn new_collator(
+    locale: &str,
+    numeric: bool,
+) -> Result<icu_collator::Collator, Box<dyn std::error::Error>> {
+    use icu_collator::{Collator, CollatorOptions, Numeric};
+
+    let locale: icu_locid::Locale = locale.parse()?;
+    let mut options = CollatorOptions::new();
+    if numeric {
+        options.numeric = Some(Numeric::On);
+    }
+    Ok(Collator::try_new(&(&locale).into(), options)?)
+}
+
+/// Consume a run of digits and return it without leading zeros.
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        if !(number.is_empty() && *c == '0') {
+            number.push(*c);
+        }
+        chars.next();
+    }
+    number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::natural_cmp;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("Channel 2", "Channel 10"), Ordering::Less);
+        assert_eq!(natural_cmp("Channel 10", "Channel 2"), Ordering::Greater);
+        assert_eq!(natural_cmp("Channel 02", "Channel 2"), Ordering::Equal);
+        assert_eq!(natural_cmp("abc", "abd"), Ordering::Less);
+        assert_eq!(natural_cmp("ab", "abc"), Ordering::Less);
+        assert_eq!(natural_cmp("HD 1080", "HD 720"), Ordering::Greater);
+    }
+
+    #[cfg(feature = "collation")]
+    #[test]
+    fn test_collator() {
+        let collator = super::new_collator("es", false).unwrap();
+        assert_eq!(collator.compare("Ñandú", "Oasis"), Ordering::Less);
+        assert_eq!(collator.compare("Ñandú", "Nube"), Ordering::Greater);
+        assert_eq!(collator.compare("Éxito", "Fútbol"), Ordering::Less);
+
+        let collator = super::new_collator("en", true).unwrap();
+        assert_eq!(collator.compare("Channel 2", "Channel 10"), Ordering::Less);
+    }
+}
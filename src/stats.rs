@@ -0,0 +1,45 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::{CheckErrorKind, Info};
+
+/// Struct representing aggregate counts over the stream information, returned by
+/// `M3uParser::stats`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PlaylistStats {
+    pub total: usize,
+    /// The number of streams with the "GOOD" status.
+    pub good: usize,
+    /// The number of streams with the "BAD" status.
+    pub bad: usize,
+    /// The number of streams that were checked, successfully or not.
+    pub checked: usize,
+    /// The number of failed checks by kind of failure. Failures reported without a kind, e.g. by
+    /// custom stream checkers, are counted as `CheckErrorKind::Other`.
+    pub check_errors: BTreeMap<CheckErrorKind, usize>,
+}
+
+impl PlaylistStats {
+    pub(crate) fn new(streams_info: &[Info]) -> Self {
+        let mut stats = PlaylistStats {
+            total: streams_info.len(),
+            ..Default::default()
+        };
+        for info in streams_info {
+            match info.status.as_str() {
+                "GOOD" => stats.good += 1,
+                "BAD" => stats.bad += 1,
+                _ => {}
+            }
+            let Some(check) = &info.check else {
+                continue;
+            };
+            stats.checked += 1;
+            if !check.available {
+                let kind = check.error_kind.unwrap_or(CheckErrorKind::Other);
+                *stats.check_errors.entry(kind).or_default() += 1;
+            }
+        }
+        stats
+    }
+}
@@ -0,0 +1,154 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::{hash_entry, Info, InfoKey};
+
+/// Enum representing how `M3uParser::sync_from` handles the streams edited locally.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Keep the title, logo, categories and other descriptive fields of the streams edited since
+    /// parsing, and only take their URL and status from the provider.
+    #[default]
+    KeepLocalEdits,
+    /// Take every field from the provider, discarding the local edits.
+    PreferProvider,
+}
+
+/// Struct representing a field of a locally edited stream whose value differs from the provider.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyncConflict {
+    /// The URL of the stream after the sync.
+    pub url: String,
+    pub field: InfoKey,
+    pub local: String,
+    pub provider: String,
+}
+
+/// Struct representing the outcome of `M3uParser::sync_from`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SyncReport {
+    /// The number of streams found in both playlists.
+    pub updated: usize,
+    /// The number of streams of the provider that were not in the local playlist and were added.
+    pub added: usize,
+    /// The URLs of the local streams that are no longer in the provider playlist. They are kept.
+    pub missing: Vec<String>,
+    /// The fields of edited streams that differ between the two playlists.
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// The fields describing a stream, as opposed to its location and status.
+pub(crate) const DESCRIPTIVE_KEYS: [InfoKey; 10] = [
+    InfoKey::Title,
+    InfoKey::Logo,
+    InfoKey::Category,
+    InfoKey::TvgId,
+    InfoKey::TvgName,
+    InfoKey::TvgUrl,
+    InfoKey::CountryCode,
+    InfoKey::CountryName,
+    InfoKey::LanguageCode,
+    InfoKey::LanguageName,
+];
+
+/// Returns whether the stream information was edited since it was parsed.
+fn is_edited(info: &Info) -> bool {
+    info.raw.is_some() && hash_entry(&info.to_m3u_entry()) != info.raw_hash
+}
+
+/// Returns the earliest of two optional times, ignoring the missing ones.
+fn earliest(local: Option<u64>, provider: Option<u64>) -> Option<u64> {
+    match (local, provider) {
+        (Some(local), Some(provider)) => Some(local.min(provider)),
+        (local, provider) => local.or(provider),
+    }
+}
+
+/// Updates the local streams from the provider streams, matched by `tvg-id` or else by URL.
+pub(crate) fn sync(local: &mut Vec<Info>, provider: &[Info], policy: SyncPolicy) -> SyncReport {
+    let mut by_tvg_id: HashMap<&str, usize> = HashMap::new();
+    let mut by_url: HashMap<&str, usize> = HashMap::new();
+    for (position, info) in provider.iter().enumerate() {
+        if !info.tvg.id.is_empty() {
+            by_tvg_id.entry(&info.tvg.id).or_insert(position);
+        }
+        by_url.entry(&info.url).or_insert(position);
+    }
+
+    let mut report = SyncReport::default();
+    let mut matched = vec![false; provider.len()];
+    for info in local.iter_mut() {
+        let position = match info.tvg.id.as_str() {
+            "" => None,
+            id => by_tvg_id.get(id),
+        }
+        .or_else(|| by_url.get(info.url.as_str()))
+        .copied()
+        .filter(|position| !matched[*position]);
+        let Some(position) = position else {
+            report.missing.push(info.url.clone());
+            continue;
+        };
+        matched[position] = true;
+        report.updated += 1;
+
+        let fresh = &provider[position];
+        let first_seen = earliest(info.first_seen, fresh.first_seen);
+        if !is_edited(info) {
+            *info = Info {
+                source_index: info.source_index,
+                first_seen,
+                ..fresh.clone()
+            };
+            continue;
+        }
+
+        let mut conflicts: Vec<SyncConflict> = DESCRIPTIVE_KEYS
+            .iter()
+            .filter(|key| key.value(info) != key.value(fresh))
+            .map(|key| SyncConflict {
+                url: fresh.url.clone(),
+                field: *key,
+                local: key.value(info).to_string(),
+                provider: key.value(fresh).to_string(),
+            })
+            .collect();
+        report.conflicts.append(&mut conflicts);
+        match policy {
+            SyncPolicy::KeepLocalEdits => {
+                info.url = fresh.url.clone();
+                info.status = fresh.status.clone();
+                info.check = fresh.check.clone();
+                info.resolved_url = fresh.resolved_url.clone();
+                info.duration = fresh.duration;
+                info.user_agent = fresh.user_agent.clone();
+                info.referrer = fresh.referrer.clone();
+                info.first_seen = first_seen;
+                info.last_seen = fresh.last_seen;
+            }
+            SyncPolicy::PreferProvider => {
+                *info = Info {
+                    source_index: info.source_index,
+                    first_seen,
+                    ..fresh.clone()
+                };
+            }
+        }
+    }
+
+    let next_index = local.iter().map(|info| info.source_index + 1).max();
+    let added: Vec<&Info> = provider
+        .iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(info, _)| info)
+        .collect();
+    report.added = added.len();
+    for (source_index, info) in (next_index.unwrap_or_default()..).zip(added) {
+        local.push(Info {
+            source_index,
+            ..info.clone()
+        });
+    }
+    report
+}
@@ -0,0 +1,225 @@
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+use crate::{Info, InfoKey};
+
+/// A piece of a parsed template.
+#[derive(Debug, PartialEq)]
+enum Node {
+    Text(String),
+    Field(String),
+    /// Renders `then` if the field is not empty, or `otherwise` if it is, or the reverse when
+    /// negated.
+    If {
+        field: String,
+        negated: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+enum Token {
+    Text(String),
+    Tag(String),
+}
+
+/// Template rendering each stream as text, e.g. to write HTML channel pages or TVHeadend
+/// configurations.
+///
+/// `{title}`, `{url}`, `{tvg.id}` and the other fields are replaced by their value, and
+/// `{if tvg.id}...{else}...{end}` renders its content if the field is not empty, or the
+/// `{else}` part otherwise. `{if !logo}` negates the condition. `{{` and `}}` are written as `{`
+/// and `}`.
+#[derive(Debug)]
+pub(crate) struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// Parses the template, failing on unknown fields and unbalanced conditionals.
+    pub(crate) fn parse(template: &str) -> Result<Self, String> {
+        let mut tokens = tokenize(template)?.into_iter().peekable();
+        match parse_nodes(&mut tokens)? {
+            (nodes, None) => Ok(Template { nodes }),
+            (_, Some(tag)) => Err(format!("Template: unexpected {{{}}}", tag)),
+        }
+    }
+
+    /// Renders the template for the stream at the given position.
+    pub(crate) fn render(&self, info: &Info, index: usize) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, info, index, &mut output);
+        output
+    }
+}
+
+fn tokenize(template: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut tag = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => tag.push(c),
+                        None => return Err(format!("Template: unclosed {{{}", tag)),
+                    }
+                }
+                if !text.is_empty() {
+                    tokens.push(Token::Text(std::mem::take(&mut text)));
+                }
+                tokens.push(Token::Tag(tag.trim().to_string()));
+            }
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    Ok(tokens)
+}
+
+/// Parses the nodes up to the end of the template or to an `{else}` or `{end}` tag, which is
+/// returned along with them.
+fn parse_nodes(
+    tokens: &mut Peekable<IntoIter<Token>>,
+) -> Result<(Vec<Node>, Option<String>), String> {
+    let mut nodes = vec![];
+    while let Some(token) = tokens.next() {
+        let tag = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text));
+                continue;
+            }
+            Token::Tag(tag) => tag,
+        };
+        if tag == "else" || tag == "end" {
+            return Ok((nodes, Some(tag)));
+        }
+        let Some(condition) = tag.strip_prefix("if ") else {
+            check_field(&tag)?;
+            nodes.push(Node::Field(tag));
+            continue;
+        };
+
+        let condition = condition.trim();
+        let (negated, field) = match condition.strip_prefix('!') {
+            Some(field) => (true, field.trim()),
+            None => (false, condition),
+        };
+        check_field(field)?;
+        let (then, end) = parse_nodes(tokens)?;
+        let otherwise = match end.as_deref() {
+            Some("else") => match parse_nodes(tokens)? {
+                (otherwise, Some(end)) if end == "end" => otherwise,
+                _ => return Err(format!("Template: unclosed {{if {}}}", condition)),
+            },
+            Some(_) => vec![],
+            None => return Err(format!("Template: unclosed {{if {}}}", condition)),
+        };
+        nodes.push(Node::If {
+            field: field.to_string(),
+            negated,
+            then,
+            otherwise,
+        });
+    }
+    Ok((nodes, None))
+}
+
+fn check_field(field: &str) -> Result<(), String> {
+    match field_value(&Info::default(), 0, field) {
+        Some(_) => Ok(()),
+        None => Err(format!("Template: unknown field {{{}}}", field)),
+    }
+}
+
+/// Returns the value of the field of the stream, or `None` if there is no such field.
+fn field_value(info: &Info, index: usize, field: &str) -> Option<String> {
+    let value = match field {
+        "index" => index.to_string(),
+        "display_title" => info.display_title(),
+        "tags" => info.tags_attribute(),
+        "comment" => info.comment.clone().unwrap_or_default(),
+        "duration" => info
+            .duration
+            .map(|duration| duration.to_string())
+            .unwrap_or_default(),
+        field => match field.split_once('.') {
+            Some(("extra", name)) => info.extra_attributes.get(name).cloned().unwrap_or_default(),
+            Some((key_0, key_1)) => InfoKey::from_keys(key_0, key_1)?.value(info).to_string(),
+            None => InfoKey::from_keys(field, "")?.value(info).to_string(),
+        },
+    };
+    Some(value)
+}
+
+fn render_nodes(nodes: &[Node], info: &Info, index: usize, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Field(field) => {
+                output.push_str(&field_value(info, index, field).unwrap_or_default())
+            }
+            Node::If {
+                field,
+                negated,
+                then,
+                otherwise,
+            } => {
+                let is_set = !field_value(info, index, field)
+                    .unwrap_or_default()
+                    .is_empty();
+                let branch = if is_set != *negated { then } else { otherwise };
+                render_nodes(branch, info, index, output);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Template;
+    use crate::{Info, Tvg};
+
+    #[test]
+    fn test_template() {
+        let info = Info {
+            title: String::from("BBC One"),
+            url: String::from("http://example.com/bbc.m3u8"),
+            tvg: Tvg {
+                id: String::from("bbcone.uk"),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let template =
+            Template::parse("{index}: {title} <{url}>{if tvg.id} [{tvg.id}]{end}\n").unwrap();
+        assert_eq!(
+            template.render(&info, 3),
+            "3: BBC One <http://example.com/bbc.m3u8> [bbcone.uk]\n"
+        );
+
+        let template =
+            Template::parse("{{ {if logo}<img src=\"{logo}\">{else}{title}{end} }}").unwrap();
+        assert_eq!(template.render(&info, 0), "{ BBC One }");
+        let template = Template::parse("{if !country.code}unknown{end}").unwrap();
+        assert_eq!(template.render(&info, 0), "unknown");
+
+        assert!(Template::parse("{nope}").is_err());
+        assert!(Template::parse("{if title}open").is_err());
+        assert!(Template::parse("{end}").is_err());
+        assert!(Template::parse("{title").is_err());
+    }
+}
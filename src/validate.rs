@@ -0,0 +1,131 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use url::Url;
+
+use crate::{Info, ParseWarning, ParseWarningReason};
+
+/// Enum representing the kinds of problems reported by `M3uParser::validate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ValidationIssueKind {
+    /// The entry has no title.
+    MissingTitle,
+    /// The entry has no `tvg-id`.
+    EmptyTvgId,
+    /// The URL of the entry was already used by a previous entry.
+    DuplicateUrl,
+    /// The `tvg-logo` of the entry is not a valid URL.
+    MalformedLogoUrl,
+    /// The `tvg-country` of the entry is not a known ISO 3166-1 alpha-2 code.
+    UnknownCountryCode,
+    /// The `#EXTINF` line could not be turned into an entry, e.g. because no URL follows it.
+    UnparsedEntry,
+}
+
+/// Struct representing a problem found in the playlist.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    pub kind: ValidationIssueKind,
+    /// The line of the `#EXTINF` line of the entry in the source playlist, starting at 1.
+    pub line_number: Option<usize>,
+    pub message: String,
+}
+
+/// Validate the stream information and the entries skipped while parsing.
+///
+/// # Arguments
+///
+/// * `streams_info` - The stream information to validate.
+/// * `warnings` - The warnings collected while parsing the playlist.
+///
+/// # Returns
+///
+/// A `Vec<ValidationIssue>` ordered by line number.
+///
+pub fn validate(streams_info: &[Info], warnings: &[ParseWarning]) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut seen_urls: HashMap<&str, usize> = HashMap::new();
+
+    for info in streams_info {
+        let mut report = |kind, message: String| {
+            issues.push(ValidationIssue {
+                kind,
+                line_number: Some(info.line_number),
+                message,
+            })
+        };
+
+        if info.title.is_empty() {
+            report(
+                ValidationIssueKind::MissingTitle,
+                format!("Entry for {} has no title", info.url),
+            );
+        }
+        if info.tvg.id.is_empty() {
+            report(
+                ValidationIssueKind::EmptyTvgId,
+                format!("Entry '{}' has no tvg-id", info.title),
+            );
+        }
+        match seen_urls.get(info.url.as_str()) {
+            Some(first_line) => report(
+                ValidationIssueKind::DuplicateUrl,
+                format!("URL {} was already used on line {}", info.url, first_line),
+            ),
+            None => {
+                seen_urls.insert(&info.url, info.line_number);
+            }
+        }
+        if !info.logo.is_empty() && Url::parse(&info.logo).is_err() {
+            report(
+                ValidationIssueKind::MalformedLogoUrl,
+                format!(
+                    "Logo '{}' of entry '{}' is not a valid URL",
+                    info.logo, info.title
+                ),
+            );
+        }
+        let unknown_codes: Vec<&str> = info
+            .country
+            .code
+            .split(';')
+            .map(|code| code.trim())
+            .filter(|code| !code.is_empty() && !is_known_country(code))
+            .collect();
+        if !unknown_codes.is_empty() {
+            report(
+                ValidationIssueKind::UnknownCountryCode,
+                format!(
+                    "Country code(s) {} of entry '{}' are unknown",
+                    unknown_codes.join(", "),
+                    info.title
+                ),
+            );
+        }
+    }
+
+    for warning in warnings {
+        if warning.reason == ParseWarningReason::MissingUrl {
+            issues.push(ValidationIssue {
+                kind: ValidationIssueKind::UnparsedEntry,
+                line_number: Some(warning.line_number),
+                message: String::from("#EXTINF line is not followed by a valid URL or path"),
+            });
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.line_number);
+    issues
+}
+
+/// Returns whether the code is a known ISO 3166-1 alpha-2 country code.
+#[cfg(feature = "geo")]
+fn is_known_country(code: &str) -> bool {
+    celes::Country::from_alpha2(code).is_ok()
+}
+
+/// Returns whether the code looks like an ISO 3166-1 alpha-2 country code. Without the `geo`
+/// feature the list of countries is not available, so any two letters are accepted.
+#[cfg(not(feature = "geo"))]
+fn is_known_country(code: &str) -> bool {
+    code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic())
+}
@@ -0,0 +1,113 @@
+use futures::executor::block_on;
+use wasm_bindgen::prelude::*;
+
+use crate::M3uParser;
+
+/// Parses the content of an M3U playlist and returns its stream information as an array of
+/// objects, as serialized by `get_json`.
+///
+/// # Arguments
+///
+/// * `content` - The content of the M3U playlist.
+///
+/// # Returns
+///
+/// The array of stream information, or an error if it cannot be serialized.
+///
+#[wasm_bindgen(js_name = parseM3u)]
+pub fn parse_m3u(content: &str) -> Result<JsValue, JsValue> {
+    let mut parser = WasmM3uParser::new();
+    parser.parse_content(content, true);
+    parser.streams()
+}
+
+/// JavaScript class wrapping `M3uParser`, for the operations on the stream information.
+///
+/// Streams are not checked, as the parser cannot make requests from WebAssembly; only the content
+/// of a playlist, e.g. downloaded with `fetch`, can be parsed.
+#[wasm_bindgen(js_name = M3uParser)]
+pub struct WasmM3uParser {
+    parser: M3uParser,
+}
+
+#[wasm_bindgen(js_class = M3uParser)]
+impl WasmM3uParser {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmM3uParser {
+        WasmM3uParser {
+            parser: M3uParser::new(None),
+        }
+    }
+
+    /// Parses the content of an M3U playlist, replacing the current stream information.
+    #[wasm_bindgen(js_name = parseContent)]
+    pub fn parse_content(&mut self, content: &str, enforce_schema: bool) {
+        // Without checks, parsing never waits, so the future completes on the first poll.
+        block_on(
+            self.parser
+                .parse_m3u_content(content, false, enforce_schema),
+        );
+    }
+
+    /// Filters the stream information, see `M3uParser::filter_by`.
+    #[wasm_bindgen(js_name = filterBy)]
+    pub fn filter_by(
+        &mut self,
+        key: &str,
+        filters: Vec<String>,
+        key_splitter: &str,
+        retrieve: bool,
+        nested_key: bool,
+    ) {
+        let filters = filters.iter().map(String::as_str).collect();
+        self.parser
+            .filter_by(key, filters, key_splitter, retrieve, nested_key);
+    }
+
+    /// Sorts the stream information, see `M3uParser::sort_by`.
+    #[wasm_bindgen(js_name = sortBy)]
+    pub fn sort_by(&mut self, key: &str, key_splitter: &str, asc: bool, nested_key: bool) {
+        self.parser.sort_by(key, key_splitter, asc, nested_key);
+    }
+
+    /// Restores the stream information as parsed, undoing filters and sorting.
+    #[wasm_bindgen(js_name = resetOperations)]
+    pub fn reset_operations(&mut self) {
+        self.parser.reset_operations();
+    }
+
+    /// Returns the number of streams.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.parser.streams_info.len()
+    }
+
+    /// Returns the stream information as an array of objects.
+    pub fn streams(&self) -> Result<JsValue, JsValue> {
+        let content = self
+            .parser
+            .get_json(false)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        js_sys::JSON::parse(&content)
+    }
+
+    /// Returns the stream information as JSON.
+    #[wasm_bindgen(js_name = getJson)]
+    pub fn get_json(&self, pretty: bool) -> Result<String, JsValue> {
+        self.parser
+            .get_json(pretty)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Returns the stream information as an M3U playlist.
+    #[wasm_bindgen(js_name = toM3u)]
+    pub fn to_m3u(&self) -> String {
+        self.parser.get_m3u_content()
+    }
+}
+
+impl Default for WasmM3uParser {
+    fn default() -> Self {
+        WasmM3uParser::new()
+    }
+}
@@ -0,0 +1,403 @@
+use once_cell::sync::Lazy;
+use std::io::Write;
+
+use crate::Info;
+
+/// Enum representing an attribute of the `#EXTINF` line written by the M3U writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum M3uAttribute {
+    TvgId,
+    TvgName,
+    TvgUrl,
+    TvgLogo,
+    TvgCountry,
+    TvgLanguage,
+    GroupTitle,
+    /// The tags of the entry, as `x-tags`.
+    Tags,
+    /// When the entry was last checked, as an RFC 3339 UTC timestamp in `x-checked-at`. Not
+    /// written by default, since it changes with every check; add it to
+    /// `M3uWriteOptions.attributes` to write it.
+    CheckedAt,
+}
+
+impl M3uAttribute {
+    /// The attributes written by default, in order.
+    pub const ALL: [M3uAttribute; 8] = [
+        M3uAttribute::TvgId,
+        M3uAttribute::TvgName,
+        M3uAttribute::TvgUrl,
+        M3uAttribute::TvgLogo,
+        M3uAttribute::TvgCountry,
+        M3uAttribute::TvgLanguage,
+        M3uAttribute::GroupTitle,
+        M3uAttribute::Tags,
+    ];
+
+    /// Returns the name of the attribute as written in the playlist, e.g. "tvg-id".
+    pub fn name(&self) -> &'static str {
+        match self {
+            M3uAttribute::TvgId => "tvg-id",
+            M3uAttribute::TvgName => "tvg-name",
+            M3uAttribute::TvgUrl => "tvg-url",
+            M3uAttribute::TvgLogo => "tvg-logo",
+            M3uAttribute::TvgCountry => "tvg-country",
+            M3uAttribute::TvgLanguage => "tvg-language",
+            M3uAttribute::GroupTitle => "group-title",
+            M3uAttribute::Tags => "x-tags",
+            M3uAttribute::CheckedAt => "x-checked-at",
+        }
+    }
+
+    fn value(&self, info: &Info) -> String {
+        match self {
+            M3uAttribute::TvgId => info.tvg.id.clone(),
+            M3uAttribute::TvgName => info.tvg.name.clone(),
+            M3uAttribute::TvgUrl => info.tvg.url.clone(),
+            M3uAttribute::TvgLogo => info.logo.clone(),
+            M3uAttribute::TvgCountry => info.country.code.clone(),
+            M3uAttribute::TvgLanguage => info.language.name.clone(),
+            M3uAttribute::GroupTitle => info.group_title(),
+            M3uAttribute::Tags => info.tags_attribute(),
+            M3uAttribute::CheckedAt => info
+                .check
+                .as_ref()
+                .map(|check| check.checked_at_utc())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Struct representing the options used to write the stream information as an M3U playlist.
+///
+/// Players differ in the dialect they accept; the default options write every attribute in the
+/// order of `M3uAttribute::ALL`, with `\n` line endings and a bare `#EXTM3U` header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct M3uWriteOptions {
+    /// The attributes written in the `#EXTINF` lines, in order. Attributes with an empty value are
+    /// always skipped.
+    pub attributes: Vec<M3uAttribute>,
+    /// The attributes of the `#EXTM3U` header line as `(name, value)` pairs, e.g. `url-tvg` with
+    /// the URL of the EPG.
+    pub header_attributes: Vec<(String, String)>,
+    /// Whether to end lines with `\r\n` rather than `\n`.
+    pub crlf: bool,
+    /// Whether to write the group in an `#EXTGRP` directive rather than in the `group-title`
+    /// attribute.
+    pub group_directive: bool,
+    /// Whether to prefix the titles with the flag emoji of their country, as `Info::display_title`
+    /// does.
+    pub flag_titles: bool,
+    /// The guide number of the first entry, written along with the following ones in the
+    /// `tvg-chno` and `channel-id` attributes, or `None` to not number the entries.
+    pub channel_numbers: Option<usize>,
+    /// Whether to remove the quotes, commas and control characters from the titles and attribute
+    /// values, which some players fail to parse.
+    pub sanitize: bool,
+    /// The maximum number of entries written, e.g. the number of channels a tuner supports.
+    pub max_entries: Option<usize>,
+    /// Whether to give the entries without `tvg-id` the id generated from their title, under which
+    /// `M3uParser::xmltv_stub` lists them, so that they can be mapped to a guide.
+    pub stub_ids: bool,
+}
+
+impl Default for M3uWriteOptions {
+    fn default() -> Self {
+        M3uWriteOptions {
+            attributes: M3uAttribute::ALL.to_vec(),
+            header_attributes: vec![],
+            crlf: false,
+            group_directive: false,
+            flag_titles: false,
+            channel_numbers: None,
+            sanitize: false,
+            max_entries: None,
+            stub_ids: false,
+        }
+    }
+}
+
+impl M3uWriteOptions {
+    /// Returns the options without the given attribute, e.g. to leave out `tvg-url`.
+    pub fn without(mut self, attribute: M3uAttribute) -> Self {
+        self.attributes.retain(|written| *written != attribute);
+        self
+    }
+
+    /// Returns the options producing the dialect expected by the Live TV tuners of Plex and
+    /// Jellyfin: entries numbered from 1 in `tvg-chno` and `channel-id`, sanitized names, at most
+    /// 480 channels and generated ids for the channels without `tvg-id`, to be matched with the
+    /// guide of `M3uParser::xmltv_stub`.
+    pub fn media_server() -> Self {
+        M3uWriteOptions {
+            attributes: vec![
+                M3uAttribute::TvgId,
+                M3uAttribute::TvgName,
+                M3uAttribute::TvgLogo,
+                M3uAttribute::GroupTitle,
+            ],
+            channel_numbers: Some(1),
+            sanitize: true,
+            max_entries: Some(480),
+            stub_ids: true,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the entries written with these options.
+    pub(crate) fn limit<'a>(&self, streams_info: &'a [Info]) -> &'a [Info] {
+        match self.max_entries {
+            Some(max_entries) => &streams_info[..streams_info.len().min(max_entries)],
+            None => streams_info,
+        }
+    }
+}
+
+/// Removes the quotes and control characters from the value, replaces its commas by spaces and
+/// collapses its whitespace.
+fn sanitize(value: &str) -> String {
+    let value: String = value
+        .chars()
+        .filter(|c| *c != '"' && !c.is_control())
+        .map(|c| if c == ',' { ' ' } else { c })
+        .collect();
+    value.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Returns the id of the entry in the guide of `M3uParser::xmltv_stub`: its `tvg-id`, or else an
+/// id generated from its title, such as "bbc-one.stub".
+pub(crate) fn stub_id(info: &Info) -> String {
+    if !info.tvg.id.is_empty() {
+        return info.tvg.id.clone();
+    }
+    let slug: String = crate::search::normalize(&info.title)
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    let slug = slug.split_whitespace().collect::<Vec<&str>>().join("-");
+    format!("{}.stub", if slug.is_empty() { "channel" } else { &slug })
+}
+
+pub(crate) static DEFAULT_OPTIONS: Lazy<M3uWriteOptions> = Lazy::new(M3uWriteOptions::default);
+
+/// Renders the entry at the given position as its `# note:` lines and `#EXTINF` line, followed by
+/// its directives, the URL line and the lines of the backup URLs.
+pub(crate) fn render_entry(info: &Info, options: &M3uWriteOptions, position: usize) -> String {
+    let duration = match info.duration {
+        Some(duration) => duration.to_string(),
+        None => String::from("-1"),
+    };
+    let mut line = format!("#EXTINF:{}", duration);
+    let clean = |value: String| match options.sanitize {
+        true => sanitize(&value),
+        false => value,
+    };
+
+    if let Some(first) = options.channel_numbers {
+        let number = first + position;
+        line.push_str(&format!(
+            " tvg-chno=\"{}\" channel-id=\"{}\"",
+            number, number
+        ));
+    }
+    for attribute in &options.attributes {
+        if options.group_directive && *attribute == M3uAttribute::GroupTitle {
+            continue;
+        }
+        let value = match attribute {
+            M3uAttribute::TvgId if options.stub_ids => stub_id(info),
+            attribute => clean(attribute.value(info)),
+        };
+        if !value.is_empty() {
+            line.push_str(&format!(" {}=\"{}\"", attribute.name(), value));
+        }
+    }
+
+    let title = match options.flag_titles {
+        true => clean(info.display_title()),
+        false => clean(info.title.clone()),
+    };
+    if !title.is_empty() {
+        line.push(',');
+        line.push_str(&title);
+    }
+
+    let group = options.group_directive.then(|| info.group_title());
+    let directives = [
+        ("#EXTGRP:", group.as_ref().filter(|group| !group.is_empty())),
+        ("#EXTALB:", info.album.as_ref()),
+        ("#EXTART:", info.artist.as_ref()),
+        ("#EXTIMG:", info.image.as_ref()),
+        ("#EXTVLCOPT:http-user-agent=", info.user_agent.as_ref()),
+        ("#EXTVLCOPT:http-referrer=", info.referrer.as_ref()),
+    ];
+    for (directive, value) in directives {
+        if let Some(value) = value {
+            line.push_str(&format!("\n{}{}", directive, value));
+        }
+    }
+
+    let mut entry = format!("{}{}\n{}", info.note_lines(), line, info.url);
+    for backup_url in &info.backup_urls {
+        entry.push('\n');
+        entry.push_str(backup_url);
+    }
+    match options.crlf {
+        true => entry.replace('\n', "\r\n"),
+        false => entry,
+    }
+}
+
+/// Renders the `#EXTM3U` header line.
+pub(crate) fn render_header(options: &M3uWriteOptions) -> String {
+    let mut header = String::from("#EXTM3U");
+    for (name, value) in &options.header_attributes {
+        header.push_str(&format!(" {}=\"{}\"", name, value));
+    }
+    header
+}
+
+/// Renders the stream information as an M3U playlist.
+pub(crate) fn render_m3u(streams_info: &[Info], options: &M3uWriteOptions) -> String {
+    if streams_info.is_empty() {
+        return String::new();
+    }
+
+    let line_ending = if options.crlf { "\r\n" } else { "\n" };
+    let mut content = vec![render_header(options)];
+    content.extend(
+        options
+            .limit(streams_info)
+            .iter()
+            .enumerate()
+            .map(|(position, stream_info)| render_entry(stream_info, options, position)),
+    );
+    content.join(line_ending)
+}
+
+/// Writes the stream information as an M3U playlist entry by entry, producing the same output as
+/// `render_m3u` without holding it in memory.
+pub(crate) fn write_m3u<W: Write>(
+    writer: &mut W,
+    streams_info: &[Info],
+    options: &M3uWriteOptions,
+) -> std::io::Result<()> {
+    if streams_info.is_empty() {
+        return Ok(());
+    }
+
+    writer.write_all(render_header(options).as_bytes())?;
+    write_entries(writer, streams_info, options, true, 0).map(|_| ())
+}
+
+/// Writes the entries of the stream information, separated by line endings.
+///
+/// With `separate_first`, a line ending is also written before the first entry, which follows the
+/// header or another entry. The entries are numbered from `first_position`, the number of entries
+/// written before them. Returns the number of entries written.
+pub(crate) fn write_entries<W: Write>(
+    writer: &mut W,
+    streams_info: &[Info],
+    options: &M3uWriteOptions,
+    separate_first: bool,
+    first_position: usize,
+) -> std::io::Result<usize> {
+    let line_ending = if options.crlf { "\r\n" } else { "\n" };
+    let streams_info = options.limit(streams_info);
+    for (position, stream_info) in streams_info.iter().enumerate() {
+        if position > 0 || separate_first {
+            writer.write_all(line_ending.as_bytes())?;
+        }
+        let position = first_position + position;
+        writer.write_all(render_entry(stream_info, options, position).as_bytes())?;
+    }
+    Ok(streams_info.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_m3u, sanitize, write_entries, write_m3u, M3uAttribute, M3uWriteOptions};
+    use crate::{Info, Tvg};
+
+    #[test]
+    fn test_render_m3u() {
+        let info = Info {
+            title: String::from("BBC One"),
+            url: String::from("http://example.com/bbc.m3u8"),
+            category: String::from("News"),
+            tvg: Tvg {
+                id: String::from("bbcone.uk"),
+                url: String::from("http://example.com/epg.xml"),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let streams_info = vec![info];
+        assert_eq!(
+            render_m3u(&streams_info, &M3uWriteOptions::default()),
+            "#EXTM3U\n#EXTINF:-1 tvg-id=\"bbcone.uk\" tvg-url=\"http://example.com/epg.xml\" \
+             group-title=\"News\",BBC One\nhttp://example.com/bbc.m3u8"
+        );
+
+        let options = M3uWriteOptions {
+            attributes: vec![M3uAttribute::GroupTitle, M3uAttribute::TvgId],
+            header_attributes: vec![(
+                String::from("url-tvg"),
+                String::from("http://example.com/epg.xml"),
+            )],
+            crlf: true,
+            group_directive: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            render_m3u(&streams_info, &options),
+            "#EXTM3U url-tvg=\"http://example.com/epg.xml\"\r\n\
+             #EXTINF:-1 tvg-id=\"bbcone.uk\",BBC One\r\n#EXTGRP:News\r\nhttp://example.com/bbc.m3u8"
+        );
+
+        let mut output = vec![];
+        write_m3u(&mut output, &streams_info, &options).unwrap();
+        assert_eq!(output, render_m3u(&streams_info, &options).into_bytes());
+
+        let options = M3uWriteOptions::default().without(M3uAttribute::TvgUrl);
+        assert!(!render_m3u(&streams_info, &options).contains("tvg-url"));
+        assert_eq!(render_m3u(&[], &options), "");
+    }
+
+    #[test]
+    fn test_media_server_profile() {
+        let info = |title: &str, id: &str| Info {
+            title: title.to_string(),
+            url: format!("http://example.com/{}.m3u8", id),
+            tvg: Tvg {
+                id: id.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let streams_info = vec![
+            info("BBC One", "bbcone.uk"),
+            info("Télé \"Matin\", Live", ""),
+            info("Extra", "extra"),
+        ];
+        let options = M3uWriteOptions {
+            max_entries: Some(2),
+            ..M3uWriteOptions::media_server()
+        };
+        assert_eq!(
+            render_m3u(&streams_info, &options),
+            "#EXTM3U\n\
+             #EXTINF:-1 tvg-chno=\"1\" channel-id=\"1\" tvg-id=\"bbcone.uk\",BBC One\n\
+             http://example.com/bbcone.uk.m3u8\n\
+             #EXTINF:-1 tvg-chno=\"2\" channel-id=\"2\" tvg-id=\"tele-matin-live.stub\",Télé Matin Live\n\
+             http://example.com/.m3u8"
+        );
+
+        let mut output = vec![];
+        assert_eq!(
+            write_entries(&mut output, &streams_info, &options, false, 0).unwrap(),
+            2
+        );
+        assert_eq!(sanitize(" a,\tb\"c "), "a bc");
+    }
+}